@@ -0,0 +1,123 @@
+//! Use cargo-msrv as a library.
+//!
+//! Each entry point takes a [`Config`], which can be built with a [`ConfigBuilder`], and returns
+//! a typed report instead of printing its result. Progress of long running actions, like
+//! installing and checking toolchains, is reported to the given [`Output`]; use [`NoOutput`] to
+//! not report any progress.
+//!
+//! ```no_run
+//! use cargo_msrv::api;
+//! use cargo_msrv::config::{ConfigBuilder, ModeIntent};
+//! use cargo_msrv::reporter::no_output::NoOutput;
+//!
+//! let config = ConfigBuilder::new(ModeIntent::Find, "x86_64-unknown-linux-gnu")
+//!     .crate_path(Some("path/to/crate"))
+//!     .build();
+//!
+//! let report = api::find(&config, &NoOutput).unwrap();
+//! ```
+//!
+//! [`ConfigBuilder`]: crate::config::ConfigBuilder
+//! [`NoOutput`]: crate::reporter::no_output::NoOutput
+
+use rust_releases::ReleaseIndex;
+
+use crate::config::Config;
+use crate::dependencies::resolver::{CargoMetadataResolver, DependencyResolver};
+use crate::dependencies::verify::DependenciesCompatibility;
+use crate::errors::TResult;
+use crate::manifest::bare_version::BareVersion;
+use crate::reporter::Output;
+use crate::subcommands::find::FindReport;
+use crate::subcommands::list::{list_dependencies, ListReport};
+use crate::subcommands::set::{write_msrv, SetReport};
+use crate::subcommands::show::{show_msrv, ShowReport};
+use crate::subcommands::verify::{verify_msrv, VerifyReport};
+
+/// Fetch the index of Rust releases from the release source of the configuration.
+///
+/// The index can be reused for several actions, by calling [`find_msrv`], or
+/// [`run_verify_msrv_action`] directly.
+///
+/// [`find_msrv`]: crate::find_msrv
+/// [`run_verify_msrv_action`]: crate::run_verify_msrv_action
+pub fn fetch_index(config: &Config) -> TResult<ReleaseIndex> {
+    crate::fetch_index(config)
+}
+
+/// Find the MSRV of the crate, or, like `cargo msrv`, the MSRV of each workspace member, feature
+/// combination or target when the configuration asks for it.
+pub fn find(config: &Config, output: &impl Output) -> TResult<FindReport> {
+    let index = fetch_index(config)?;
+
+    crate::subcommands::find::find(config, output, &index)
+}
+
+/// Check whether the crate is compatible with the MSRV specified in its Cargo manifest.
+///
+/// Unlike `cargo msrv verify`, an incompatible MSRV is not an error, but is reported by
+/// [`VerifyReport::is_satisfied`].
+pub fn verify(config: &Config, output: &impl Output) -> TResult<VerifyReport> {
+    let index = fetch_index(config)?;
+
+    verify_msrv(config, output, &index)
+}
+
+/// List the dependencies of the crate, with the MSRV specified by their authors.
+///
+/// The dependencies of the kinds given by the [`ListCmdConfig`] of the configuration are listed,
+/// or else its normal and build dependencies.
+///
+/// [`ListCmdConfig`]: crate::config::list::ListCmdConfig
+pub fn list(config: &Config) -> TResult<ListReport> {
+    list_dependencies(config)
+}
+
+/// Check the MSRV of each dependency of the crate, by building each dependency by itself, and
+/// compare it to the MSRV of the crate.
+pub fn verify_dependencies(
+    config: &Config,
+    output: &impl Output,
+) -> TResult<DependenciesCompatibility> {
+    let index = fetch_index(config)?;
+    let graph = CargoMetadataResolver::try_from_config(config)?.resolve()?;
+    let crate_msrv = show(config)?.msrv().map(BareVersion::to_semver_version);
+
+    crate::dependencies::verify::verify_dependencies(config, output, &index, &graph, crate_msrv)
+}
+
+/// Read the MSRV of the crate from its Cargo manifest.
+pub fn show(config: &Config) -> TResult<ShowReport> {
+    show_msrv(config)
+}
+
+/// Write the given MSRV to the Cargo manifest of the crate.
+pub fn set(config: &Config, msrv: &BareVersion) -> TResult<SetReport> {
+    write_msrv(config, msrv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::show;
+    use crate::config::{ConfigBuilder, ModeIntent};
+    use crate::manifest::bare_version::BareVersion;
+    use std::path::Path;
+
+    #[yare::parameterized(
+        rust_version = { "1.56.0-edition-2021", Some(BareVersion::ThreeComponents(1, 56, 0)) },
+        metadata_msrv = { "1.36.0", Some(BareVersion::TwoComponents(1, 36)) },
+        unspecified = { "1.38.0", None },
+    )]
+    fn show_fixture_msrv(fixture: &str, expected: Option<BareVersion>) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join(fixture);
+
+        let config = ConfigBuilder::new(ModeIntent::Show, "x86_64-unknown-linux-gnu")
+            .crate_path(Some(path))
+            .build();
+
+        assert_eq!(show(&config).unwrap().msrv(), expected.as_ref());
+    }
+}
@@ -80,6 +80,10 @@ fn init_and_run(config: &Config) -> TResult<()> {
         "initializing"
     );
 
+    for option in config::file::CONFIGURABLE_OPTIONS {
+        tracing::debug!(option, source = %config.value_source(option), "configured");
+    }
+
     match config.output_format() {
         config::OutputFormat::Human => {
             let custom_cmd = config.check_command_string();
@@ -97,6 +101,31 @@ fn init_and_run(config: &Config) -> TResult<()> {
                 reporter::json::JsonPrinter::new(1, config.target(), custom_cmd.as_deref());
             run_app(config, &reporter)
         }
+        config::OutputFormat::Junit => {
+            let custom_cmd = config.check_command_string();
+            let reporter = reporter::junit::JunitPrinter::new(config.target(), Some(&custom_cmd));
+            let result = run_app(config, &reporter);
+
+            // The report is a single document, so it's printed once the run has completed, also
+            // when it failed.
+            print!("{}", reporter.report());
+            result
+        }
+        config::OutputFormat::Sarif => {
+            let custom_cmd = config.check_command_string();
+            let manifest = match config.crate_path() {
+                Some(path) => path.join("Cargo.toml"),
+                None => std::env::current_dir()
+                    .map(|folder| folder.join("Cargo.toml"))
+                    .unwrap_or_else(|_| "Cargo.toml".into()),
+            };
+
+            let reporter = reporter::sarif::SarifPrinter::new(manifest, &custom_cmd);
+            let result = run_app(config, &reporter);
+
+            println!("{}", reporter.report());
+            result
+        }
         config::OutputFormat::None => {
             // To disable regular output. Useful when outputting logs to stdout, as the
             //   regular output and the log output may otherwise interfere with each other.
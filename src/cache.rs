@@ -0,0 +1,585 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use toml_edit::{Document, Item, TableLike};
+
+use crate::check::uses_json_diagnostics;
+use crate::config::Config;
+use crate::diagnose::CompilerDiagnostic;
+use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
+use crate::lockfile::CARGO_LOCK;
+use crate::manifest::DEPENDENCY_TABLES;
+use crate::outcome::Outcome;
+use crate::paths::crate_root_folder;
+use crate::toolchain::ToolchainSpec;
+
+const CACHE_FOLDER: &str = "checks";
+
+/// How long a failed check outcome remains valid. A check may fail for reasons which have nothing
+/// to do with the crate, like a network or disk failure, so unlike successes, failures are
+/// checked again after a while.
+const FAILURE_EXPIRY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// An on-disk cache of the outcomes of previously run checks.
+///
+/// Each outcome is stored in its own file, named after a digest of the toolchain, the check
+/// command and a fingerprint of the crate sources. When any of these change, the cached outcome
+/// will no longer be found, and the check will have to run again. Failed outcomes also expire
+/// after a day.
+#[derive(Debug)]
+pub struct CheckCache {
+    folder: PathBuf,
+}
+
+impl CheckCache {
+    pub fn new(folder: PathBuf) -> Self {
+        Self { folder }
+    }
+
+    /// Use the cache folder given by the config, or the default cache folder otherwise.
+    pub fn from_config(config: &Config) -> TResult<Self> {
+        match config.cache_folder() {
+            Some(folder) => Ok(Self::new(folder.to_path_buf())),
+            None => Self::try_default(),
+        }
+    }
+
+    /// Use the default cache folder, which is located in the cargo-msrv data folder.
+    pub fn try_default() -> TResult<Self> {
+        dirs::data_local_dir()
+            .map(|path| Self::new(path.join("cargo-msrv").join(CACHE_FOLDER)))
+            .ok_or(CargoMSRVError::UnableToAccessCacheFolder)
+    }
+
+    pub fn get(&self, key: &CacheKey, toolchain: &ToolchainSpec) -> Option<Outcome> {
+        let path = self.entry_path(key);
+        let contents = std::fs::read_to_string(&path).ok()?;
+        let entry = json::parse(&contents).ok()?;
+
+        // Guard against digest collisions, and entries written by other versions of cargo-msrv
+        if entry["toolchain"].as_str() != Some(toolchain.spec())
+            || entry["check_cmd"].as_str() != Some(key.check_command.as_str())
+        {
+            return None;
+        }
+
+        if entry["success"].as_bool()? {
+            Some(Outcome::new_success(toolchain.to_owned()))
+        } else if is_expired(entry["checked_at"].as_u64()?) {
+            None
+        } else {
+            let error_message = entry["error_message"].as_str().unwrap_or_default();
+            let diagnostics = entry["diagnostics"]
+                .members()
+                .filter_map(CompilerDiagnostic::from_json)
+                .collect();
+
+            Some(Outcome::new_failure_with_diagnostics(
+                toolchain.to_owned(),
+                error_message.to_string(),
+                diagnostics,
+            ))
+        }
+    }
+
+    pub fn insert(&self, key: &CacheKey, outcome: &Outcome) -> TResult<()> {
+        self.insert_checked_at(key, outcome, SystemTime::now())
+    }
+
+    fn insert_checked_at(
+        &self,
+        key: &CacheKey,
+        outcome: &Outcome,
+        checked_at: SystemTime,
+    ) -> TResult<()> {
+        std::fs::create_dir_all(&self.folder).map_err(|error| CargoMSRVError::Io {
+            error,
+            source: IoErrorSource::CreateFolder(self.folder.clone()),
+        })?;
+
+        let (error_message, diagnostics) = match outcome {
+            Outcome::Success(_) => (None, Vec::new()),
+            Outcome::Failure(failure) => (
+                Some(failure.error_message.as_str()),
+                failure
+                    .diagnostics
+                    .iter()
+                    .map(CompilerDiagnostic::to_json)
+                    .collect(),
+            ),
+        };
+
+        let entry = json::object! {
+            toolchain: outcome.toolchain_spec().spec(),
+            check_cmd: key.check_command.as_str(),
+            success: outcome.is_success(),
+            checked_at: seconds_since_epoch(checked_at),
+            error_message: error_message,
+            diagnostics: diagnostics,
+        };
+
+        let path = self.entry_path(key);
+        std::fs::write(&path, entry.dump()).map_err(|error| CargoMSRVError::Io {
+            error,
+            source: IoErrorSource::WriteFile(path.clone()),
+        })
+    }
+
+    fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        self.folder.join(format!("{}.json", key.digest()))
+    }
+}
+
+fn seconds_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+fn is_expired(checked_at: u64) -> bool {
+    let age = seconds_since_epoch(SystemTime::now()).saturating_sub(checked_at);
+
+    age > FAILURE_EXPIRY.as_secs()
+}
+
+/// Identifies the outcome of a check.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CacheKey {
+    toolchain: String,
+    check_command: String,
+    ignore_lockfile: bool,
+    json_diagnostics: bool,
+    fingerprint: String,
+}
+
+impl CacheKey {
+    pub fn new(config: &Config, toolchain: &ToolchainSpec, fingerprint: &str) -> Self {
+        Self {
+            toolchain: toolchain.spec().to_string(),
+            check_command: config.check_command_string(),
+            ignore_lockfile: config.ignore_lockfile(),
+            json_diagnostics: uses_json_diagnostics(config),
+            fingerprint: fingerprint.to_string(),
+        }
+    }
+
+    fn digest(&self) -> String {
+        let mut context = md5::Context::new();
+
+        for part in [
+            self.toolchain.as_str(),
+            self.check_command.as_str(),
+            if self.ignore_lockfile { "1" } else { "0" },
+            if self.json_diagnostics { "1" } else { "0" },
+            self.fingerprint.as_str(),
+        ] {
+            context.consume(part.as_bytes());
+            context.consume([0]);
+        }
+
+        format!("{:x}", context.compute())
+    }
+}
+
+/// Computes a fingerprint of the crate sources.
+///
+/// The fingerprint covers every file within the crate root and the folders of its path
+/// dependencies, except for those in `target` folders and hidden folders, and the closest
+/// `Cargo.lock`, which may be located in the root of a workspace instead of the crate root.
+/// When the lockfile is ignored, it is regenerated by every check, so it is left out.
+pub fn fingerprint(config: &Config) -> TResult<String> {
+    let crate_root = crate_root_folder(config)?;
+
+    let mut context = md5::Context::new();
+    consume_files(
+        &mut context,
+        &crate_root,
+        Path::new(""),
+        config.ignore_lockfile(),
+    )?;
+
+    let canonical_root = crate_root
+        .canonicalize()
+        .unwrap_or_else(|_| crate_root.clone());
+
+    for dependency in path_dependencies(&crate_root)? {
+        // Nested path dependencies are already covered by the files of the crate root
+        if !dependency.starts_with(&canonical_root) {
+            consume_files(&mut context, &dependency, &dependency, true)?;
+        }
+    }
+
+    if !config.ignore_lockfile() && !crate_root.join(CARGO_LOCK).is_file() {
+        if let Some(lockfile) = find_workspace_lockfile(&crate_root) {
+            context.consume(read_file(&lockfile)?);
+        }
+    }
+
+    Ok(format!("{:x}", context.compute()))
+}
+
+/// Add the files of a package to the fingerprint, under the given name. The lockfile of a path
+/// dependency is not used by cargo, so it may be skipped.
+fn consume_files(
+    context: &mut md5::Context,
+    folder: &Path,
+    name: &Path,
+    skip_lockfile: bool,
+) -> TResult<()> {
+    let mut files = Vec::new();
+    collect_files(folder, folder, &mut files)?;
+    files.sort();
+
+    for file in &files {
+        if skip_lockfile && file == Path::new(CARGO_LOCK) {
+            continue;
+        }
+
+        let contents = read_file(&folder.join(file))?;
+
+        context.consume(name.join(file).to_string_lossy().as_bytes());
+        context.consume([0]);
+        context.consume(&contents);
+        context.consume([0]);
+    }
+
+    Ok(())
+}
+
+/// The folders of the path dependencies of the package in the given folder, and of their own
+/// path dependencies, in a stable order.
+fn path_dependencies(package: &Path) -> TResult<BTreeSet<PathBuf>> {
+    let mut dependencies = BTreeSet::new();
+    let mut pending = vec![package.to_path_buf()];
+
+    while let Some(folder) = pending.pop() {
+        for path in manifest_path_dependencies(&folder)? {
+            // A missing path dependency fails the check itself, so it can't affect its outcome
+            if let Ok(path) = folder.join(path).canonicalize() {
+                if dependencies.insert(path.clone()) {
+                    pending.push(path);
+                }
+            }
+        }
+    }
+
+    Ok(dependencies)
+}
+
+/// The paths of the path dependencies listed in the manifest in the given folder, as written.
+fn manifest_path_dependencies(folder: &Path) -> TResult<Vec<String>> {
+    let manifest_path = folder.join("Cargo.toml");
+    let contents = std::fs::read_to_string(&manifest_path).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFile(manifest_path.clone()),
+    })?;
+    let manifest = contents.parse::<Document>()?;
+
+    let mut tables: Vec<&dyn TableLike> = vec![manifest.as_table()];
+
+    if let Some(targets) = manifest.get("target").and_then(Item::as_table_like) {
+        tables.extend(
+            targets
+                .iter()
+                .filter_map(|(_, target)| target.as_table_like()),
+        );
+    }
+
+    let mut paths = Vec::new();
+
+    for table in tables {
+        for name in DEPENDENCY_TABLES {
+            let dependencies = match table.get(name).and_then(Item::as_table_like) {
+                Some(dependencies) => dependencies,
+                None => continue,
+            };
+
+            paths.extend(dependencies.iter().filter_map(|(_, dependency)| {
+                dependency
+                    .as_table_like()?
+                    .get("path")?
+                    .as_str()
+                    .map(String::from)
+            }));
+        }
+    }
+
+    Ok(paths)
+}
+
+fn collect_files(root: &Path, folder: &Path, files: &mut Vec<PathBuf>) -> TResult<()> {
+    let entries = std::fs::read_dir(folder).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFolder(folder.to_path_buf()),
+    })?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+
+        if path.is_dir() {
+            if is_hidden || path == root.join("target") {
+                continue;
+            }
+
+            collect_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_path_buf());
+        }
+    }
+
+    Ok(())
+}
+
+fn find_workspace_lockfile(crate_root: &Path) -> Option<PathBuf> {
+    crate_root
+        .ancestors()
+        .skip(1)
+        .map(|folder| folder.join(CARGO_LOCK))
+        .find(|lockfile| lockfile.is_file())
+}
+
+fn read_file(path: &Path) -> TResult<Vec<u8>> {
+    std::fs::read(path).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFile(path.to_path_buf()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fingerprint, CacheKey, CheckCache, FAILURE_EXPIRY};
+    use crate::config::{ConfigBuilder, ModeIntent};
+    use crate::diagnose::{CompilerDiagnostic, DiagnosticSpan};
+    use crate::outcome::Outcome;
+    use crate::semver::Version;
+    use crate::toolchain::ToolchainSpec;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    fn temp_cache(name: &str) -> CheckCache {
+        let folder = std::env::temp_dir()
+            .join("cargo-msrv-check-cache-tests")
+            .join(name);
+        let _ = std::fs::remove_dir_all(&folder);
+
+        CheckCache::new(folder)
+    }
+
+    #[test]
+    fn get_inserted_outcomes() {
+        let cache = temp_cache("get_inserted_outcomes");
+        let config = ConfigBuilder::new(ModeIntent::Find, "x86_64-unknown-linux-gnu").build();
+
+        let good = Version::new(1, 56, 0);
+        let bad = Version::new(1, 55, 0);
+        let good = ToolchainSpec::new(&good, config.target());
+        let bad = ToolchainSpec::new(&bad, config.target());
+
+        let good_key = CacheKey::new(&config, &good, "fingerprint");
+        let bad_key = CacheKey::new(&config, &bad, "fingerprint");
+
+        assert!(cache.get(&good_key, &good).is_none());
+
+        cache
+            .insert(&good_key, &Outcome::new_success(good.to_owned()))
+            .unwrap();
+        cache
+            .insert(
+                &bad_key,
+                &Outcome::new_failure(bad.to_owned(), "error[E0658]".to_string()),
+            )
+            .unwrap();
+
+        assert!(cache.get(&good_key, &good).unwrap().is_success());
+
+        match cache.get(&bad_key, &bad).unwrap() {
+            Outcome::Failure(failure) => assert_eq!(failure.error_message, "error[E0658]"),
+            Outcome::Success(_) => panic!("expected a cached failure"),
+        }
+    }
+
+    #[test]
+    fn failures_expire() {
+        let cache = temp_cache("failures_expire");
+        let config = ConfigBuilder::new(ModeIntent::Find, "x86_64-unknown-linux-gnu").build();
+
+        let good = Version::new(1, 56, 0);
+        let bad = Version::new(1, 55, 0);
+        let good = ToolchainSpec::new(&good, config.target());
+        let bad = ToolchainSpec::new(&bad, config.target());
+        let good_key = CacheKey::new(&config, &good, "fingerprint");
+        let bad_key = CacheKey::new(&config, &bad, "fingerprint");
+
+        let checked_at = SystemTime::now() - FAILURE_EXPIRY - Duration::from_secs(60);
+
+        cache
+            .insert_checked_at(
+                &good_key,
+                &Outcome::new_success(good.to_owned()),
+                checked_at,
+            )
+            .unwrap();
+        cache
+            .insert_checked_at(
+                &bad_key,
+                &Outcome::new_failure(bad.to_owned(), "error: network failure".to_string()),
+                checked_at,
+            )
+            .unwrap();
+
+        assert!(cache.get(&good_key, &good).unwrap().is_success());
+        assert!(cache.get(&bad_key, &bad).is_none());
+    }
+
+    #[test]
+    fn get_inserted_diagnostics() {
+        let cache = temp_cache("get_inserted_diagnostics");
+        let config = ConfigBuilder::new(ModeIntent::Find, "x86_64-unknown-linux-gnu")
+            .json_diagnostics(true)
+            .build();
+
+        let version = Version::new(1, 55, 0);
+        let toolchain = ToolchainSpec::new(&version, config.target());
+        let key = CacheKey::new(&config, &toolchain, "fingerprint");
+
+        let diagnostic = CompilerDiagnostic {
+            level: "error".to_string(),
+            code: Some("E0658".to_string()),
+            message: "`let...else` statements are unstable".to_string(),
+            rendered: None,
+            spans: vec![DiagnosticSpan {
+                file_name: "src/main.rs".to_string(),
+                line_start: 12,
+                line_end: 12,
+                column_start: 5,
+                column_end: 37,
+                is_primary: true,
+                label: None,
+            }],
+            children: vec![
+                "add `#![feature(let_else)]` to the crate attributes to enable".to_string(),
+            ],
+        };
+
+        cache
+            .insert(
+                &key,
+                &Outcome::new_failure_with_diagnostics(
+                    toolchain.to_owned(),
+                    "error[E0658]".to_string(),
+                    vec![diagnostic.clone()],
+                ),
+            )
+            .unwrap();
+
+        match cache.get(&key, &toolchain).unwrap() {
+            Outcome::Failure(failure) => assert_eq!(failure.diagnostics, vec![diagnostic]),
+            Outcome::Success(_) => panic!("expected a cached failure"),
+        }
+    }
+
+    #[test]
+    fn different_fingerprint_misses() {
+        let cache = temp_cache("different_fingerprint_misses");
+        let config = ConfigBuilder::new(ModeIntent::Find, "x86_64-unknown-linux-gnu").build();
+
+        let version = Version::new(1, 56, 0);
+        let toolchain = ToolchainSpec::new(&version, config.target());
+
+        let key = CacheKey::new(&config, &toolchain, "before");
+        cache
+            .insert(&key, &Outcome::new_success(toolchain.to_owned()))
+            .unwrap();
+
+        let changed = CacheKey::new(&config, &toolchain, "after");
+        assert!(cache.get(&changed, &toolchain).is_none());
+    }
+
+    #[test]
+    fn different_check_command_misses() {
+        let cache = temp_cache("different_check_command_misses");
+        let config = ConfigBuilder::new(ModeIntent::Find, "x86_64-unknown-linux-gnu").build();
+        let other_config = ConfigBuilder::new(ModeIntent::Find, "x86_64-unknown-linux-gnu")
+            .check_command(vec!["cargo", "test"])
+            .build();
+
+        let version = Version::new(1, 56, 0);
+        let toolchain = ToolchainSpec::new(&version, config.target());
+
+        let key = CacheKey::new(&config, &toolchain, "fingerprint");
+        cache
+            .insert(&key, &Outcome::new_success(toolchain.to_owned()))
+            .unwrap();
+
+        let other_key = CacheKey::new(&other_config, &toolchain, "fingerprint");
+        assert!(cache.get(&other_key, &toolchain).is_none());
+    }
+
+    /// A crate with a path dependency outside of its own folder.
+    fn temp_crate_with_path_dependency(name: &str) -> PathBuf {
+        let folder = std::env::temp_dir()
+            .join("cargo-msrv-fingerprint-tests")
+            .join(name);
+        let _ = std::fs::remove_dir_all(&folder);
+
+        let write = |path: &str, contents: &str| {
+            let path = folder.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        };
+
+        write(
+            "app/Cargo.toml",
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\ndep = { path = \"../dep\" }\n",
+        );
+        write("app/src/lib.rs", "pub use dep::f;\n");
+        write("app/Cargo.lock", "version = 3\n");
+        write(
+            "dep/Cargo.toml",
+            "[package]\nname = \"dep\"\nversion = \"0.1.0\"\n",
+        );
+        write("dep/src/lib.rs", "pub fn f() {}\n");
+
+        folder
+    }
+
+    fn fingerprint_of(crate_root: &Path, ignore_lockfile: bool) -> String {
+        let config = ConfigBuilder::new(ModeIntent::Find, "x86_64-unknown-linux-gnu")
+            .crate_path(Some(crate_root))
+            .ignore_lockfile(ignore_lockfile)
+            .build();
+
+        fingerprint(&config).unwrap()
+    }
+
+    #[test]
+    fn changed_path_dependency_changes_fingerprint() {
+        let folder = temp_crate_with_path_dependency("changed_path_dependency");
+        let crate_root = folder.join("app");
+
+        let before = fingerprint_of(&crate_root, false);
+        std::fs::write(folder.join("dep/src/lib.rs"), "pub fn f() -> u8 { 0 }\n").unwrap();
+        let after = fingerprint_of(&crate_root, false);
+
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn ignored_lockfile_is_not_fingerprinted() {
+        let folder = temp_crate_with_path_dependency("ignored_lockfile");
+        let crate_root = folder.join("app");
+
+        let locked = fingerprint_of(&crate_root, false);
+        let ignored = fingerprint_of(&crate_root, true);
+        std::fs::write(
+            crate_root.join("Cargo.lock"),
+            "version = 3\n\n# regenerated\n",
+        )
+        .unwrap();
+
+        assert_ne!(fingerprint_of(&crate_root, false), locked);
+        assert_eq!(fingerprint_of(&crate_root, true), ignored);
+    }
+}
@@ -1,14 +1,20 @@
 use std::path::Path;
+use std::time::Instant;
 
-use crate::command::RustupCommand;
-use crate::config::Config;
+use once_cell::sync::OnceCell;
+
+use crate::cache::{fingerprint, CacheKey, CheckCache};
+use crate::command::{RustupCommand, RustupOutput};
+use crate::compatibility_report::CheckLog;
+use crate::config::{inject_check_args, Config};
+use crate::diagnose::{parse_compiler_messages, FailureLog};
 use crate::download::{DownloadToolchain, ToolchainDownloader};
 use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
 use crate::lockfile::{LockfileHandler, CARGO_LOCK};
 use crate::outcome::Outcome;
 use crate::paths::crate_root_folder;
 use crate::reporter::{Output, ProgressAction};
-use crate::toolchain::ToolchainSpec;
+use crate::toolchain::{DatedToolchain, OwnedToolchainSpec, ToolchainSpec};
 
 pub trait Check {
     fn check(&self, config: &Config, toolchain: &ToolchainSpec) -> TResult<Outcome>;
@@ -37,7 +43,8 @@ impl<'reporter, R: Output> Check for RunCheck<'reporter, R> {
         let outcome = self.run_check_command_via_rustup(
             toolchain,
             config.crate_path(),
-            config.check_command(),
+            &check_command(config),
+            uses_json_diagnostics(config),
         )?;
 
         // move the lockfile back
@@ -55,8 +62,9 @@ impl<'reporter, R: Output> RunCheck<'reporter, R> {
     }
 
     fn prepare(&self, toolchain: &ToolchainSpec, config: &Config) -> TResult<()> {
-        let downloader = ToolchainDownloader::new(self.reporter);
-        downloader.download(toolchain)?;
+        if config.install_toolchains() {
+            install_toolchain(toolchain, config, self.reporter)?;
+        }
 
         if config.ignore_lockfile() {
             remove_lockfile(config)?;
@@ -69,20 +77,25 @@ impl<'reporter, R: Output> RunCheck<'reporter, R> {
         &self,
         toolchain: &ToolchainSpec,
         dir: Option<&Path>,
-        check: &[&str],
+        check: &[String],
+        json_diagnostics: bool,
     ) -> TResult<Outcome> {
         let mut cmd: Vec<&str> = vec![toolchain.spec()];
-        cmd.extend_from_slice(check);
+        cmd.extend(check.iter().map(String::as_str));
 
         self.reporter
             .progress(ProgressAction::Checking(toolchain.version()));
 
-        let rustup_output = RustupCommand::new()
+        let mut rustup = RustupCommand::new()
             .with_args(cmd.iter())
             .with_optional_dir(dir)
-            .with_stderr()
-            .run()
-            .map_err(|_| CargoMSRVError::UnableToRunCheck)?;
+            .with_stderr();
+
+        if json_diagnostics {
+            rustup = rustup.with_stdout();
+        }
+
+        let rustup_output = rustup.run().map_err(|_| CargoMSRVError::UnableToRunCheck)?;
 
         let status = rustup_output.exit_status();
 
@@ -102,15 +115,191 @@ impl<'reporter, R: Output> RunCheck<'reporter, R> {
                 "try_building run failed"
             );
 
-            Ok(Outcome::new_failure(
-                toolchain.to_owned(),
-                stderr.to_string(),
-            ))
+            let outcome = failure_outcome(toolchain.to_owned(), &rustup_output, json_diagnostics);
+            report_failure(self.reporter, &outcome);
+
+            Ok(outcome)
+        }
+    }
+}
+
+/// Install the toolchain, and the target to compile to, if any.
+///
+/// Not every dated toolchain exists, since nightly toolchains are not released on days on which
+/// the build failed, so a dated toolchain which can not be installed is reported as unavailable,
+/// and skipped by the search.
+pub(crate) fn install_toolchain(
+    toolchain: &ToolchainSpec,
+    config: &Config,
+    output: &impl Output,
+) -> TResult<()> {
+    let downloader = ToolchainDownloader::new(output);
+
+    match downloader.download(toolchain) {
+        Err(CargoMSRVError::RustupInstallFailed(_))
+            if DatedToolchain::from_version(toolchain.version()).is_some() =>
+        {
+            return Err(CargoMSRVError::UnavailableDatedToolchain(
+                toolchain.version().clone(),
+            ));
+        }
+        result => result?,
+    }
+
+    if let Some(target) = config.compile_target() {
+        downloader.add_target(toolchain, target)?;
+    }
+
+    Ok(())
+}
+
+/// Whether structured diagnostics are collected, which is only possible when the check command
+/// is a cargo command, and the user did not choose a message format already.
+pub(crate) fn uses_json_diagnostics(config: &Config) -> bool {
+    let command = config.check_command();
+
+    config.json_diagnostics()
+        && command.first().map(String::as_str) == Some("cargo")
+        && !command
+            .iter()
+            .any(|arg| arg.starts_with("--message-format"))
+}
+
+/// The check command which is run for each toolchain, with `--message-format=json` added when
+/// structured diagnostics are collected.
+pub(crate) fn check_command(config: &Config) -> Vec<String> {
+    if uses_json_diagnostics(config) {
+        inject_check_args(config.check_command(), ["--message-format=json"])
+    } else {
+        config.check_command().to_vec()
+    }
+}
+
+/// Create the outcome of a failed check. With structured diagnostics, cargo prints the errors of
+/// the compiler to stdout, so their rendered form is prepended to the error message.
+pub(crate) fn failure_outcome(
+    toolchain: OwnedToolchainSpec,
+    output: &RustupOutput,
+    json_diagnostics: bool,
+) -> Outcome {
+    if !json_diagnostics {
+        return Outcome::new_failure(toolchain, output.stderr().to_string());
+    }
+
+    let diagnostics = parse_compiler_messages(output.stdout());
+
+    let mut error_message = diagnostics
+        .iter()
+        .filter_map(|diagnostic| diagnostic.rendered.as_deref())
+        .collect::<String>();
+    error_message.push_str(output.stderr());
+
+    Outcome::new_failure_with_diagnostics(toolchain, error_message, diagnostics)
+}
+
+/// Pass the error output of a failed check on to the reporter.
+fn report_failure(reporter: &impl Output, outcome: &Outcome) {
+    if let Outcome::Failure(failure) = outcome {
+        reporter.check_failed(failure.toolchain_spec.version(), &failure.error_message);
+    }
+}
+
+/// A [`Check`] which first looks up whether the outcome of a check is already known, before
+/// running the inner check. Outcomes of the inner check are stored in the [`CheckCache`].
+pub struct CachedCheck<'reporter, C: Check, R: Output> {
+    inner: C,
+    cache: CheckCache,
+    fingerprint: OnceCell<String>,
+    reporter: &'reporter R,
+}
+
+impl<'reporter, C: Check, R: Output> CachedCheck<'reporter, C, R> {
+    pub fn new(inner: C, cache: CheckCache, reporter: &'reporter R) -> Self {
+        Self {
+            inner,
+            cache,
+            fingerprint: OnceCell::new(),
+            reporter,
         }
     }
 }
 
-fn remove_lockfile(config: &Config) -> TResult<()> {
+impl<'reporter, C: Check, R: Output> Check for CachedCheck<'reporter, C, R> {
+    fn check(&self, config: &Config, toolchain: &ToolchainSpec) -> TResult<Outcome> {
+        // The sources do not change while we're searching, so the fingerprint is computed only once
+        let fingerprint = self.fingerprint.get_or_try_init(|| fingerprint(config))?;
+        let key = CacheKey::new(config, toolchain, fingerprint);
+
+        if let Some(outcome) = self.cache.get(&key, toolchain) {
+            info!(toolchain = toolchain.spec(), "using cached check outcome");
+
+            self.reporter
+                .complete_step(toolchain.version(), outcome.is_success());
+            report_failure(self.reporter, &outcome);
+
+            return Ok(outcome);
+        }
+
+        let outcome = self.inner.check(config, toolchain)?;
+
+        if let Err(err) = self.cache.insert(&key, &outcome) {
+            warn!(%err, "unable to store check outcome in cache");
+        }
+
+        Ok(outcome)
+    }
+}
+
+/// A [`Check`] which records the failures of the inner check in a [`FailureLog`], so they can
+/// be diagnosed after the search has completed.
+pub struct RecordFailures<'log, C: Check> {
+    inner: C,
+    log: &'log FailureLog,
+}
+
+impl<'log, C: Check> RecordFailures<'log, C> {
+    pub fn new(inner: C, log: &'log FailureLog) -> Self {
+        Self { inner, log }
+    }
+}
+
+impl<'log, C: Check> Check for RecordFailures<'log, C> {
+    fn check(&self, config: &Config, toolchain: &ToolchainSpec) -> TResult<Outcome> {
+        let outcome = self.inner.check(config, toolchain)?;
+
+        if let Outcome::Failure(failure) = &outcome {
+            self.log.record(failure);
+        }
+
+        Ok(outcome)
+    }
+}
+
+/// A [`Check`] which records the outcome of each check of the inner check in a [`CheckLog`],
+/// together with the time it took, so they can be written to a compatibility report.
+pub struct RecordChecks<'log, C: Check> {
+    inner: C,
+    log: &'log CheckLog,
+}
+
+impl<'log, C: Check> RecordChecks<'log, C> {
+    pub fn new(inner: C, log: &'log CheckLog) -> Self {
+        Self { inner, log }
+    }
+}
+
+impl<'log, C: Check> Check for RecordChecks<'log, C> {
+    fn check(&self, config: &Config, toolchain: &ToolchainSpec) -> TResult<Outcome> {
+        let started = Instant::now();
+        let outcome = self.inner.check(config, toolchain)?;
+
+        self.log.record(&outcome, Some(started.elapsed()));
+
+        Ok(outcome)
+    }
+}
+
+pub(crate) fn remove_lockfile(config: &Config) -> TResult<()> {
     let lock_file = crate_root_folder(config).map(|p| p.join(CARGO_LOCK))?;
 
     if lock_file.is_file() {
@@ -122,3 +311,33 @@ fn remove_lockfile(config: &Config) -> TResult<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{check_command, uses_json_diagnostics};
+    use crate::config::{ConfigBuilder, ModeIntent};
+
+    #[yare::parameterized(
+        cargo = { &["cargo", "check"], &["cargo", "check", "--message-format=json"] },
+        before_separator = { &["cargo", "test", "--", "--nocapture"], &["cargo", "test", "--message-format=json", "--", "--nocapture"] },
+        not_cargo = { &["./check.sh"], &["./check.sh"] },
+        custom_message_format = { &["cargo", "check", "--message-format=short"], &["cargo", "check", "--message-format=short"] },
+    )]
+    fn json_message_format(command: &[&str], expected: &[&str]) {
+        let config = ConfigBuilder::new(ModeIntent::Find, "x86_64-unknown-linux-gnu")
+            .check_command(command.to_vec())
+            .json_diagnostics(true)
+            .build();
+
+        assert_eq!(check_command(&config), expected);
+        assert_eq!(uses_json_diagnostics(&config), command != expected);
+    }
+
+    #[test]
+    fn without_json_diagnostics() {
+        let config = ConfigBuilder::new(ModeIntent::Find, "x86_64-unknown-linux-gnu").build();
+
+        assert_eq!(check_command(&config), vec!["cargo", "check"]);
+        assert!(!uses_json_diagnostics(&config));
+    }
+}
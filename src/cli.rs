@@ -1,5 +1,5 @@
 use crate::config::{OutputFormat, TracingTargetOption};
-use clap::{App, AppSettings, Arg};
+use clap::{App, AppSettings, Arg, ArgGroup};
 use std::str::FromStr;
 
 use crate::fetch::is_target_available;
@@ -14,20 +14,36 @@ pub mod id {
     pub const ARG_MAX: &str = "max";
     pub const ARG_BISECT: &str = "bisect";
     pub const ARG_LINEAR: &str = "linear";
+    pub const ARG_PARALLEL: &str = "parallel";
+    pub const ARG_JOBS: &str = "jobs";
     pub const ARG_TOOLCHAIN_FILE: &str = "toolchain_file";
     pub const ARG_IGNORE_LOCKFILE: &str = "lockfile";
     pub const ARG_OUTPUT_FORMAT: &str = "output_format";
     pub const ARG_NO_USER_OUTPUT: &str = "no_user_output";
     pub const ARG_VERIFY: &str = "verify_msrv";
     pub const ARG_RELEASE_SOURCE: &str = "release_source";
+    pub const ARG_RELEASE_INDEX_FILE: &str = "release_index_file";
+    pub const ARG_CHANNEL: &str = "channel";
     pub const ARG_NO_LOG: &str = "no_log";
     pub const ARG_LOG_LEVEL: &str = "log_level";
     pub const ARG_LOG_TARGET: &str = "log_target";
     pub const ARG_NO_READ_MIN_EDITION: &str = "no_read_min_edition";
     pub const ARG_NO_CHECK_FEEDBACK: &str = "no_check_feedback";
+    pub const ARG_JSON_DIAGNOSTICS: &str = "json_diagnostics";
+    pub const ARG_WORKSPACE: &str = "workspace";
+    pub const ARG_NO_CACHE: &str = "no_cache";
+    pub const ARG_FEATURE_MATRIX: &str = "feature_matrix";
+    pub const ARG_FEATURE_SET: &str = "feature_set";
+    pub const ARG_STATIC: &str = "static";
+    pub const ARG_MIN_FROM_SOURCE: &str = "min_from_source";
+    pub const ARG_UNSTABLE_FEATURES: &str = "unstable_features";
+    pub const ARG_REPORT: &str = "report";
 
     pub const SUB_COMMAND_LIST: &str = "list";
     pub const SUB_COMMAND_LIST_VARIANT: &str = "list_variant";
+    pub const SUB_COMMAND_LIST_VERIFY: &str = "list_verify";
+    pub const SUB_COMMAND_LIST_FORMAT: &str = "list_format";
+    pub const SUB_COMMAND_LIST_DEP_KINDS: &str = "list_dep_kinds";
 
     pub const SUB_COMMAND_SET: &str = "set";
     pub const SUB_COMMAND_SET_VALUE: &str = "set_value";
@@ -35,6 +51,48 @@ pub mod id {
     pub const SUB_COMMAND_SHOW: &str = "show";
 
     pub const SUB_COMMAND_VERIFY: &str = "verify";
+    pub const SUB_COMMAND_VERIFY_RESOLVE_LOCKFILE: &str = "verify_resolve_lockfile";
+    pub const SUB_COMMAND_VERIFY_REGISTRY_INDEX: &str = "verify_registry_index";
+
+    pub const SUB_COMMAND_BUMP: &str = "bump";
+    pub const SUB_COMMAND_BUMP_TO: &str = "bump_to";
+    pub const SUB_COMMAND_BUMP_DEPENDENCY: &str = "bump_dependency";
+    pub const SUB_COMMAND_BUMP_LANGUAGE_FEATURE: &str = "bump_language_feature";
+}
+
+/// The environment variables which may be used instead of the command line arguments. An argument
+/// given on the command line takes precedence over its environment variable.
+pub mod env {
+    pub const PATH: &str = "CARGO_MSRV_PATH";
+    pub const TARGET: &str = "CARGO_MSRV_TARGET";
+    pub const INCLUDE_ALL_PATCH_RELEASES: &str = "CARGO_MSRV_INCLUDE_ALL_PATCH_RELEASES";
+    pub const MIN: &str = "CARGO_MSRV_MIN";
+    pub const MAX: &str = "CARGO_MSRV_MAX";
+    pub const BISECT: &str = "CARGO_MSRV_BISECT";
+    pub const LINEAR: &str = "CARGO_MSRV_LINEAR";
+    pub const PARALLEL: &str = "CARGO_MSRV_PARALLEL";
+    pub const JOBS: &str = "CARGO_MSRV_JOBS";
+    pub const TOOLCHAIN_FILE: &str = "CARGO_MSRV_TOOLCHAIN_FILE";
+    pub const IGNORE_LOCKFILE: &str = "CARGO_MSRV_IGNORE_LOCKFILE";
+    pub const OUTPUT_FORMAT: &str = "CARGO_MSRV_OUTPUT_FORMAT";
+    pub const NO_USER_OUTPUT: &str = "CARGO_MSRV_NO_USER_OUTPUT";
+    pub const RELEASE_SOURCE: &str = "CARGO_MSRV_RELEASE_SOURCE";
+    pub const RELEASE_INDEX_FILE: &str = "CARGO_MSRV_RELEASE_INDEX_FILE";
+    pub const CHANNEL: &str = "CARGO_MSRV_CHANNEL";
+    pub const NO_LOG: &str = "CARGO_MSRV_NO_LOG";
+    pub const LOG_TARGET: &str = "CARGO_MSRV_LOG_TARGET";
+    pub const LOG_LEVEL: &str = "CARGO_MSRV_LOG_LEVEL";
+    pub const NO_READ_MIN_EDITION: &str = "CARGO_MSRV_NO_READ_MIN_EDITION";
+    pub const NO_CHECK_FEEDBACK: &str = "CARGO_MSRV_NO_CHECK_FEEDBACK";
+    pub const JSON_DIAGNOSTICS: &str = "CARGO_MSRV_JSON_DIAGNOSTICS";
+    pub const NO_CACHE: &str = "CARGO_MSRV_NO_CACHE";
+    pub const WORKSPACE: &str = "CARGO_MSRV_WORKSPACE";
+    pub const FEATURE_MATRIX: &str = "CARGO_MSRV_FEATURE_MATRIX";
+    pub const FEATURE_SET: &str = "CARGO_MSRV_FEATURE_SET";
+    pub const STATIC: &str = "CARGO_MSRV_STATIC";
+    pub const MIN_FROM_SOURCE: &str = "CARGO_MSRV_MIN_FROM_SOURCE";
+    pub const UNSTABLE_FEATURES: &str = "CARGO_MSRV_UNSTABLE_FEATURES";
+    pub const REPORT: &str = "CARGO_MSRV_REPORT";
 }
 
 pub fn cli() -> App<'static> {
@@ -57,9 +115,11 @@ rustup like so: `rustup run <toolchain> <COMMAND...>`. You'll only need to provi
         .subcommand(set())
         .subcommand(show())
         .subcommand(verify())
+        .subcommand(bump())
         .arg(
             Arg::new(id::ARG_SEEK_PATH)
                 .long("path")
+                .env(env::PATH)
                 .help("Path to the cargo project directory")
                 .takes_value(true)
                 .value_name("DIR")
@@ -78,8 +138,17 @@ rustup like so: `rustup run <toolchain> <COMMAND...>`. You'll only need to provi
         .arg(
             Arg::new(id::ARG_SEEK_CUSTOM_TARGET)
                 .long("target")
+                .env(env::TARGET)
                 .help("Check against a custom target (instead of the rustup default)")
+                .long_help(
+                    "Check against a custom target (instead of the rustup default). \
+                     May be given more than once, in which case the MSRV is determined for each \
+                     target separately. The toolchains of the rustup default target are then \
+                     used, with the rust-std component of each target added, and `--target <TARGET>` \
+                     is passed on to the check command.",
+                )
                 .takes_value(true)
+                .multiple_occurrences(true)
                 .value_name("TARGET")
                 .validator(|value| {
                     is_target_available(&value).map_err(|_| {
@@ -91,39 +160,76 @@ rustup like so: `rustup run <toolchain> <COMMAND...>`. You'll only need to provi
         )
         .arg(Arg::new(id::ARG_INCLUDE_ALL_PATCH_RELEASES)
             .long("include-all-patch-releases")
+            .env(env::INCLUDE_ALL_PATCH_RELEASES)
             .help("Include all patch releases, instead of only the last")
             .takes_value(false)
         )
         .arg(Arg::new(id::ARG_MIN)
             .long("min")
+            .env(env::MIN)
             .visible_alias("minimum")
             .help("Earliest version to take into account")
             .long_help("Earliest (least recent) version to take into account. \
-             Version must match a valid Rust toolchain, and be semver compatible. Edition aliases may also be used.")
+             Version must match a valid Rust toolchain, and be semver compatible. Edition aliases may also be used. \
+             When searching the beta or nightly channel, a dated toolchain like `nightly-2023-04-12` may be given instead.")
             .takes_value(true)
         )
         .arg(Arg::new(id::ARG_MAX)
             .long("max")
+            .env(env::MAX)
             .visible_alias("maximum")
             .help("Latest version to take into account")
             .long_help("Latest (most recent) version to take into account.\
-             Version must match a valid Rust toolchain, and be semver compatible.")
+             Version must match a valid Rust toolchain, and be semver compatible. \
+             When searching the beta or nightly channel, a dated toolchain like `nightly-2023-04-12` may be given instead.")
             .takes_value(true)
         )
         .arg(Arg::new(id::ARG_BISECT)
             .long("bisect")
             .help("Use a binary search to find the MSRV instead of a linear search")
-            .conflicts_with(id::ARG_LINEAR)
+            .long_help("Use a binary search to find the MSRV instead of a linear search. \
+            May also be enabled with the CARGO_MSRV_BISECT environment variable, \
+            which a search method given on the command line overrides.")
+            .conflicts_with_all(&[id::ARG_LINEAR, id::ARG_PARALLEL])
             .takes_value(false)
         )
         .arg(Arg::new(id::ARG_LINEAR)
             .long("linear")
             .help("Use a linear search to find the MSRV, by checking toolchains from latest to earliest")
-            .conflicts_with(id::ARG_BISECT)
+            .long_help("Use a linear search to find the MSRV, by checking toolchains from latest to earliest. \
+            May also be enabled with the CARGO_MSRV_LINEAR environment variable, \
+            which a search method given on the command line overrides.")
+            .conflicts_with_all(&[id::ARG_BISECT, id::ARG_PARALLEL])
+            .takes_value(false)
+        )
+        .arg(Arg::new(id::ARG_PARALLEL)
+            .long("parallel")
+            .help("Use a linear search to find the MSRV, but check several toolchains at once")
+            .long_help("Use a linear search to find the MSRV, but check several toolchains at once. \
+            Each check uses its own target directory, located in 'target/cargo-msrv', \
+            so builds of different toolchains do not clobber each other. \
+            The amount of toolchains which are checked at once can be set with --jobs. \
+            May also be enabled with the CARGO_MSRV_PARALLEL environment variable, \
+            which a search method given on the command line overrides.")
+            .conflicts_with_all(&[id::ARG_BISECT, id::ARG_LINEAR])
             .takes_value(false)
         )
+        .arg(Arg::new(id::ARG_JOBS)
+            .long("jobs")
+            .env(env::JOBS)
+            .help("The amount of toolchains to check at once, when using the parallel search method")
+            .long_help("The amount of toolchains to check at once, when using the parallel search method. \
+            Defaults to the number of logical CPUs.")
+            .value_name("N")
+            .validator(|value| match value.parse::<usize>() {
+                Ok(jobs) if jobs > 0 => Ok(()),
+                _ => Err(String::from("Expected a number of jobs greater than zero")),
+            })
+            .takes_value(true)
+        )
         .arg(Arg::new(id::ARG_TOOLCHAIN_FILE)
             .long("toolchain-file")
+            .env(env::TOOLCHAIN_FILE)
             .help("Output a rust-toolchain file with the MSRV as toolchain")
             .long_help("Output a rust-toolchain file with the MSRV as toolchain. \
             The toolchain file will pin the Rust version for this crate. \
@@ -131,12 +237,14 @@ rustup like so: `rustup run <toolchain> <COMMAND...>`. You'll only need to provi
         )
         .arg(Arg::new(id::ARG_IGNORE_LOCKFILE)
             .long("ignore-lockfile")
+            .env(env::IGNORE_LOCKFILE)
             .help("Temporarily removes the lockfile, so it will not interfere with the building process")
             .long_help("Temporarily removes the lockfile, so it will not interfere with the building process. \
             This is important when testing against Rust versions prior to 1.38.0, for which Cargo does not recognize the new v2 lockfile.")
         )
         .arg(Arg::new(id::ARG_OUTPUT_FORMAT)
             .long("output-format")
+            .env(env::OUTPUT_FORMAT)
             .help("Output status messages in machine-readable format")
             .takes_value(true)
             .possible_values(OutputFormat::custom_formats())
@@ -146,6 +254,7 @@ rustup like so: `rustup run <toolchain> <COMMAND...>`. You'll only need to provi
         )
         .arg(Arg::new(id::ARG_NO_USER_OUTPUT)
             .long("no-user-output")
+            .env(env::NO_USER_OUTPUT)
             .help("Disables user output")
             .long_help("Disables user output. Useful when when `--log-target stdout` is present, \
             so no clipping between the user output prints and log message prints will take place. \
@@ -164,18 +273,50 @@ rustup like so: `rustup run <toolchain> <COMMAND...>`. You'll only need to provi
         )
         .arg(Arg::new(id::ARG_RELEASE_SOURCE)
             .long("release-source")
+            .env(env::RELEASE_SOURCE)
             .help("Select the rust-releases source to use as the release index")
             .takes_value(true)
-            .possible_values(&["rust-changelog", "rust-dist"])
+            .long_help("Select the rust-releases source to use as the release index. \
+            When the index of the `rust-changelog` or `rust-dist` source can not be fetched, the most recently \
+            fetched index, which is stored in the cargo-msrv data folder, will be used instead. \
+            The `file` source builds the index from the local file given by `--release-index-file`. \
+            The `installed` source builds the index from the stable toolchains installed with rustup, \
+            for the given target; toolchains will not be installed when this source is selected.")
+            .possible_values(["rust-changelog", "rust-dist", "file", "installed"])
             .default_value("rust-changelog")
         )
+        .arg(Arg::new(id::ARG_RELEASE_INDEX_FILE)
+            .long("release-index-file")
+            .env(env::RELEASE_INDEX_FILE)
+            .help("The local file from which the release index is built, when the release source is `file`")
+            .long_help("The local file from which the release index is built, when the release source is `file`. \
+            The file may either be a saved copy of the Rust changelog (RELEASES.md), or a plain list of \
+            Rust versions, with one version per line.")
+            .takes_value(true)
+            .value_name("FILE")
+        )
+        .arg(Arg::new(id::ARG_CHANNEL)
+            .long("channel")
+            .env(env::CHANNEL)
+            .help("The release channel of the toolchains which are searched")
+            .long_help("The release channel of the toolchains which are searched. For the `beta` and \
+            `nightly` channels, the search runs over the dated toolchains of the channel, e.g. `nightly-2023-04-12`, \
+            instead of over the stable releases, and the oldest dated toolchain which is compatible is reported. \
+            The release index is not fetched for these channels, and the `--min` and `--max` options also accept \
+            dated toolchains.")
+            .takes_value(true)
+            .possible_values(["stable", "beta", "nightly"])
+            .default_value("stable")
+        )
         .arg(Arg::new(id::ARG_NO_LOG)
             .long("no-log")
+            .env(env::NO_LOG)
             .help("Disable logging")
             .takes_value(false)
         )
         .arg(Arg::new(id::ARG_LOG_TARGET)
             .long("log-target")
+            .env(env::LOG_TARGET)
             .help("Specify where the program should output its logs")
             .takes_value(true)
             .number_of_values(1)
@@ -184,6 +325,7 @@ rustup like so: `rustup run <toolchain> <COMMAND...>`. You'll only need to provi
         )
         .arg(Arg::new(id::ARG_LOG_LEVEL)
             .long("log-level")
+            .env(env::LOG_LEVEL)
             .help("Specify the verbosity of logs the program should output")
             .takes_value(true)
             .number_of_values(1)
@@ -192,15 +334,125 @@ rustup like so: `rustup run <toolchain> <COMMAND...>`. You'll only need to provi
         )
         .arg(Arg::new(id::ARG_NO_READ_MIN_EDITION)
             .long("no-read-min-edition")
+            .env(env::NO_READ_MIN_EDITION)
             .help("If provided, the 'package.edition' value in the Cargo.toml will not \
             be used to reduce search space.")
             .takes_value(false)
         )
         .arg(Arg::new(id::ARG_NO_CHECK_FEEDBACK)
             .long("no-check-feedback")
+            .env(env::NO_CHECK_FEEDBACK)
             .help("If provided, the outcome of each individual check will not be printed.")
             .takes_value(false)
         )
+        .arg(Arg::new(id::ARG_JSON_DIAGNOSTICS)
+            .long("json-diagnostics")
+            .env(env::JSON_DIAGNOSTICS)
+            .help("Collect the errors of failed checks as structured diagnostics")
+            .long_help("Collect the errors of failed checks as structured diagnostics. \
+            When the check command is a cargo command, `--message-format=json` is added to it, and the \
+            errors reported by the compiler are parsed, including their error codes and source locations. \
+            With the json output format, these diagnostics are included in the messages of failed checks.")
+            .takes_value(false)
+        )
+        .arg(Arg::new(id::ARG_NO_CACHE)
+            .long("no-cache")
+            .env(env::NO_CACHE)
+            .help("If provided, the outcome of previously run checks will not be read from, or written to the cache")
+            .long_help("If provided, the outcome of previously run checks will not be read from, or written to the cache. \
+            By default, the outcome of each check is stored in the cargo-msrv data folder, keyed by the toolchain, \
+            the check command and a fingerprint of the files in the crate root (and the Cargo lockfile). \
+            Toolchains for which the outcome is already known will not be checked again.")
+            .takes_value(false)
+        )
+        .arg(Arg::new(id::ARG_WORKSPACE)
+            .long("workspace")
+            .env(env::WORKSPACE)
+            .help("Find the MSRV of each member of the workspace, and of the workspace as a whole")
+            .long_help("Find the MSRV of each member of the workspace, and of the workspace as a whole. \
+            The members are determined with `cargo metadata` from the workspace manifest found at the \
+            current directory, or at the directory given by `--path`. The MSRV of the workspace is the \
+            greatest MSRV of its members. When present, the 'package.edition' value of the Cargo manifest \
+            will not be used to reduce the search space.")
+            .conflicts_with(id::ARG_VERIFY)
+            .takes_value(false)
+        )
+        .arg(Arg::new(id::ARG_FEATURE_MATRIX)
+            .long("feature-matrix")
+            .env(env::FEATURE_MATRIX)
+            .help("Find the MSRV of each of the given feature combinations")
+            .long_help("Find the MSRV of each of the given feature combinations. \
+            Accepts a comma separated list of: 'default' (the default features), 'no-default' \
+            (--no-default-features), 'all' (--all-features) and 'each' (each feature of the package \
+            by itself). The feature flags are passed on to the check command, which should accept \
+            them, like `cargo check` does.")
+            .value_name("COMBINATIONS")
+            .possible_values(["default", "no-default", "all", "each"])
+            .use_delimiter(true)
+            .require_delimiter(true)
+            .multiple_occurrences(true)
+            .conflicts_with_all(&[id::ARG_VERIFY, id::ARG_WORKSPACE])
+            .takes_value(true)
+        )
+        .arg(Arg::new(id::ARG_FEATURE_SET)
+            .long("feature-set")
+            .env(env::FEATURE_SET)
+            .help("Find the MSRV with only the given features enabled")
+            .long_help("Find the MSRV with only the given features enabled, i.e. the default \
+            features are disabled. Features are separated by commas or spaces. May be given more than \
+            once, and may be combined with --feature-matrix.")
+            .value_name("FEATURES")
+            .multiple_occurrences(true)
+            .conflicts_with_all(&[id::ARG_VERIFY, id::ARG_WORKSPACE])
+            .takes_value(true)
+        )
+        .arg(Arg::new(id::ARG_STATIC)
+            .long("static")
+            .env(env::STATIC)
+            .help("Determine a lower bound on the MSRV from the crate sources, without running any toolchain")
+            .long_help("Determine a lower bound on the MSRV from the crate sources, without running any toolchain. \
+            The Rust files in the 'src' folder and the build script are tokenized, and the language features and \
+            standard library APIs they use are looked up in a bundled table of the Rust releases in which they \
+            were stabilized. The table is not exhaustive, so the true MSRV may be greater than the lower bound.")
+            .conflicts_with_all(&[id::ARG_VERIFY, id::ARG_WORKSPACE, id::ARG_FEATURE_MATRIX, id::ARG_FEATURE_SET])
+            .takes_value(false)
+        )
+        .arg(Arg::new(id::ARG_MIN_FROM_SOURCE)
+            .long("min-from-source")
+            .env(env::MIN_FROM_SOURCE)
+            .help("Use the lower bound on the MSRV, determined from the crate sources, as the earliest version to check")
+            .long_help("Use the lower bound on the MSRV, determined from the crate sources like `--static` does, \
+            as the earliest version to check, when it is greater than the version given by `--min` (or the edition). \
+            This reduces the search space, but if the lower bound is mistaken, e.g. because a crate defines a method \
+            which shares the name of a standard library API, the found MSRV will be too high.")
+            .conflicts_with(id::ARG_STATIC)
+            .takes_value(false)
+        )
+        .arg(Arg::new(id::ARG_UNSTABLE_FEATURES)
+            .long("unstable-features")
+            .env(env::UNSTABLE_FEATURES)
+            .help("Find the oldest nightly toolchain which compiles the crate, and when its unstable features were stabilized")
+            .long_help("List the unstable features which the crate enables with `#![feature(...)]` attributes, and \
+            the Rust release in which each of them was stabilized, if known. The dated toolchains of the nightly \
+            channel are searched for the oldest nightly toolchain which compiles the crate; this implies `--channel nightly`. \
+            Once all features are stable, the crate can move to the stable channel.")
+            .conflicts_with_all(&[id::ARG_VERIFY, id::ARG_WORKSPACE, id::ARG_FEATURE_MATRIX, id::ARG_FEATURE_SET, id::ARG_STATIC])
+            .takes_value(false)
+        )
+        .arg(Arg::new(id::ARG_REPORT)
+            .long("report")
+            .env(env::REPORT)
+            .help("Write a report of every checked toolchain to the given Markdown or HTML file")
+            .long_help("Write a self-contained report to the given file, which lists the MSRV, and every checked \
+            toolchain with its verdict, the time the check took, and the (truncated) error output of failed checks. \
+            The report is written as HTML when the file has an '.html' or '.htm' extension, and as Markdown otherwise. \
+            When the MSRV is determined for several targets, feature combinations or workspace members, a single \
+            report lists the checks of all of them, and each check is labelled with the one it belongs to. \
+            Combine with `--linear` and `--include-all-patch-releases` to check every release.")
+            .value_name("FILE")
+            .conflicts_with_all(&[id::ARG_VERIFY, id::ARG_STATIC])
+            .takes_value(true)
+        )
         .arg(custom_check())
 }
 
@@ -228,9 +480,53 @@ pub fn list() -> App<'static> {
             Arg::new(id::SUB_COMMAND_LIST_VARIANT)
                 .long("variant")
                 .takes_value(true)
-                .possible_values(&[list::DIRECT_DEPS, list::ORDERED_BY_MSRV])
+                .possible_values([list::DIRECT_DEPS, list::ORDERED_BY_MSRV, list::TREE])
                 .default_value(list::ListVariant::default().as_str()),
         )
+        .arg(
+            Arg::new(id::SUB_COMMAND_LIST_VERIFY)
+                .long("verify")
+                .help("Check the MSRV of each dependency, instead of listing the declared MSRV's")
+                .long_help(
+                    "Check the MSRV of each dependency, instead of listing the declared MSRV's. \
+                     Each dependency in the resolved dependency graph is built by itself, from a copy of its \
+                     sources in 'target/cargo-msrv/dependencies'. A dependency which declares an MSRV is \
+                     checked with that MSRV; for a dependency which does not, or which can not be built with \
+                     it, the MSRV is searched for. Dependencies with an MSRV greater than the MSRV of the \
+                     crate are flagged. When present, the `--variant` option will be ignored.",
+                )
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new(id::SUB_COMMAND_LIST_FORMAT)
+                .long("format")
+                .help("Serialize the dependency graph, instead of printing a table")
+                .long_help(
+                    "Serialize the dependency graph, instead of printing a table. The graph consists of the \
+                     dependencies of the crate, with their name, version and declared MSRV, and the edges between \
+                     them, with the kinds of dependency. A `dot` graph can be rendered by Graphviz, a `mermaid` graph \
+                     can be embedded in Markdown. When present, the `--variant` option will be ignored.",
+                )
+                .takes_value(true)
+                .possible_values([list::DOT, list::MERMAID, list::JSON])
+                .conflicts_with(id::SUB_COMMAND_LIST_VERIFY),
+        )
+        .arg(
+            Arg::new(id::SUB_COMMAND_LIST_DEP_KINDS)
+                .long("dep-kinds")
+                .help("The kinds of dependency to list, separated by commas")
+                .long_help(
+                    "The kinds of dependency to list, separated by commas. By default, the normal and build \
+                     dependencies are listed, which are needed to build the crate. Each dependency is marked with \
+                     the kinds of dependency of the crate through which it was reached.",
+                )
+                .value_name("KINDS")
+                .takes_value(true)
+                .multiple_values(true)
+                .use_delimiter(true)
+                .require_delimiter(true)
+                .possible_values([list::NORMAL, list::BUILD, list::DEV]),
+        )
 }
 
 pub fn set() -> App<'static> {
@@ -261,6 +557,91 @@ pub fn show() -> App<'static> {
 pub fn verify() -> App<'static> {
     App::new(id::SUB_COMMAND_VERIFY)
         .about("Verify whether the MSRV is satisfiable. The MSRV must be specified using the 'package.rust-version' or 'package.metadata.msrv' key in the Cargo.toml manifest.")
+        .arg(
+            Arg::new(id::SUB_COMMAND_VERIFY_RESOLVE_LOCKFILE)
+                .long("resolve-lockfile")
+                .help("Downgrade the locked dependencies which require a newer Rust version than the MSRV, before verifying")
+                .long_help(
+                    "Downgrade the locked dependencies which require a newer Rust version than the MSRV, before verifying. \
+                     Each such dependency is replaced in the Cargo.lock lockfile by its greatest version which is \
+                     compatible with the MSRV, and with the requirements of its dependents. The available versions, and \
+                     their 'rust-version', are read from the registry index given by `--registry-index`, so no network \
+                     access is required. If the MSRV can not be verified with the resolved lockfile, the original \
+                     lockfile is restored.",
+                )
+                .takes_value(false)
+                .requires(id::SUB_COMMAND_VERIFY_REGISTRY_INDEX),
+        )
+        .arg(
+            Arg::new(id::SUB_COMMAND_VERIFY_REGISTRY_INDEX)
+                .long("registry-index")
+                .help("Path to a local checkout, or mirror, of the registry index, used by `--resolve-lockfile`")
+                .value_name("INDEX")
+                .takes_value(true),
+        )
+        .arg(custom_check())
+}
+
+pub fn bump() -> App<'static> {
+    use crate::config::bump::DependencyVersion;
+    use crate::language_features::{known_features, stabilized_in};
+
+    App::new(id::SUB_COMMAND_BUMP)
+        .about("Raise the MSRV to the first Rust release which allows the given dependency versions or language features to be used.")
+        .after_help(
+            "The current MSRV is read from the Cargo manifest. The new MSRV is checked with the check command, and if it \
+            is satisfiable, it is written to the Cargo manifest in the same way as `cargo msrv set` does. A \
+            rust-toolchain file which pins a Rust version older than the new MSRV is updated as well.",
+        )
+        .arg(
+            Arg::new(id::SUB_COMMAND_BUMP_TO)
+                .long("to")
+                .help("Raise the MSRV to at least the given Rust version")
+                .value_name("VERSION")
+                .takes_value(true)
+                .validator(BareVersion::from_str),
+        )
+        .arg(
+            Arg::new(id::SUB_COMMAND_BUMP_DEPENDENCY)
+                .long("dependency")
+                .help("Raise the MSRV so the given version of a dependency can be used")
+                .long_help("Raise the MSRV so the given version of a dependency can be used. Written as \
+                'name@version', e.g. 'serde@1.0.150'. The MSRV of the dependency is read from the index of the \
+                local Cargo registry, so the crate does not have to depend on this version yet. May be given more \
+                than once.")
+                .value_name("NAME@VERSION")
+                .multiple_occurrences(true)
+                .takes_value(true)
+                .validator(DependencyVersion::from_str),
+        )
+        .arg(
+            Arg::new(id::SUB_COMMAND_BUMP_LANGUAGE_FEATURE)
+                .long("language-feature")
+                .help("Raise the MSRV so the given language feature can be used")
+                .long_help("Raise the MSRV so the given language feature can be used. Features are given by the \
+                name of their feature gate, e.g. 'let_else'. May be given more than once.")
+                .value_name("FEATURE")
+                .multiple_occurrences(true)
+                .takes_value(true)
+                .validator(|feature| match stabilized_in(feature) {
+                    Some(_) => Ok(()),
+                    None => Err(format!(
+                        "Unknown language feature '{}', expected one of: {}",
+                        feature,
+                        known_features().collect::<Vec<_>>().join(", ")
+                    )),
+                }),
+        )
+        .group(
+            ArgGroup::new("bump_targets")
+                .args(&[
+                    id::SUB_COMMAND_BUMP_TO,
+                    id::SUB_COMMAND_BUMP_DEPENDENCY,
+                    id::SUB_COMMAND_BUMP_LANGUAGE_FEATURE,
+                ])
+                .multiple(true)
+                .required(true),
+        )
         .arg(custom_check())
 }
 
@@ -1,4 +1,4 @@
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
 use std::path::Path;
 use std::process::{Command, Stdio};
 
@@ -38,6 +38,11 @@ impl RustupCommand {
         self
     }
 
+    pub fn with_env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        let _ = self.command.env(key, value);
+        self
+    }
+
     pub fn with_stdout(mut self) -> Self {
         self.stdout = Stdio::piped();
         self
@@ -0,0 +1,401 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rust_releases::semver;
+
+use crate::config::Config;
+use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
+use crate::outcome::Outcome;
+use crate::toolchain::toolchain_name;
+
+/// The error output of a failed check is truncated to this many lines in the report.
+const MAX_ERROR_LINES: usize = 30;
+
+/// The checks run during an action, in the order in which they completed. An action may run
+/// several searches, like one for each target, so each check is recorded with the scope of its
+/// search. Checks may be recorded by concurrent checks.
+#[derive(Debug, Default)]
+pub struct CheckLog {
+    checks: Mutex<Vec<CheckedToolchain>>,
+    scope: Mutex<Option<String>>,
+}
+
+impl CheckLog {
+    /// Record the outcome of a check, and how long it took, if known.
+    pub fn record(&self, outcome: &Outcome, elapsed: Option<Duration>) {
+        let scope = self.scope.lock().unwrap().clone();
+
+        self.checks.lock().unwrap().push(CheckedToolchain {
+            outcome: outcome.clone(),
+            elapsed,
+            scope,
+        });
+    }
+
+    /// Record the checks which follow within the given scope, like a target or a dependency.
+    pub fn set_scope(&self, scope: Option<String>) {
+        *self.scope.lock().unwrap() = scope;
+    }
+
+    pub fn checks(&self) -> Vec<CheckedToolchain> {
+        self.checks.lock().unwrap().clone()
+    }
+}
+
+/// The outcome of the check of a single toolchain.
+#[derive(Clone, Debug)]
+pub struct CheckedToolchain {
+    outcome: Outcome,
+    elapsed: Option<Duration>,
+    scope: Option<String>,
+}
+
+impl CheckedToolchain {
+    pub fn outcome(&self) -> &Outcome {
+        &self.outcome
+    }
+
+    /// How long the check took, if known.
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.elapsed
+    }
+
+    /// The scope of the search which ran the check, like a target or a dependency, if any.
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_deref()
+    }
+
+    fn toolchain(&self) -> String {
+        let toolchain = toolchain_name(self.outcome.version());
+
+        match &self.scope {
+            Some(scope) => format!("{} ({})", toolchain, scope),
+            None => toolchain,
+        }
+    }
+
+    fn verdict(&self) -> &'static str {
+        if self.outcome.is_success() {
+            "pass"
+        } else {
+            "fail"
+        }
+    }
+
+    fn elapsed_seconds(&self) -> String {
+        self.elapsed.map_or_else(
+            || "-".to_string(),
+            |elapsed| format!("{:.1}s", elapsed.as_secs_f64()),
+        )
+    }
+
+    fn error_output(&self) -> Option<String> {
+        match &self.outcome {
+            Outcome::Success(_) => None,
+            Outcome::Failure(failure) => Some(truncate(failure.error_message.trim())),
+        }
+    }
+}
+
+/// The format of a report file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    /// HTML for files with an `.html` or `.htm` extension, and Markdown otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("html") | Some("htm") => Self::Html,
+            _ => Self::Markdown,
+        }
+    }
+}
+
+/// A self-contained report of every toolchain checked while searching for the MSRV, and the
+/// MSRV which was found.
+#[derive(Clone, Debug)]
+pub struct CompatibilityReport {
+    target: String,
+    check_cmd: String,
+    checks: Vec<CheckedToolchain>,
+    msrv: Option<semver::Version>,
+}
+
+impl CompatibilityReport {
+    pub fn new(
+        config: &Config,
+        checks: Vec<CheckedToolchain>,
+        msrv: Option<semver::Version>,
+    ) -> Self {
+        Self {
+            target: config
+                .compile_target()
+                .unwrap_or_else(|| config.target())
+                .to_string(),
+            check_cmd: config.check_command_string(),
+            checks,
+            msrv,
+        }
+    }
+
+    /// Write the report to the given file, in the format which matches its extension.
+    pub fn write_to(&self, path: &Path) -> TResult<()> {
+        let contents = match ReportFormat::from_path(path) {
+            ReportFormat::Markdown => self.markdown(),
+            ReportFormat::Html => self.html(),
+        };
+
+        std::fs::write(path, contents).map_err(|error| CargoMSRVError::Io {
+            error,
+            source: IoErrorSource::WriteFile(path.to_path_buf()),
+        })
+    }
+
+    fn msrv_summary(&self) -> String {
+        match &self.msrv {
+            Some(version) => toolchain_name(version),
+            None => "no compatible toolchain found".to_string(),
+        }
+    }
+
+    pub fn markdown(&self) -> String {
+        let mut md = String::from("# MSRV compatibility report\n\n");
+
+        let _ = writeln!(md, "* **MSRV**: {}", self.msrv_summary());
+        let _ = writeln!(md, "* **Target**: `{}`", self.target);
+        let _ = writeln!(md, "* **Check command**: `{}`", self.check_cmd);
+        let _ = writeln!(md, "* **Checked toolchains**: {}", self.checks.len());
+
+        md.push_str("\n| Toolchain | Verdict | Elapsed |\n|---|---|---|\n");
+        for check in &self.checks {
+            let verdict = if check.outcome.is_success() {
+                "✅ pass"
+            } else {
+                "❌ fail"
+            };
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} |",
+                check.toolchain(),
+                verdict,
+                check.elapsed_seconds()
+            );
+        }
+
+        for check in &self.checks {
+            if let Some(error_output) = check.error_output() {
+                let _ = write!(
+                    md,
+                    "\n## {}\n\n```text\n{}\n```\n",
+                    check.toolchain(),
+                    error_output.replace("```", "` ` `")
+                );
+            }
+        }
+
+        md
+    }
+
+    pub fn html(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+            <title>MSRV compatibility report</title>\n<style>\n\
+            body { font-family: sans-serif; margin: 2em; }\n\
+            table { border-collapse: collapse; }\n\
+            th, td { border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: left; }\n\
+            .pass { color: #1a7f37; }\n\
+            .fail { color: #cf222e; }\n\
+            pre { background: #f6f8fa; padding: 1em; overflow-x: auto; }\n\
+            </style>\n</head>\n<body>\n<h1>MSRV compatibility report</h1>\n<ul>\n",
+        );
+
+        let _ = writeln!(
+            html,
+            "<li><strong>MSRV</strong>: {}</li>",
+            escape(&self.msrv_summary())
+        );
+        let _ = writeln!(
+            html,
+            "<li><strong>Target</strong>: <code>{}</code></li>",
+            escape(&self.target)
+        );
+        let _ = writeln!(
+            html,
+            "<li><strong>Check command</strong>: <code>{}</code></li>",
+            escape(&self.check_cmd)
+        );
+        let _ = writeln!(
+            html,
+            "<li><strong>Checked toolchains</strong>: {}</li>",
+            self.checks.len()
+        );
+
+        html.push_str("</ul>\n<table>\n<tr><th>Toolchain</th><th>Verdict</th><th>Elapsed</th><th>Error output</th></tr>\n");
+        for check in &self.checks {
+            let error_output = check.error_output().map_or_else(String::new, |output| {
+                format!(
+                    "<details><summary>show</summary><pre>{}</pre></details>",
+                    escape(&output)
+                )
+            });
+
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"{verdict}\">{verdict}</td><td>{}</td><td>{}</td></tr>",
+                escape(&check.toolchain()),
+                check.elapsed_seconds(),
+                error_output,
+                verdict = check.verdict(),
+            );
+        }
+
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+/// Write the report of the checks of an action, if a report file was given. Actions which run
+/// several searches write a single report, once all searches completed.
+pub fn write_compatibility_report(
+    config: &Config,
+    checks: &CheckLog,
+    msrv: Option<semver::Version>,
+) -> TResult<()> {
+    if let Some(path) = config.report_file() {
+        CompatibilityReport::new(config, checks.checks(), msrv).write_to(path)?;
+        eprintln!("Written compatibility report to '{}'", path.display());
+    }
+
+    Ok(())
+}
+
+/// Keeps the first lines of the error output, which usually contain the errors which matter.
+fn truncate(error_output: &str) -> String {
+    let lines = error_output.lines().count();
+
+    if lines <= MAX_ERROR_LINES {
+        return error_output.to_string();
+    }
+
+    let mut truncated = error_output
+        .lines()
+        .take(MAX_ERROR_LINES)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let _ = write!(truncated, "\n... ({} more lines)", lines - MAX_ERROR_LINES);
+    truncated
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{truncate, CheckLog, CompatibilityReport, ReportFormat};
+    use crate::config::{ConfigBuilder, ModeIntent};
+    use crate::outcome::Outcome;
+    use crate::semver::Version;
+    use crate::toolchain::OwnedToolchainSpec;
+    use std::path::Path;
+    use std::time::Duration;
+
+    fn report() -> CompatibilityReport {
+        let target = "x86_64-unknown-linux-gnu";
+        let config = ConfigBuilder::new(ModeIntent::Find, target).build();
+
+        let log = CheckLog::default();
+        log.record(
+            &Outcome::new_success(OwnedToolchainSpec::new(&Version::new(1, 58, 1), target)),
+            Some(Duration::from_millis(12_340)),
+        );
+        log.record(
+            &Outcome::new_failure(
+                OwnedToolchainSpec::new(&Version::new(1, 55, 0), target),
+                "error[E0658]: use of unstable library feature <let_else>\n".to_string(),
+            ),
+            None,
+        );
+
+        CompatibilityReport::new(&config, log.checks(), Some(Version::new(1, 58, 1)))
+    }
+
+    #[test]
+    fn markdown_report() {
+        assert_eq!(
+            report().markdown(),
+            concat!(
+                "# MSRV compatibility report\n\n",
+                "* **MSRV**: 1.58.1\n",
+                "* **Target**: `x86_64-unknown-linux-gnu`\n",
+                "* **Check command**: `cargo check`\n",
+                "* **Checked toolchains**: 2\n",
+                "\n| Toolchain | Verdict | Elapsed |\n|---|---|---|\n",
+                "| 1.58.1 | ✅ pass | 12.3s |\n",
+                "| 1.55.0 | ❌ fail | - |\n",
+                "\n## 1.55.0\n\n```text\n",
+                "error[E0658]: use of unstable library feature <let_else>\n",
+                "```\n",
+            )
+        );
+    }
+
+    #[test]
+    fn checks_are_labelled_with_their_scope() {
+        let target = "x86_64-unknown-linux-gnu";
+        let config = ConfigBuilder::new(ModeIntent::Find, target).build();
+        let outcome =
+            Outcome::new_success(OwnedToolchainSpec::new(&Version::new(1, 56, 0), target));
+
+        let log = CheckLog::default();
+        log.record(&outcome, None);
+        log.set_scope(Some("wasm32-unknown-unknown".to_string()));
+        log.record(&outcome, None);
+
+        assert_eq!(log.checks()[0].scope(), None);
+        assert_eq!(log.checks()[1].scope(), Some("wasm32-unknown-unknown"));
+
+        let markdown = CompatibilityReport::new(&config, log.checks(), None).markdown();
+        assert!(markdown.contains("| 1.56.0 | ✅ pass | - |"));
+        assert!(markdown.contains("| 1.56.0 (wasm32-unknown-unknown) | ✅ pass | - |"));
+    }
+
+    #[test]
+    fn html_report_escapes_error_output() {
+        let html = report().html();
+
+        assert!(html.contains("<td class=\"fail\">fail</td>"));
+        assert!(html.contains("feature &lt;let_else&gt;"));
+        assert!(!html.contains("<let_else>"));
+    }
+
+    #[test]
+    fn truncate_long_error_output() {
+        let output = (1..=40)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let truncated = truncate(&output);
+
+        assert_eq!(truncated.lines().count(), 31);
+        assert!(truncated.ends_with("30\n... (10 more lines)"));
+    }
+
+    #[yare::parameterized(
+        markdown = { "report.md", ReportFormat::Markdown },
+        html = { "report.html", ReportFormat::Html },
+        htm = { "report.htm", ReportFormat::Html },
+        no_extension = { "report", ReportFormat::Markdown },
+    )]
+    fn format_from_extension(path: &str, expected: ReportFormat) {
+        assert_eq!(ReportFormat::from_path(Path::new(path)), expected);
+    }
+}
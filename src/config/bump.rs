@@ -0,0 +1,126 @@
+use crate::manifest::bare_version::BareVersion;
+use crate::{semver, CargoMSRVError};
+use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BumpCmdConfig {
+    pub targets: Vec<BumpTarget>,
+}
+
+impl<'a> TryFrom<&'a ArgMatches> for BumpCmdConfig {
+    type Error = crate::CargoMSRVError;
+
+    fn try_from(args: &'a ArgMatches) -> Result<Self, Self::Error> {
+        use crate::cli::id;
+
+        let mut targets = Vec::new();
+
+        if let Some(version) = args.value_of(id::SUB_COMMAND_BUMP_TO) {
+            targets.push(BumpTarget::Version(version.parse()?));
+        }
+
+        for dependency in args
+            .values_of(id::SUB_COMMAND_BUMP_DEPENDENCY)
+            .into_iter()
+            .flatten()
+        {
+            targets.push(BumpTarget::Dependency(dependency.parse()?));
+        }
+
+        for feature in args
+            .values_of(id::SUB_COMMAND_BUMP_LANGUAGE_FEATURE)
+            .into_iter()
+            .flatten()
+        {
+            targets.push(BumpTarget::LanguageFeature(feature.to_string()));
+        }
+
+        Ok(Self { targets })
+    }
+}
+
+/// Something which requires the MSRV to be raised.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BumpTarget {
+    /// The MSRV should be at least the given Rust version
+    Version(BareVersion),
+    /// The given version of a dependency should be usable
+    Dependency(DependencyVersion),
+    /// The language feature, by the name of its feature gate, should be usable
+    LanguageFeature(String),
+}
+
+impl Display for BumpTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Version(version) => write!(f, "rust {}", version),
+            Self::Dependency(dependency) => write!(f, "{}", dependency),
+            Self::LanguageFeature(feature) => write!(f, "feature {}", feature),
+        }
+    }
+}
+
+/// A dependency at a specific version, written as `name@version`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DependencyVersion {
+    pub name: String,
+    pub version: semver::Version,
+}
+
+impl FromStr for DependencyVersion {
+    type Err = CargoMSRVError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, version) = s
+            .split_once('@')
+            .filter(|(name, _)| !name.is_empty())
+            .ok_or_else(|| {
+                CargoMSRVError::InvalidConfig(format!(
+                    "Expected a dependency written as 'name@version', but got '{}'",
+                    s
+                ))
+            })?;
+
+        Ok(Self {
+            name: name.to_string(),
+            version: version.parse()?,
+        })
+    }
+}
+
+impl Display for DependencyVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.name, self.version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DependencyVersion;
+    use crate::semver::Version;
+
+    #[test]
+    fn parse_dependency_version() {
+        let dependency = "serde@1.0.150".parse::<DependencyVersion>().unwrap();
+
+        assert_eq!(dependency.name, "serde");
+        assert_eq!(dependency.version, Version::new(1, 0, 150));
+        assert_eq!(dependency.to_string(), "serde@1.0.150");
+    }
+
+    #[yare::parameterized(
+        no_version = { "serde" },
+        no_name = { "@1.0.150" },
+        partial_version = { "serde@1.0" },
+    )]
+    fn parse_invalid_dependency_version(input: &str) {
+        assert!(input.parse::<DependencyVersion>().is_err());
+    }
+}
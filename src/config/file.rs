@@ -0,0 +1,370 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use toml_edit::easy::Value;
+
+use crate::config::source::ValueSource;
+use crate::config::SearchMethod;
+use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
+
+/// The name of the standalone configuration file, located at the crate root.
+pub const CONFIG_FILE: &str = "cargo-msrv.toml";
+
+/// The options which can be set from a configuration file, by the name of their command line
+/// flag.
+pub const CONFIGURABLE_OPTIONS: &[&str] = &[
+    "min",
+    "max",
+    "target",
+    "check-command",
+    "search-method",
+    "jobs",
+    "include-all-patch-releases",
+    "toolchain-file",
+    "ignore-lockfile",
+    "release-source",
+    "release-index-file",
+    "channel",
+    "no-check-feedback",
+    "json-diagnostics",
+    "no-read-min-edition",
+    "feature-matrix",
+    "no-cache",
+    "min-from-source",
+];
+
+/// The key of the configuration table in the `package.metadata` and `workspace.metadata` tables
+/// of a Cargo manifest.
+const METADATA_KEY: &str = "cargo-msrv";
+
+/// The options which can be set from a configuration file, or from the metadata of a Cargo
+/// manifest. Each key has the name of its command line option.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FileConfig {
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub target: Option<OneOrMany>,
+    pub check_command: Option<OneOrMany>,
+    pub search_method: Option<SearchMethod>,
+    pub jobs: Option<usize>,
+    pub include_all_patch_releases: Option<bool>,
+    pub toolchain_file: Option<bool>,
+    pub ignore_lockfile: Option<bool>,
+    pub release_source: Option<String>,
+    pub release_index_file: Option<PathBuf>,
+    pub channel: Option<String>,
+    pub no_check_feedback: Option<bool>,
+    pub json_diagnostics: Option<bool>,
+    pub no_read_min_edition: Option<bool>,
+    pub feature_matrix: Option<Vec<String>>,
+    pub no_cache: Option<bool>,
+    pub min_from_source: Option<bool>,
+}
+
+/// A value which may be written as a single string, or as an array of strings.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    pub fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::One(value) => vec![value],
+            Self::Many(values) => values,
+        }
+    }
+
+    /// Split a single string on whitespace, like a command line would be.
+    pub fn into_args(self) -> Vec<String> {
+        match self {
+            Self::One(value) => value.split_whitespace().map(String::from).collect(),
+            Self::Many(values) => values,
+        }
+    }
+}
+
+/// The configuration files of a crate, ordered from the highest to the lowest precedence:
+///
+/// 1. The standalone `cargo-msrv.toml` file at the crate root
+/// 2. The `[package.metadata.cargo-msrv]` table of the Cargo manifest of the crate
+/// 3. The `[workspace.metadata.cargo-msrv]` table of the Cargo manifest of the workspace root
+///
+/// Files which do not exist, or which do not have a configuration table, are skipped.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigFiles {
+    layers: Vec<(ValueSource, FileConfig)>,
+}
+
+impl ConfigFiles {
+    pub fn load(crate_root: &Path) -> TResult<Self> {
+        let mut layers = Vec::new();
+
+        let config_file = crate_root.join(CONFIG_FILE);
+        if config_file.is_file() {
+            let config = parse_config(&read_toml(&config_file)?, &config_file)?;
+            layers.push((ValueSource::ConfigFile(config_file), config));
+        }
+
+        let cargo_toml = crate_root.join("Cargo.toml");
+        let manifest = if cargo_toml.is_file() {
+            Some(read_toml(&cargo_toml)?)
+        } else {
+            None
+        };
+
+        if let Some(table) = manifest
+            .as_ref()
+            .and_then(|manifest| metadata_table(manifest, "package"))
+        {
+            let config = parse_config(table, &cargo_toml)?;
+            layers.push((ValueSource::PackageMetadata(cargo_toml.clone()), config));
+        }
+
+        if let Some((workspace_toml, workspace)) = workspace_manifest(crate_root, manifest)? {
+            if let Some(table) = metadata_table(&workspace, "workspace") {
+                let config = parse_config(table, &workspace_toml)?;
+                layers.push((ValueSource::WorkspaceMetadata(workspace_toml), config));
+            }
+        }
+
+        Ok(Self { layers })
+    }
+
+    /// The value of the option from the configuration file with the highest precedence which
+    /// sets it, and where it was set.
+    pub fn value<T>(&self, option: impl Fn(&FileConfig) -> Option<T>) -> Option<(T, ValueSource)> {
+        self.layers
+            .iter()
+            .find_map(|(source, config)| option(config).map(|value| (value, source.clone())))
+    }
+}
+
+fn read_toml(path: &Path) -> TResult<Value> {
+    let contents = std::fs::read_to_string(path).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFile(path.to_path_buf()),
+    })?;
+
+    toml_edit::easy::from_str(&contents).map_err(|err| invalid_config(path, err))
+}
+
+/// Parse a configuration table. Unknown options, which may be options of a newer version of
+/// cargo-msrv, are ignored with a warning, instead of failing every subcommand.
+fn parse_config(table: &Value, path: &Path) -> TResult<FileConfig> {
+    for option in unknown_options(table) {
+        eprintln!(
+            "Ignoring unknown option '{}' in the cargo-msrv configuration in '{}'",
+            option,
+            path.display()
+        );
+    }
+
+    table
+        .clone()
+        .try_into()
+        .map_err(|err| invalid_config(path, err))
+}
+
+/// The keys of a configuration table which are not one of the [`CONFIGURABLE_OPTIONS`].
+fn unknown_options(table: &Value) -> Vec<&str> {
+    table
+        .as_table()
+        .into_iter()
+        .flat_map(|table| table.keys())
+        .map(String::as_str)
+        .filter(|key| !CONFIGURABLE_OPTIONS.contains(key))
+        .collect()
+}
+
+fn invalid_config(path: &Path, err: impl std::fmt::Display) -> CargoMSRVError {
+    CargoMSRVError::InvalidConfig(format!(
+        "Unable to read the cargo-msrv configuration in '{}': {}",
+        path.display(),
+        err
+    ))
+}
+
+/// The `[<root>.metadata.cargo-msrv]` table of a Cargo manifest, where root is either `package`,
+/// or `workspace`.
+fn metadata_table<'v>(manifest: &'v Value, root: &str) -> Option<&'v Value> {
+    manifest.get(root)?.get("metadata")?.get(METADATA_KEY)
+}
+
+/// The Cargo manifest of the workspace the crate belongs to, which is the manifest of the crate
+/// itself if it is the workspace root, or the first manifest with a `[workspace]` table in one of
+/// the parent folders otherwise.
+fn workspace_manifest(
+    crate_root: &Path,
+    crate_manifest: Option<Value>,
+) -> TResult<Option<(PathBuf, Value)>> {
+    if let Some(manifest) = crate_manifest {
+        if manifest.get("workspace").is_some() {
+            return Ok(Some((crate_root.join("Cargo.toml"), manifest)));
+        }
+    }
+
+    for folder in crate_root.ancestors().skip(1) {
+        let cargo_toml = folder.join("Cargo.toml");
+
+        if cargo_toml.is_file() {
+            let manifest = read_toml(&cargo_toml)?;
+
+            if manifest.get("workspace").is_some() {
+                return Ok(Some((cargo_toml, manifest)));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{unknown_options, ConfigFiles, FileConfig, OneOrMany, CONFIG_FILE};
+    use crate::config::source::ValueSource;
+    use crate::config::SearchMethod;
+    use std::path::{Path, PathBuf};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join("cargo-msrv-test-config-files")
+            .join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn parse_all_options() {
+        let config: FileConfig = toml_edit::easy::from_str(
+            r#"
+min = "1.40.0"
+max = "1.58.1"
+target = ["x86_64-unknown-linux-gnu", "wasm32-unknown-unknown"]
+check-command = "cargo test --all"
+search-method = "linear"
+jobs = 4
+include-all-patch-releases = true
+toolchain-file = false
+ignore-lockfile = true
+release-source = "rust-dist"
+release-index-file = "index.json"
+no-check-feedback = true
+json-diagnostics = true
+no-read-min-edition = true
+feature-matrix = ["default", "no-default"]
+no-cache = true
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.min.as_deref(), Some("1.40.0"));
+        assert_eq!(
+            config.target.unwrap().into_vec(),
+            vec!["x86_64-unknown-linux-gnu", "wasm32-unknown-unknown"]
+        );
+        assert_eq!(
+            config.check_command.unwrap().into_args(),
+            vec!["cargo", "test", "--all"]
+        );
+        assert_eq!(config.search_method, Some(SearchMethod::Linear));
+        assert_eq!(config.jobs, Some(4));
+        assert_eq!(config.release_index_file, Some(PathBuf::from("index.json")));
+    }
+
+    #[test]
+    fn unknown_option_is_ignored() {
+        let table: toml_edit::easy::Value =
+            toml_edit::easy::from_str("mni = \"1.40\"\nmax = \"1.50\"\n").unwrap();
+
+        assert_eq!(unknown_options(&table), vec!["mni"]);
+
+        let config: FileConfig = table.try_into().unwrap();
+        assert_eq!(config.min, None);
+        assert_eq!(config.max.as_deref(), Some("1.50"));
+    }
+
+    #[test]
+    fn precedence_of_config_files() {
+        let workspace = temp_dir("precedence");
+        let member = workspace.join("member");
+
+        write(
+            &workspace.join("Cargo.toml"),
+            r#"
+[workspace]
+members = ["member"]
+
+[workspace.metadata.cargo-msrv]
+min = "1.40.0"
+max = "1.50"
+jobs = 2
+"#,
+        );
+        write(
+            &member.join("Cargo.toml"),
+            r#"
+[package]
+name = "member"
+version = "0.1.0"
+
+[package.metadata.cargo-msrv]
+max = "1.55"
+check-command = ["cargo", "test"]
+"#,
+        );
+        write(
+            &member.join(CONFIG_FILE),
+            "check-command = \"cargo build\"\n",
+        );
+
+        let files = ConfigFiles::load(&member).unwrap();
+
+        assert_eq!(
+            files.value(|config| config.check_command.clone()),
+            Some((
+                OneOrMany::One("cargo build".to_string()),
+                ValueSource::ConfigFile(member.join(CONFIG_FILE))
+            ))
+        );
+        assert_eq!(
+            files.value(|config| config.max.clone()),
+            Some((
+                "1.55".to_string(),
+                ValueSource::PackageMetadata(member.join("Cargo.toml"))
+            ))
+        );
+        assert_eq!(
+            files.value(|config| config.jobs),
+            Some((
+                2,
+                ValueSource::WorkspaceMetadata(workspace.join("Cargo.toml"))
+            ))
+        );
+        assert!(files.value(|config| config.no_cache).is_none());
+
+        std::fs::remove_dir_all(&workspace).unwrap();
+    }
+
+    #[test]
+    fn invalid_metadata_is_an_error() {
+        let root = temp_dir("invalid");
+        write(
+            &root.join("Cargo.toml"),
+            "[package]\nname = \"a\"\n\n[package.metadata.cargo-msrv]\njobs = \"many\"\n",
+        );
+
+        assert!(ConfigFiles::load(&root).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,9 +1,26 @@
+use cargo_metadata::DependencyKind;
 use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
 use std::{convert::TryFrom, str::FromStr};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct ListCmdConfig {
     pub variant: ListVariant,
+    /// Check each dependency with its declared MSRV, or search for its MSRV when it has none,
+    /// instead of only listing the declared MSRV's
+    #[serde(default)]
+    pub verify: bool,
+    /// Serialize the dependency graph in this format, instead of printing a table
+    #[serde(default)]
+    pub format: Option<GraphFormat>,
+    /// The kinds of dependency which are included in the dependency graph
+    #[serde(default = "default_dependency_kinds")]
+    pub dep_kinds: Vec<DependencyKind>,
+}
+
+fn default_dependency_kinds() -> Vec<DependencyKind> {
+    crate::dependencies::DEFAULT_DEPENDENCY_KINDS.to_vec()
 }
 
 impl<'a> TryFrom<&'a ArgMatches> for ListCmdConfig {
@@ -18,18 +35,58 @@ impl<'a> TryFrom<&'a ArgMatches> for ListCmdConfig {
             ListVariant::default()
         };
 
-        Ok(ListCmdConfig { variant })
+        let verify = args.is_present(id::SUB_COMMAND_LIST_VERIFY);
+
+        let format = args
+            .value_of(id::SUB_COMMAND_LIST_FORMAT)
+            .map(GraphFormat::from_str)
+            .transpose()?;
+
+        let dep_kinds = match args.values_of(id::SUB_COMMAND_LIST_DEP_KINDS) {
+            Some(kinds) => kinds
+                .map(parse_dependency_kind)
+                .collect::<Result<Vec<_>, _>>()?,
+            None => default_dependency_kinds(),
+        };
+
+        Ok(ListCmdConfig {
+            variant,
+            verify,
+            format,
+            dep_kinds,
+        })
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+pub(crate) const NORMAL: &str = "normal";
+pub(crate) const BUILD: &str = "build";
+pub(crate) const DEV: &str = "dev";
+
+fn parse_dependency_kind(kind: &str) -> Result<DependencyKind, crate::CargoMSRVError> {
+    Ok(match kind {
+        NORMAL => DependencyKind::Normal,
+        BUILD => DependencyKind::Build,
+        DEV => DependencyKind::Development,
+        elsy => {
+            return Err(crate::CargoMSRVError::InvalidConfig(format!(
+                "No such dependency kind '{}'",
+                elsy
+            )))
+        }
+    })
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ListVariant {
     DirectDeps,
     OrderedByMSRV,
+    Tree,
 }
 
 pub(crate) const DIRECT_DEPS: &str = "direct-deps";
 pub(crate) const ORDERED_BY_MSRV: &str = "ordered-by-msrv";
+pub(crate) const TREE: &str = "tree";
 
 impl FromStr for ListVariant {
     type Err = crate::CargoMSRVError;
@@ -38,6 +95,7 @@ impl FromStr for ListVariant {
         Ok(match s {
             DIRECT_DEPS => Self::DirectDeps,
             ORDERED_BY_MSRV => Self::OrderedByMSRV,
+            TREE => Self::Tree,
             elsy => {
                 return Err(crate::CargoMSRVError::InvalidConfig(format!(
                     "No such list variant '{}'",
@@ -53,6 +111,7 @@ impl ListVariant {
         match self {
             Self::DirectDeps => DIRECT_DEPS,
             Self::OrderedByMSRV => ORDERED_BY_MSRV,
+            Self::Tree => TREE,
         }
     }
 }
@@ -62,3 +121,44 @@ impl Default for ListVariant {
         Self::OrderedByMSRV
     }
 }
+
+/// The formats in which the dependency graph can be serialized.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+pub(crate) const DOT: &str = "dot";
+pub(crate) const MERMAID: &str = "mermaid";
+pub(crate) const JSON: &str = "json";
+
+impl FromStr for GraphFormat {
+    type Err = crate::CargoMSRVError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            DOT => Self::Dot,
+            MERMAID => Self::Mermaid,
+            JSON => Self::Json,
+            elsy => {
+                return Err(crate::CargoMSRVError::InvalidConfig(format!(
+                    "No such graph format '{}'",
+                    elsy
+                )))
+            }
+        })
+    }
+}
+
+impl GraphFormat {
+    pub(crate) const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dot => DOT,
+            Self::Mermaid => MERMAID,
+            Self::Json => JSON,
+        }
+    }
+}
@@ -1,18 +1,32 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 use toml_edit::{Document, Item};
 
+use crate::config::bump::BumpCmdConfig;
+use crate::config::file::{ConfigFiles, FileConfig, OneOrMany};
 use crate::config::list::ListCmdConfig;
 use crate::config::set::SetCmdConfig;
-use clap::ArgMatches;
+use crate::config::source::ValueSource;
+use crate::config::verify::VerifyCmdConfig;
+use clap::{ArgMatches, ArgSettings, Values};
 use rust_releases::semver;
 
 use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
+use crate::feature_matrix::{inject_feature_flags, FeatureCombination, FeatureMatrixEntry};
+use crate::toolchain::DatedToolchain;
 
-pub(crate) mod list;
+pub(crate) mod bump;
+pub mod file;
+pub mod list;
 pub(crate) mod set;
+pub mod source;
+pub(crate) mod verify;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum OutputFormat {
     /// Progress bar rendered to stderr
     Human,
@@ -22,6 +36,10 @@ pub enum OutputFormat {
     None,
     /// Save all versions tested and save success result for all runs -- meant to be used for testing
     TestSuccesses,
+    /// A JUnit XML report of the checked toolchains, printed to stdout when cargo-msrv finishes
+    Junit,
+    /// A SARIF log of the failed MSRV verifications, printed to stdout when cargo-msrv finishes
+    Sarif,
 }
 
 impl Default for OutputFormat {
@@ -32,11 +50,13 @@ impl Default for OutputFormat {
 
 impl OutputFormat {
     pub const JSON: &'static str = "json";
+    pub const JUNIT: &'static str = "junit";
+    pub const SARIF: &'static str = "sarif";
 
     /// A set of formats which may be given as a configuration option
     ///   through the CLI.
     pub fn custom_formats() -> &'static [&'static str] {
-        &[Self::JSON]
+        &[Self::JSON, Self::JUNIT, Self::SARIF]
     }
 
     /// Parse the output format from the given `&str`.
@@ -47,6 +67,8 @@ impl OutputFormat {
     pub fn from_custom_format_str(item: &str) -> Self {
         match item {
             Self::JSON => Self::Json,
+            Self::JUNIT => Self::Junit,
+            Self::SARIF => Self::Sarif,
             _ => unreachable!(),
         }
     }
@@ -58,10 +80,12 @@ impl OutputFormat {
 pub fn test_config_from_matches(matches: &ArgMatches) -> TResult<Config> {
     let mut config = Config::try_from(matches)?;
     config.output_format = OutputFormat::None;
+    config.cache_folder = Some(std::env::temp_dir().join("cargo-msrv-test-cache"));
     Ok(config)
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ModeIntent {
     // Determines the MSRV for a project
     Find,
@@ -73,6 +97,8 @@ pub enum ModeIntent {
     Set,
     // Shows the MSRV of the current crate as specified in the Cargo manifest
     Show,
+    // Raise the MSRV in the Cargo manifest, so the given dependencies or language features can be used
+    Bump,
 }
 
 impl From<ModeIntent> for &'static str {
@@ -83,14 +109,20 @@ impl From<ModeIntent> for &'static str {
             ModeIntent::Verify => "verify-msrv",
             ModeIntent::Set => "set-msrv",
             ModeIntent::Show => "show-msrv",
+            ModeIntent::Bump => "bump-msrv",
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ReleaseSource {
     RustChangelog,
     RustDist,
+    /// A local file, see [`Config::release_index_file`]
+    File,
+    /// The toolchains installed with rustup
+    Installed,
 }
 
 impl From<ReleaseSource> for &'static str {
@@ -98,6 +130,8 @@ impl From<ReleaseSource> for &'static str {
         match value {
             ReleaseSource::RustChangelog => "rust-changelog",
             ReleaseSource::RustDist => "rust-dist",
+            ReleaseSource::File => "file",
+            ReleaseSource::Installed => "installed",
         }
     }
 }
@@ -109,15 +143,61 @@ impl TryFrom<&str> for ReleaseSource {
         match source {
             "rust-changelog" => Ok(Self::RustChangelog),
             "rust-dist" => Ok(Self::RustDist),
+            "file" => Ok(Self::File),
+            "installed" => Ok(Self::Installed),
             s => Err(CargoMSRVError::RustReleasesSourceParseError(s.to_string())),
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// The release channel of the toolchains which are searched.
+///
+/// The toolchains of the beta and nightly channels are identified by their release date, see
+/// [`DatedToolchain`](crate::toolchain::DatedToolchain).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReleaseChannel {
+    Stable,
+    Beta,
+    Nightly,
+}
+
+impl ReleaseChannel {
+    /// Whether the toolchains of this channel are identified by their release date.
+    pub fn is_dated(self) -> bool {
+        !matches!(self, Self::Stable)
+    }
+}
+
+impl From<ReleaseChannel> for &'static str {
+    fn from(value: ReleaseChannel) -> Self {
+        match value {
+            ReleaseChannel::Stable => "stable",
+            ReleaseChannel::Beta => "beta",
+            ReleaseChannel::Nightly => "nightly",
+        }
+    }
+}
+
+impl TryFrom<&str> for ReleaseChannel {
+    type Error = CargoMSRVError;
+
+    fn try_from(channel: &str) -> Result<Self, Self::Error> {
+        match channel {
+            "stable" => Ok(Self::Stable),
+            "beta" => Ok(Self::Beta),
+            "nightly" => Ok(Self::Nightly),
+            s => Err(CargoMSRVError::ReleaseChannelParseError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum SearchMethod {
     Linear,
     Bisect,
+    Parallel,
 }
 
 impl From<SearchMethod> for &'static str {
@@ -125,6 +205,7 @@ impl From<SearchMethod> for &'static str {
         match method {
             SearchMethod::Linear => "linear",
             SearchMethod::Bisect => "bisect",
+            SearchMethod::Parallel => "parallel",
         }
     }
 }
@@ -135,46 +216,79 @@ impl Default for SearchMethod {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Config<'a> {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
     mode_intent: ModeIntent,
     target: String,
-    check_command: Vec<&'a str>,
+    compile_targets: Vec<String>,
+    compile_target: Option<String>,
+    check_command: Vec<String>,
     crate_path: Option<PathBuf>,
     include_all_patch_releases: bool,
     minimum_version: Option<semver::Version>,
     maximum_version: Option<semver::Version>,
     search_method: SearchMethod,
+    jobs: usize,
     output_toolchain_file: bool,
     ignore_lockfile: bool,
     output_format: OutputFormat,
     release_source: ReleaseSource,
+    release_index_file: Option<PathBuf>,
+    release_channel: ReleaseChannel,
     tracing_config: Option<TracingOptions>,
     no_read_min_edition: Option<semver::Version>,
     no_check_feedback: bool,
+    json_diagnostics: bool,
+    workspace: bool,
+    feature_matrix: Vec<FeatureMatrixEntry>,
+    no_cache: bool,
+    cache_folder: Option<PathBuf>,
+    static_msrv: bool,
+    min_from_source: bool,
+    unstable_features: bool,
+    report_file: Option<PathBuf>,
 
     sub_command_config: SubCommandConfig,
+
+    #[serde(skip)]
+    value_sources: BTreeMap<&'static str, ValueSource>,
 }
 
-impl<'a> Config<'a> {
+impl Config {
     pub fn new(mode_intent: ModeIntent, target: String) -> Self {
         Self {
             mode_intent,
             target,
-            check_command: vec!["cargo", "check"],
+            compile_targets: Vec::new(),
+            compile_target: None,
+            check_command: vec!["cargo".to_string(), "check".to_string()],
             crate_path: None,
             include_all_patch_releases: false,
             minimum_version: None,
             maximum_version: None,
             search_method: SearchMethod::default(),
+            jobs: num_cpus::get(),
             output_toolchain_file: false,
             ignore_lockfile: false,
             output_format: OutputFormat::Human,
             release_source: ReleaseSource::RustChangelog,
+            release_index_file: None,
+            release_channel: ReleaseChannel::Stable,
             tracing_config: None,
             no_read_min_edition: None,
             no_check_feedback: false,
+            json_diagnostics: false,
+            workspace: false,
+            feature_matrix: Vec::new(),
+            no_cache: false,
+            cache_folder: None,
+            static_msrv: false,
+            min_from_source: false,
+            unstable_features: false,
+            report_file: None,
             sub_command_config: SubCommandConfig::None,
+            value_sources: BTreeMap::new(),
         }
     }
 
@@ -186,7 +300,20 @@ impl<'a> Config<'a> {
         &self.target
     }
 
-    pub fn check_command(&self) -> &Vec<&'a str> {
+    /// The targets for which the MSRV should be determined separately, when more than one
+    /// target was given. The toolchains used to check these targets are those of [`Config::target`].
+    pub fn compile_targets(&self) -> &[String] {
+        &self.compile_targets
+    }
+
+    /// The target to which the check command compiles, if it differs from the target of the
+    /// toolchain. The `rust-std` component for this target is added to each toolchain before
+    /// it is checked.
+    pub fn compile_target(&self) -> Option<&str> {
+        self.compile_target.as_deref()
+    }
+
+    pub fn check_command(&self) -> &[String] {
         &self.check_command
     }
 
@@ -214,6 +341,12 @@ impl<'a> Config<'a> {
         self.search_method
     }
 
+    /// The amount of toolchains which are checked at once, when the search method is
+    /// [`SearchMethod::Parallel`]. Defaults to the number of logical CPUs.
+    pub fn jobs(&self) -> usize {
+        self.jobs
+    }
+
     pub fn output_toolchain_file(&self) -> bool {
         self.output_toolchain_file
     }
@@ -230,6 +363,24 @@ impl<'a> Config<'a> {
         self.release_source
     }
 
+    /// Whether toolchains which are not yet installed should be installed before running a check.
+    ///
+    /// When the release index consists of the installed toolchains only, nothing will be installed.
+    pub fn install_toolchains(&self) -> bool {
+        !matches!(self.release_source, ReleaseSource::Installed)
+    }
+
+    /// The local file from which the release index is built, when the release source is
+    /// [`ReleaseSource::File`].
+    pub fn release_index_file(&self) -> Option<&Path> {
+        self.release_index_file.as_deref()
+    }
+
+    /// The release channel of the toolchains which are searched.
+    pub fn release_channel(&self) -> ReleaseChannel {
+        self.release_channel
+    }
+
     /// Options as to configure tracing (and logging) settings. If absent, tracing will be disabled.
     pub fn tracing(&self) -> Option<&TracingOptions> {
         self.tracing_config.as_ref()
@@ -243,24 +394,103 @@ impl<'a> Config<'a> {
         self.no_check_feedback
     }
 
+    /// Whether cargo check commands should be run with `--message-format=json`, so the errors of
+    /// the compiler can be collected as structured diagnostics.
+    pub fn json_diagnostics(&self) -> bool {
+        self.json_diagnostics
+    }
+
+    /// Whether the MSRV should be determined for each member of the workspace.
+    pub fn workspace(&self) -> bool {
+        self.workspace
+    }
+
+    /// The feature combinations for which the MSRV should be determined. When empty, the MSRV
+    /// is only determined for the check command as given.
+    pub fn feature_matrix(&self) -> &[FeatureMatrixEntry] {
+        &self.feature_matrix
+    }
+
+    /// Whether the outcome of checks should bypass the check cache.
+    pub fn no_cache(&self) -> bool {
+        self.no_cache
+    }
+
+    /// The folder in which the outcome of checks are cached. When not set, the cache is located
+    /// in the cargo-msrv data folder.
+    pub fn cache_folder(&self) -> Option<&Path> {
+        self.cache_folder.as_deref()
+    }
+
+    /// Whether a lower bound on the MSRV should be determined from the crate sources only,
+    /// instead of searching for the MSRV by running the check command.
+    pub fn static_msrv(&self) -> bool {
+        self.static_msrv
+    }
+
+    /// Whether the lower bound on the MSRV, determined from the crate sources, should be used as
+    /// the minimum version of the search, when it is greater than [`Config::minimum_version`].
+    pub fn min_from_source(&self) -> bool {
+        self.min_from_source
+    }
+
+    /// Whether the oldest nightly toolchain which compiles the crate should be found, and the
+    /// unstable features enabled by the crate should be reported.
+    pub fn unstable_features(&self) -> bool {
+        self.unstable_features
+    }
+
+    /// The file to which a report of all checked toolchains should be written, if any.
+    pub fn report_file(&self) -> Option<&Path> {
+        self.report_file.as_deref()
+    }
+
     pub fn sub_command_config(&self) -> &SubCommandConfig {
         &self.sub_command_config
     }
+
+    /// The platforms (target triples) for which the dependencies of the crate are considered, or
+    /// an empty list if all platforms should be considered. Only targets which were given
+    /// explicitly restrict the platforms, the default target of the host does not.
+    pub fn platforms(&self) -> Vec<&str> {
+        if !self.compile_targets.is_empty() {
+            self.compile_targets.iter().map(String::as_str).collect()
+        } else if let Some(target) = self.compile_target() {
+            vec![target]
+        } else if self.value_source("target") != ValueSource::Default {
+            vec![self.target.as_str()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Where the value of the given option, by the name of its command line flag, came from.
+    pub fn value_source(&self, option: &str) -> ValueSource {
+        self.value_sources.get(option).cloned().unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone)]
 #[must_use]
-pub struct ConfigBuilder<'a> {
-    inner: Config<'a>,
+pub struct ConfigBuilder {
+    inner: Config,
 }
 
-impl<'a> ConfigBuilder<'a> {
+impl ConfigBuilder {
     pub fn new(action_intent: ModeIntent, default_target: &str) -> Self {
         Self {
             inner: Config::new(action_intent, default_target.to_string()),
         }
     }
 
+    /// Start from an existing [`Config`], for example to derive the configuration of a single
+    /// workspace member from the configuration of the workspace.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            inner: config.clone(),
+        }
+    }
+
     pub fn mode_intent(mut self, mode_intent: ModeIntent) -> Self {
         self.inner.mode_intent = mode_intent;
         self
@@ -271,8 +501,25 @@ impl<'a> ConfigBuilder<'a> {
         self
     }
 
-    pub fn check_command(mut self, cmd: Vec<&'a str>) -> Self {
-        self.inner.check_command = cmd;
+    pub fn check_command<I, S>(mut self, cmd: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.inner.check_command = cmd.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn compile_targets(mut self, targets: Vec<String>) -> Self {
+        self.inner.compile_targets = targets;
+        self
+    }
+
+    /// Compile for the given target, by injecting `--target <target>` into the check command.
+    pub fn compile_target(mut self, target: &str) -> Self {
+        self.inner.compile_target = Some(target.to_string());
+        self.inner.check_command =
+            inject_check_args(&self.inner.check_command, ["--target", target]);
         self
     }
 
@@ -301,6 +548,11 @@ impl<'a> ConfigBuilder<'a> {
         self
     }
 
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.inner.jobs = jobs;
+        self
+    }
+
     pub fn output_toolchain_file(mut self, choice: bool) -> Self {
         self.inner.output_toolchain_file = choice;
         self
@@ -321,6 +573,16 @@ impl<'a> ConfigBuilder<'a> {
         self
     }
 
+    pub fn release_index_file<P: AsRef<Path>>(mut self, path: Option<P>) -> Self {
+        self.inner.release_index_file = path.map(|p| PathBuf::from(p.as_ref()));
+        self
+    }
+
+    pub fn release_channel(mut self, release_channel: ReleaseChannel) -> Self {
+        self.inner.release_channel = release_channel;
+        self
+    }
+
     pub fn tracing_config(mut self, cfg: TracingOptions) -> Self {
         self.inner.tracing_config = Some(cfg);
         self
@@ -336,20 +598,88 @@ impl<'a> ConfigBuilder<'a> {
         self
     }
 
+    pub fn json_diagnostics(mut self, choice: bool) -> Self {
+        self.inner.json_diagnostics = choice;
+        self
+    }
+
+    pub fn workspace(mut self, choice: bool) -> Self {
+        self.inner.workspace = choice;
+        self
+    }
+
+    pub fn feature_matrix(mut self, entries: Vec<FeatureMatrixEntry>) -> Self {
+        self.inner.feature_matrix = entries;
+        self
+    }
+
+    /// Enable the given feature combination, by injecting its flags into the check command.
+    pub fn feature_combination(mut self, combination: &FeatureCombination) -> Self {
+        self.inner.check_command = inject_feature_flags(&self.inner.check_command, combination);
+        self
+    }
+
+    pub fn no_cache(mut self, choice: bool) -> Self {
+        self.inner.no_cache = choice;
+        self
+    }
+
+    pub fn cache_folder<P: AsRef<Path>>(mut self, path: Option<P>) -> Self {
+        self.inner.cache_folder = path.map(|p| PathBuf::from(p.as_ref()));
+        self
+    }
+
+    pub fn static_msrv(mut self, choice: bool) -> Self {
+        self.inner.static_msrv = choice;
+        self
+    }
+
+    pub fn min_from_source(mut self, choice: bool) -> Self {
+        self.inner.min_from_source = choice;
+        self
+    }
+
+    pub fn unstable_features(mut self, choice: bool) -> Self {
+        self.inner.unstable_features = choice;
+        self
+    }
+
+    pub fn report_file<P: AsRef<Path>>(mut self, path: Option<P>) -> Self {
+        self.inner.report_file = path.map(|p| PathBuf::from(p.as_ref()));
+        self
+    }
+
     pub fn sub_command_config(mut self, cmd_config: SubCommandConfig) -> Self {
         self.inner.sub_command_config = cmd_config;
         self
     }
 
-    pub fn build(self) -> Config<'a> {
+    /// Record where the value of the given option came from.
+    pub fn value_source(mut self, option: &'static str, source: ValueSource) -> Self {
+        self.inner.value_sources.insert(option, source);
+        self
+    }
+
+    pub fn build(self) -> Config {
         self.inner
     }
 }
 
-impl<'config> TryFrom<&'config ArgMatches> for Config<'config> {
+impl TryFrom<&ArgMatches> for Config {
     type Error = CargoMSRVError;
 
-    fn try_from(matches: &'config ArgMatches) -> Result<Self, Self::Error> {
+    fn try_from(matches: &ArgMatches) -> Result<Self, Self::Error> {
+        Self::try_from_matches_and_env(matches, |name| std::env::var(name).ok())
+    }
+}
+
+impl Config {
+    /// Like [`Config::try_from`], but the environment variables of the options are looked up by
+    /// `var`, instead of in the environment of the process.
+    pub(crate) fn try_from_matches_and_env(
+        matches: &ArgMatches,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> TResult<Self> {
         use crate::cli::id;
         use crate::fetch::default_target;
 
@@ -359,6 +689,8 @@ impl<'config> TryFrom<&'config ArgMatches> for Config<'config> {
             ModeIntent::Show
         } else if matches.subcommand_matches(id::SUB_COMMAND_SET).is_some() {
             ModeIntent::Set
+        } else if matches.subcommand_matches(id::SUB_COMMAND_BUMP).is_some() {
+            ModeIntent::Bump
         } else if matches.subcommand_matches(id::SUB_COMMAND_VERIFY).is_some()
             || matches.is_present(id::ARG_VERIFY)
         {
@@ -372,34 +704,123 @@ impl<'config> TryFrom<&'config ArgMatches> for Config<'config> {
 
         let mut builder = ConfigBuilder::new(action_intent, &target);
 
-        // set the command which will be used to check if a project can build
-        builder = match matches.subcommand_matches(id::SUB_COMMAND_VERIFY) {
-            Some(verify_cmd) => set_custom_check_command(verify_cmd, builder),
-            None => set_custom_check_command(matches, builder),
-        };
+        let options = GivenOptions::new(matches, &var)?;
 
         // set the cargo workspace path
-        let crate_path = matches.value_of(id::ARG_SEEK_PATH);
+        let crate_path = options.value_of(id::ARG_SEEK_PATH);
         builder = builder.crate_path(crate_path);
 
-        // set a custom target
-        let custom_target = matches.value_of(id::ARG_SEEK_CUSTOM_TARGET);
-        if let Some(target) = custom_target {
-            builder = builder.target(target);
+        let crate_folder = match crate_path {
+            Some(path) => PathBuf::from(path),
+            None => std::env::current_dir().map_err(|error| CargoMSRVError::Io {
+                error,
+                source: IoErrorSource::CurrentDir,
+            })?,
+        };
+
+        // options which are not given on the command line, may be set in a configuration file
+        let files = ConfigFiles::load(&crate_folder)?;
+
+        // set the command which will be used to check if a project can build
+        let check_matches = matches
+            .subcommand_matches(id::SUB_COMMAND_VERIFY)
+            .or_else(|| matches.subcommand_matches(id::SUB_COMMAND_BUMP))
+            .unwrap_or(matches);
+
+        if let Some((check_command, source)) = layered(
+            check_matches
+                .values_of(id::ARG_CUSTOM_CHECK)
+                .map(|cmd| (cmd.map(String::from).collect(), ValueSource::Cli)),
+            &files,
+            |file| file.check_command.clone().map(OneOrMany::into_args),
+        ) {
+            builder = builder
+                .check_command(check_command)
+                .value_source("check-command", source);
         }
 
-        match matches.value_of(id::ARG_MIN) {
-            Some(min) => builder = builder.minimum_version(parse_version(min)?),
-            None if matches.is_present(id::ARG_NO_READ_MIN_EDITION) => {}
+        let feature_matrix = match feature_matrix(&options)? {
+            entries if !entries.is_empty() => {
+                let source = options
+                    .source(id::ARG_FEATURE_MATRIX)
+                    .or_else(|| options.source(id::ARG_FEATURE_SET))
+                    .unwrap_or(ValueSource::Cli);
+
+                Some((entries, source))
+            }
+            _ => files
+                .value(|file| file.feature_matrix.clone())
+                .map(|(entries, source)| {
+                    let entries = entries
+                        .iter()
+                        .map(|entry| FeatureMatrixEntry::try_from(entry.as_str()))
+                        .collect::<TResult<Vec<_>>>()?;
+
+                    Ok::<_, CargoMSRVError>((entries, source))
+                })
+                .transpose()?,
+        };
+
+        if feature_matrix.is_some() && options.is_present(id::ARG_WORKSPACE) {
+            return Err(CargoMSRVError::InvalidConfig(
+                "A feature matrix can not be combined with --workspace".to_string(),
+            ));
+        }
+
+        // set a custom target, or the targets which should be checked separately
+        if let Some((custom_targets, source)) = layered(
+            given(
+                &options,
+                id::ARG_SEEK_CUSTOM_TARGET,
+                options
+                    .values_of(id::ARG_SEEK_CUSTOM_TARGET)
+                    .map(|targets| targets.map(String::from).collect::<Vec<_>>()),
+            ),
+            &files,
+            |file| file.target.clone().map(OneOrMany::into_vec),
+        ) {
+            match custom_targets.as_slice() {
+                [] => {}
+                [target] => builder = builder.target(target),
+                _ if options.is_present(id::ARG_WORKSPACE) || feature_matrix.is_some() => {
+                    return Err(CargoMSRVError::InvalidConfig(
+                        "Multiple targets can not be combined with --workspace, --feature-matrix or --feature-set"
+                            .to_string(),
+                    ));
+                }
+                _ => builder = builder.compile_targets(custom_targets),
+            }
+
+            builder = builder.value_source("target", source);
+        }
+
+        let no_read_min_edition =
+            layered_flag(&options, id::ARG_NO_READ_MIN_EDITION, &files, |file| {
+                file.no_read_min_edition
+            });
+
+        if let Some((_, source)) = &no_read_min_edition {
+            builder = builder.value_source("no-read-min-edition", source.clone());
+        }
+
+        match layered(
+            given(
+                &options,
+                id::ARG_MIN,
+                options.value_of(id::ARG_MIN).map(String::from),
+            ),
+            &files,
+            |file| file.min.clone(),
+        ) {
+            Some((min, source)) => {
+                builder = builder
+                    .minimum_version(parse_version(&min)?)
+                    .value_source("min", source);
+            }
+            None if matches!(no_read_min_edition, Some((true, _))) => {}
+            // The edition of the workspace root says nothing about the edition of its members
+            None if options.is_present(id::ARG_WORKSPACE) => {}
             None => {
-                let crate_folder = if let Some(ref path) = builder.inner.crate_path {
-                    Ok(path.clone())
-                } else {
-                    std::env::current_dir().map_err(|error| CargoMSRVError::Io {
-                        error,
-                        source: IoErrorSource::CurrentDir,
-                    })
-                }?;
                 let cargo_toml = crate_folder.join("Cargo.toml");
 
                 let contents =
@@ -423,55 +844,213 @@ impl<'config> TryFrom<&'config ArgMatches> for Config<'config> {
             }
         }
 
-        if let Some(max) = matches.value_of(id::ARG_MAX) {
-            builder = builder.maximum_version(rust_releases::semver::Version::parse(max)?);
+        if let Some((max, source)) = layered(
+            given(
+                &options,
+                id::ARG_MAX,
+                options.value_of(id::ARG_MAX).map(String::from),
+            ),
+            &files,
+            |file| file.max.clone(),
+        ) {
+            builder = builder
+                .maximum_version(parse_toolchain_version(&max)?)
+                .value_source("max", source);
         }
 
-        builder = match (
-            matches.is_present(id::ARG_LINEAR),
-            matches.is_present(id::ARG_BISECT),
-        ) {
-            (true, false) => builder.search_method(SearchMethod::Linear),
-            (false, true) => builder.search_method(SearchMethod::Bisect),
-            _ => builder.search_method(SearchMethod::default()),
-        };
+        let search_method = layered(given_search_method(matches, &var)?, &files, |file| {
+            file.search_method
+        });
 
-        builder = builder
-            .include_all_patch_releases(matches.is_present(id::ARG_INCLUDE_ALL_PATCH_RELEASES));
+        if let Some((search_method, source)) = &search_method {
+            builder = builder
+                .search_method(*search_method)
+                .value_source("search-method", source.clone());
+        }
+
+        let jobs = options
+            .value_of(id::ARG_JOBS)
+            .map(|jobs| {
+                jobs.parse::<usize>().map_err(|_| {
+                    CargoMSRVError::InvalidConfig(format!("Invalid number of jobs '{}'", jobs))
+                })
+            })
+            .transpose()?;
+        let jobs = given(&options, id::ARG_JOBS, jobs);
+
+        if jobs.is_some() && !matches!(search_method, Some((SearchMethod::Parallel, _))) {
+            return Err(CargoMSRVError::InvalidConfig(
+                "The number of jobs can only be set for the parallel search method".to_string(),
+            ));
+        }
 
-        builder = builder.output_toolchain_file(matches.is_present(id::ARG_TOOLCHAIN_FILE));
+        if let Some((jobs, source)) = layered(jobs, &files, |file| file.jobs) {
+            builder = builder.jobs(jobs).value_source("jobs", source);
+        }
 
-        builder = builder.ignore_lockfile(matches.is_present(id::ARG_IGNORE_LOCKFILE));
+        if let Some((choice, source)) = layered_flag(
+            &options,
+            id::ARG_INCLUDE_ALL_PATCH_RELEASES,
+            &files,
+            |file| file.include_all_patch_releases,
+        ) {
+            builder = builder
+                .include_all_patch_releases(choice)
+                .value_source("include-all-patch-releases", source);
+        }
+
+        if let Some((choice, source)) =
+            layered_flag(&options, id::ARG_TOOLCHAIN_FILE, &files, |file| {
+                file.toolchain_file
+            })
+        {
+            builder = builder
+                .output_toolchain_file(choice)
+                .value_source("toolchain-file", source);
+        }
+
+        if let Some((choice, source)) =
+            layered_flag(&options, id::ARG_IGNORE_LOCKFILE, &files, |file| {
+                file.ignore_lockfile
+            })
+        {
+            builder = builder
+                .ignore_lockfile(choice)
+                .value_source("ignore-lockfile", source);
+        }
 
-        if matches.is_present(id::ARG_NO_USER_OUTPUT) {
+        if options.is_present(id::ARG_NO_USER_OUTPUT) {
             builder = builder.output_format(OutputFormat::None);
-        } else if let Some(output_format) = matches.value_of(id::ARG_OUTPUT_FORMAT) {
+        } else if let Some(output_format) = options.value_of(id::ARG_OUTPUT_FORMAT) {
             let output_format = OutputFormat::from_custom_format_str(output_format);
             builder = builder.output_format(output_format);
         }
 
-        let release_source = matches.value_of(id::ARG_RELEASE_SOURCE);
-        if let Some(release_source) = release_source {
-            let release_source = ReleaseSource::try_from(release_source)?;
-            builder = builder.release_source(release_source);
+        // the release source has a default value on the command line, which is not considered
+        // to be given, so the configuration files take precedence over it
+        let release_source = given(
+            &options,
+            id::ARG_RELEASE_SOURCE,
+            options.value_of(id::ARG_RELEASE_SOURCE).map(String::from),
+        );
+
+        if let Some((release_source, source)) =
+            layered(release_source, &files, |file| file.release_source.clone())
+        {
+            builder = builder
+                .release_source(ReleaseSource::try_from(release_source.as_str())?)
+                .value_source("release-source", source);
+        }
+
+        if let Some((release_index_file, source)) = layered(
+            given(
+                &options,
+                id::ARG_RELEASE_INDEX_FILE,
+                options
+                    .value_of(id::ARG_RELEASE_INDEX_FILE)
+                    .map(PathBuf::from),
+            ),
+            &files,
+            |file| file.release_index_file.clone(),
+        ) {
+            // relative paths in a configuration file are relative to the folder of that file
+            let release_index_file = match source.file().and_then(Path::parent) {
+                Some(folder) => folder.join(release_index_file),
+                None => release_index_file,
+            };
+
+            builder = builder
+                .release_index_file(Some(release_index_file))
+                .value_source("release-index-file", source);
+        } else if matches!(builder.inner.release_source, ReleaseSource::File) {
+            return Err(CargoMSRVError::InvalidConfig(
+                "The release source 'file' requires --release-index-file".to_string(),
+            ));
+        }
+
+        // like the release source, the channel has a default value on the command line
+        let release_channel = given(
+            &options,
+            id::ARG_CHANNEL,
+            options.value_of(id::ARG_CHANNEL).map(String::from),
+        );
+
+        if let Some((release_channel, source)) =
+            layered(release_channel, &files, |file| file.channel.clone())
+        {
+            builder = builder
+                .release_channel(ReleaseChannel::try_from(release_channel.as_str())?)
+                .value_source("channel", source);
         }
 
-        //
-        if !matches.is_present(id::ARG_NO_LOG) {
+        if !options.is_present(id::ARG_NO_LOG) {
             let mut config = TracingOptions::default();
 
-            if let Some(log_target) = matches.value_of(id::ARG_LOG_TARGET) {
+            if let Some(log_target) = options.value_of(id::ARG_LOG_TARGET) {
                 config.target = TracingTargetOption::from_str(log_target);
             }
 
-            if let Some(level) = matches.value_of(id::ARG_LOG_LEVEL) {
+            if let Some(level) = options.value_of(id::ARG_LOG_LEVEL) {
                 config.level = parse_log_level(level);
             }
 
             builder = builder.tracing_config(config);
         }
 
-        builder = builder.no_check_feedback(matches.is_present(id::ARG_NO_CHECK_FEEDBACK));
+        if let Some((choice, source)) =
+            layered_flag(&options, id::ARG_NO_CHECK_FEEDBACK, &files, |file| {
+                file.no_check_feedback
+            })
+        {
+            builder = builder
+                .no_check_feedback(choice)
+                .value_source("no-check-feedback", source);
+        }
+
+        if let Some((choice, source)) =
+            layered_flag(&options, id::ARG_JSON_DIAGNOSTICS, &files, |file| {
+                file.json_diagnostics
+            })
+        {
+            builder = builder
+                .json_diagnostics(choice)
+                .value_source("json-diagnostics", source);
+        }
+
+        builder = builder.workspace(options.is_present(id::ARG_WORKSPACE));
+
+        if let Some((entries, source)) = feature_matrix {
+            builder = builder
+                .feature_matrix(entries)
+                .value_source("feature-matrix", source);
+        }
+
+        if let Some((choice, source)) =
+            layered_flag(&options, id::ARG_NO_CACHE, &files, |file| file.no_cache)
+        {
+            builder = builder.no_cache(choice).value_source("no-cache", source);
+        }
+
+        builder = builder.static_msrv(options.is_present(id::ARG_STATIC));
+
+        if let Some((choice, source)) =
+            layered_flag(&options, id::ARG_MIN_FROM_SOURCE, &files, |file| {
+                file.min_from_source
+            })
+        {
+            builder = builder
+                .min_from_source(choice)
+                .value_source("min-from-source", source);
+        }
+
+        // feature attributes can only be used on the nightly channel
+        if options.is_present(id::ARG_UNSTABLE_FEATURES) {
+            builder = builder
+                .unstable_features(true)
+                .release_channel(ReleaseChannel::Nightly);
+        }
+
+        builder = builder.report_file(options.value_of(id::ARG_REPORT));
 
         if let Some(cmd) = matches.subcommand_matches(id::SUB_COMMAND_LIST) {
             let cmd_config = ListCmdConfig::try_from(cmd)?;
@@ -479,18 +1058,33 @@ impl<'config> TryFrom<&'config ArgMatches> for Config<'config> {
         } else if let Some(cmd) = matches.subcommand_matches(id::SUB_COMMAND_SET) {
             let cmd_config = SetCmdConfig::try_from(cmd)?;
             builder = builder.sub_command_config(SubCommandConfig::SetConfig(cmd_config));
+        } else if let Some(cmd) = matches.subcommand_matches(id::SUB_COMMAND_BUMP) {
+            let cmd_config = BumpCmdConfig::try_from(cmd)?;
+            builder = builder.sub_command_config(SubCommandConfig::BumpConfig(cmd_config));
+        } else if let Some(cmd) = matches.subcommand_matches(id::SUB_COMMAND_VERIFY) {
+            let cmd_config = VerifyCmdConfig::try_from(cmd)?;
+            builder = builder.sub_command_config(SubCommandConfig::VerifyConfig(cmd_config));
         }
 
         Ok(builder.build())
     }
 }
 
-fn parse_version(input: &str) -> Result<semver::Version, semver::Error> {
+pub(crate) fn parse_version(input: &str) -> Result<semver::Version, semver::Error> {
     match input {
         "2015" => Ok(semver::Version::new(1, 0, 0)),
         "2018" => Ok(semver::Version::new(1, 31, 0)),
         "2021" => Ok(semver::Version::new(1, 56, 0)),
-        s => semver::Version::parse(s),
+        s => parse_toolchain_version(s),
+    }
+}
+
+/// Parses a Rust version, or a dated toolchain like `nightly-2023-04-12`, which is represented by
+/// the version of [`DatedToolchain::to_version`](crate::toolchain::DatedToolchain::to_version).
+pub(crate) fn parse_toolchain_version(input: &str) -> Result<semver::Version, semver::Error> {
+    match input.parse::<DatedToolchain>() {
+        Ok(toolchain) => Ok(toolchain.to_version()),
+        Err(_) => semver::Version::parse(input),
     }
 }
 
@@ -498,19 +1092,205 @@ fn parse_log_level(input: &str) -> tracing::Level {
     input.parse().unwrap_or(tracing::Level::INFO)
 }
 
-fn set_custom_check_command<'a>(
-    matches: &'a ArgMatches,
-    builder: ConfigBuilder<'a>,
-) -> ConfigBuilder<'a> {
-    use crate::cli::id;
+/// The options given on the command line, or else by their environment variables, which are
+/// looked up by `var`. The environment variables are parsed like the command line, so their values
+/// are validated in the same way as the flags.
+struct GivenOptions<'m> {
+    cli: &'m ArgMatches,
+    env: ArgMatches,
+    // the environment variables which are set, by the id of their option
+    vars: BTreeMap<&'static str, String>,
+}
+
+impl<'m> GivenOptions<'m> {
+    fn new(cli: &'m ArgMatches, var: impl Fn(&str) -> Option<String>) -> TResult<Self> {
+        let app = crate::cli::cli();
+
+        let mut args = vec!["cargo-msrv".to_string()];
+        let mut vars = BTreeMap::new();
 
-    let check_cmd = matches.values_of(id::ARG_CUSTOM_CHECK);
+        for arg in app.get_arguments() {
+            let (name, long) = match (arg.get_env().and_then(OsStr::to_str), arg.get_long()) {
+                (Some(name), Some(long)) => (name, long),
+                _ => continue,
+            };
 
-    if let Some(custom_cmd) = check_cmd {
-        builder.check_command(custom_cmd.collect())
-    } else {
-        builder
+            if let Some(value) = var(name) {
+                if arg.is_set(ArgSettings::TakesValue) {
+                    args.push(format!("--{}={}", long, value));
+                } else if is_enabled(&value) {
+                    args.push(format!("--{}", long));
+                }
+
+                vars.insert(arg.get_name(), name.to_string());
+            }
+        }
+
+        let env = crate::cli::cli()
+            .try_get_matches_from(args)
+            .map_err(|error| CargoMSRVError::InvalidConfig(error.to_string()))?;
+
+        Ok(Self { cli, env, vars })
     }
+
+    /// The matches in which an option was given, and where it came from. Returns `None` if the
+    /// option was given by neither the command line nor its environment variable, even if it has
+    /// a default value.
+    fn matches(&self, id: &str) -> Option<(&ArgMatches, ValueSource)> {
+        if self.cli.occurrences_of(id) > 0 {
+            Some((self.cli, ValueSource::Cli))
+        } else {
+            self.vars
+                .get(id)
+                .map(|name| (&self.env, ValueSource::Env(name.clone())))
+        }
+    }
+
+    /// Where the value of an option came from: the command line, or else its environment variable.
+    fn source(&self, id: &str) -> Option<ValueSource> {
+        self.matches(id).map(|(_, source)| source)
+    }
+
+    fn value_of(&self, id: &str) -> Option<&str> {
+        self.matches(id)
+            .and_then(|(matches, _)| matches.value_of(id))
+    }
+
+    fn values_of(&self, id: &str) -> Option<Values<'_>> {
+        self.matches(id)
+            .and_then(|(matches, _)| matches.values_of(id))
+    }
+
+    /// Whether a flag is given, and enabled, on the command line or by its environment variable.
+    fn is_present(&self, id: &str) -> bool {
+        self.matches(id)
+            .map_or(false, |(matches, _)| matches.is_present(id))
+    }
+}
+
+/// The search method given on the command line, or else by one of the environment variables of
+/// the search methods, which are looked up by `var`.
+///
+/// Unlike other flags, the search methods exclude each other, so the environment variable of one
+/// search method may not conflict with another search method given on the command line.
+fn given_search_method(
+    matches: &ArgMatches,
+    var: impl Fn(&str) -> Option<String>,
+) -> TResult<Option<(SearchMethod, ValueSource)>> {
+    use crate::cli::{env, id};
+
+    let search_methods = [
+        (id::ARG_BISECT, env::BISECT, SearchMethod::Bisect),
+        (id::ARG_LINEAR, env::LINEAR, SearchMethod::Linear),
+        (id::ARG_PARALLEL, env::PARALLEL, SearchMethod::Parallel),
+    ];
+
+    if let Some((_, _, method)) = search_methods
+        .iter()
+        .find(|(id, _, _)| matches.is_present(id))
+    {
+        return Ok(Some((*method, ValueSource::Cli)));
+    }
+
+    let enabled = search_methods
+        .iter()
+        .filter(|(_, env, _)| var(env).map_or(false, |value| is_enabled(&value)))
+        .collect::<Vec<_>>();
+
+    match enabled.as_slice() {
+        [] => Ok(None),
+        [(_, env, method)] => Ok(Some((*method, ValueSource::Env(env.to_string())))),
+        _ => Err(CargoMSRVError::InvalidConfig(format!(
+            "Only one of the environment variables {} may enable a search method",
+            enabled
+                .iter()
+                .map(|(_, env, _)| *env)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
+/// Whether the value of the environment variable of a flag enables it, like clap decides for the
+/// flags which it reads from the environment.
+fn is_enabled(value: &str) -> bool {
+    !matches!(
+        value.to_lowercase().as_str(),
+        "n" | "no" | "f" | "false" | "off" | "0"
+    )
+}
+
+/// The value of an argument, paired with where it came from, if it was given on the command
+/// line or by its environment variable.
+fn given<T>(options: &GivenOptions, id: &str, value: Option<T>) -> Option<(T, ValueSource)> {
+    value.and_then(|value| options.source(id).map(|source| (value, source)))
+}
+
+/// The value of an option as given on the command line or by its environment variable, or else
+/// from the configuration file with the highest precedence which sets it, and where the value
+/// came from.
+fn layered<T>(
+    given: Option<(T, ValueSource)>,
+    files: &ConfigFiles,
+    file: impl Fn(&FileConfig) -> Option<T>,
+) -> Option<(T, ValueSource)> {
+    given.or_else(|| files.value(file))
+}
+
+/// Like [`layered`], for flags. A flag which is not given on the command line or by its
+/// environment variable, falls back to the configuration files. Both the environment variable
+/// and the configuration files may also disable the flag.
+fn layered_flag(
+    options: &GivenOptions,
+    id: &str,
+    files: &ConfigFiles,
+    file: impl Fn(&FileConfig) -> Option<bool>,
+) -> Option<(bool, ValueSource)> {
+    let given = options
+        .source(id)
+        .map(|source| (options.is_present(id), source));
+
+    layered(given, files, file)
+}
+
+fn feature_matrix(options: &GivenOptions) -> TResult<Vec<FeatureMatrixEntry>> {
+    use crate::cli::id;
+
+    let mut entries = options
+        .values_of(id::ARG_FEATURE_MATRIX)
+        .into_iter()
+        .flatten()
+        .map(FeatureMatrixEntry::try_from)
+        .collect::<TResult<Vec<_>>>()?;
+
+    entries.extend(
+        options
+            .values_of(id::ARG_FEATURE_SET)
+            .into_iter()
+            .flatten()
+            .map(FeatureMatrixEntry::from_feature_set),
+    );
+
+    Ok(entries)
+}
+
+/// Insert arguments into a check command, before its argument separator (`--`) if it has one,
+/// and at the end otherwise. Used to pass cargo flags, like features or a target, to the check
+/// command given by the user.
+pub(crate) fn inject_check_args(
+    check_command: &[String],
+    args: impl IntoIterator<Item = impl Into<String>>,
+) -> Vec<String> {
+    let position = check_command
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(check_command.len());
+
+    let mut command = check_command[..position].to_vec();
+    command.extend(args.into_iter().map(Into::into));
+    command.extend_from_slice(&check_command[position..]);
+
+    command
 }
 
 macro_rules! as_sub_command_config {
@@ -526,22 +1306,37 @@ macro_rules! as_sub_command_config {
     };
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum SubCommandConfig {
     None,
     ListConfig(ListCmdConfig),
     SetConfig(SetCmdConfig),
     ShowConfig,
+    BumpConfig(BumpCmdConfig),
+    VerifyConfig(VerifyCmdConfig),
 }
 
 impl SubCommandConfig {
     as_sub_command_config!(list, ListConfig, ListCmdConfig);
     as_sub_command_config!(set, SetConfig, SetCmdConfig);
+    as_sub_command_config!(bump, BumpConfig, BumpCmdConfig);
+
+    /// The configuration of the verify subcommand, or its defaults when the deprecated `--verify`
+    /// flag was used instead.
+    pub(crate) fn verify(&self) -> VerifyCmdConfig {
+        match self {
+            Self::VerifyConfig(c) => c.clone(),
+            _ => VerifyCmdConfig::default(),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct TracingOptions {
     target: TracingTargetOption,
+    #[serde(with = "tracing_level")]
     level: tracing::Level,
 }
 
@@ -564,7 +1359,8 @@ impl TracingOptions {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum TracingTargetOption {
     File,
     Stdout,
@@ -586,27 +1382,338 @@ impl TracingTargetOption {
     }
 }
 
+/// (De)serialize a [`tracing::Level`] by its name, since it does not implement serde's traits.
+mod tracing_level {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        level: &tracing::Level,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(level)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<tracing::Level, D::Error> {
+        let level = String::deserialize(deserializer)?;
+        level.parse().map_err(de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::source::ValueSource;
+    use super::{given_search_method, SearchMethod};
+    use super::{Config, ReleaseSource};
     use parameterized::parameterized;
     use rust_releases::semver::Version;
 
+    /// Looks up the given environment variables, instead of those of the process.
+    fn environment<'v>(vars: &'v [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'v {
+        move |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
     #[parameterized(
         input = {
             "1.35.0",
             "2015",
             "2018",
             "2021",
+            "nightly-2023-04-12",
         },
         expected_version = {
             Version::new(1,35,0),
             Version::new(1,0,0),
             Version::new(1,31,0),
             Version::new(1,56,0),
+            Version::parse("1.70.0-nightly.2023-04-12").unwrap(),
         }
     )]
     fn parse_version(input: &str, expected_version: Version) {
         let version = super::super::parse_version(input).unwrap();
         assert_eq!(version, expected_version);
     }
+
+    #[test]
+    fn config_serde_round_trip() {
+        let config = super::ConfigBuilder::new(super::ModeIntent::Find, "x86_64-unknown-linux-gnu")
+            .check_command(vec!["cargo", "test"])
+            .search_method(super::SearchMethod::Linear)
+            .release_source(super::ReleaseSource::RustDist)
+            .minimum_version(Version::new(1, 40, 0))
+            .feature_matrix(vec![
+                super::FeatureMatrixEntry::Default,
+                super::FeatureMatrixEntry::Set(vec!["std".to_string()]),
+            ])
+            .tracing_config(super::TracingOptions::default())
+            .build();
+
+        let serialized = serde_json::to_value(&config).unwrap();
+
+        assert_eq!(
+            serialized["check-command"],
+            serde_json::json!(["cargo", "test"])
+        );
+        assert_eq!(serialized["search-method"], "linear");
+        assert_eq!(serialized["release-source"], "rust-dist");
+        assert_eq!(serialized["minimum-version"], "1.40.0");
+        assert_eq!(serialized["tracing-config"]["level"], "INFO");
+
+        let deserialized: super::Config = serde_json::from_value(serialized.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&deserialized).unwrap(), serialized);
+    }
+
+    #[test]
+    fn platforms_are_the_given_targets() {
+        use super::{ConfigBuilder, ModeIntent};
+
+        let host = ConfigBuilder::new(ModeIntent::List, "x86_64-unknown-linux-gnu").build();
+        assert!(host.platforms().is_empty());
+
+        let given = ConfigBuilder::new(ModeIntent::List, "x86_64-unknown-linux-gnu")
+            .value_source("target", ValueSource::Cli)
+            .build();
+        assert_eq!(given.platforms(), vec!["x86_64-unknown-linux-gnu"]);
+
+        let many = ConfigBuilder::new(ModeIntent::List, "x86_64-unknown-linux-gnu")
+            .compile_targets(vec![
+                "x86_64-pc-windows-msvc".to_string(),
+                "wasm32-unknown-unknown".to_string(),
+            ])
+            .value_source("target", ValueSource::Cli)
+            .build();
+        assert_eq!(
+            many.platforms(),
+            vec!["x86_64-pc-windows-msvc", "wasm32-unknown-unknown"]
+        );
+    }
+
+    #[test]
+    fn command_line_takes_precedence_over_config_file() {
+        let dir = std::env::temp_dir().join("cargo-msrv-test-config-precedence");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"a\"\nversion = \"0.1.0\"\n\n[package.metadata.cargo-msrv]\nmin = \"1.40.0\"\nmax = \"1.50.0\"\nsearch-method = \"linear\"\n",
+        )
+        .unwrap();
+
+        let matches = crate::cli::cli().get_matches_from(vec![
+            "cargo-msrv",
+            "--path",
+            dir.to_str().unwrap(),
+            "--max",
+            "1.55.0",
+        ]);
+        let config = Config::try_from_matches_and_env(&matches, environment(&[])).unwrap();
+
+        assert_eq!(config.minimum_version(), Some(&Version::new(1, 40, 0)));
+        assert_eq!(config.maximum_version(), Some(&Version::new(1, 55, 0)));
+        assert_eq!(config.search_method(), super::SearchMethod::Linear);
+        assert_eq!(
+            config.value_source("min"),
+            ValueSource::PackageMetadata(dir.join("Cargo.toml"))
+        );
+        assert_eq!(config.value_source("max"), ValueSource::Cli);
+        assert_eq!(config.value_source("jobs"), ValueSource::Default);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn environment_takes_precedence_over_config_file() {
+        let dir = std::env::temp_dir().join("cargo-msrv-test-config-environment");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(super::file::CONFIG_FILE),
+            "release-source = \"rust-changelog\"\nno-cache = true\n",
+        )
+        .unwrap();
+
+        let matches = crate::cli::cli().get_matches_from(vec![
+            "cargo-msrv",
+            "--path",
+            dir.to_str().unwrap(),
+            "--no-read-min-edition",
+        ]);
+        let config = Config::try_from_matches_and_env(
+            &matches,
+            environment(&[
+                ("CARGO_MSRV_RELEASE_SOURCE", "rust-dist"),
+                ("CARGO_MSRV_NO_CACHE", "false"),
+            ]),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            config.release_source(),
+            super::ReleaseSource::RustDist
+        ));
+        assert!(!config.no_cache());
+        assert_eq!(
+            config.value_source("release-source"),
+            ValueSource::Env("CARGO_MSRV_RELEASE_SOURCE".to_string())
+        );
+        assert_eq!(
+            config.value_source("no-cache"),
+            ValueSource::Env("CARGO_MSRV_NO_CACHE".to_string())
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[yare::parameterized(
+        none = { &[], &[], None },
+        cli = { &["--parallel"], &[], Some((SearchMethod::Parallel, ValueSource::Cli)) },
+        env = { &[], &[("CARGO_MSRV_LINEAR", "1")], Some((SearchMethod::Linear, ValueSource::Env("CARGO_MSRV_LINEAR".to_string()))) },
+        env_disabled = { &[], &[("CARGO_MSRV_LINEAR", "false")], None },
+        cli_overrides_env = { &["--linear"], &[("CARGO_MSRV_BISECT", "1")], Some((SearchMethod::Linear, ValueSource::Cli)) },
+    )]
+    fn search_method_from_cli_or_environment(
+        args: &[&str],
+        vars: &[(&str, &str)],
+        expected: Option<(SearchMethod, ValueSource)>,
+    ) {
+        let matches =
+            crate::cli::cli().get_matches_from(std::iter::once(&"cargo-msrv").chain(args.iter()));
+        assert_eq!(
+            given_search_method(&matches, environment(vars)).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn jobs_require_parallel_search_method() {
+        let env = environment(&[("CARGO_MSRV_PARALLEL", "1")]);
+
+        let matches = crate::cli::cli().get_matches_from(vec!["cargo-msrv", "--jobs", "2"]);
+        let config = Config::try_from_matches_and_env(&matches, &env).unwrap();
+        assert_eq!(config.search_method(), SearchMethod::Parallel);
+        assert_eq!(config.jobs(), 2);
+
+        let matches =
+            crate::cli::cli().get_matches_from(vec!["cargo-msrv", "--linear", "--jobs", "2"]);
+        assert!(Config::try_from_matches_and_env(&matches, &env).is_err());
+    }
+
+    #[test]
+    fn environment_is_validated_like_the_command_line() {
+        let matches = crate::cli::cli().get_matches_from(vec!["cargo-msrv"]);
+
+        let env = environment(&[("CARGO_MSRV_RELEASE_SOURCE", "crates-io")]);
+        assert!(Config::try_from_matches_and_env(&matches, env).is_err());
+
+        let env = environment(&[("CARGO_MSRV_MIN", "1.40.0"), ("CARGO_MSRV_STATIC", "true")]);
+        let config = Config::try_from_matches_and_env(&matches, env).unwrap();
+        assert_eq!(config.minimum_version(), Some(&Version::new(1, 40, 0)));
+        assert!(config.static_msrv());
+
+        let env = environment(&[("CARGO_MSRV_STATIC", "false")]);
+        let config = Config::try_from_matches_and_env(&matches, env).unwrap();
+        assert!(!config.static_msrv());
+    }
+
+    #[test]
+    fn file_release_source_requires_release_index_file() {
+        let matches =
+            crate::cli::cli().get_matches_from(vec!["cargo-msrv", "--release-source", "file"]);
+        assert!(Config::try_from_matches_and_env(&matches, environment(&[])).is_err());
+
+        let env = environment(&[("CARGO_MSRV_RELEASE_INDEX_FILE", "index.json")]);
+        let config = Config::try_from_matches_and_env(&matches, env).unwrap();
+        assert!(matches!(config.release_source(), ReleaseSource::File));
+    }
+
+    #[test]
+    fn several_search_methods_from_environment() {
+        let matches = crate::cli::cli().get_matches_from(vec!["cargo-msrv"]);
+        let var = |name: &str| match name {
+            "CARGO_MSRV_BISECT" | "CARGO_MSRV_PARALLEL" => Some("true".to_string()),
+            _ => None,
+        };
+
+        assert!(given_search_method(&matches, var).is_err());
+    }
+
+    #[test]
+    fn workspace_can_not_be_combined_with_feature_matrix() {
+        let dir = std::env::temp_dir().join("cargo-msrv-test-config-workspace-feature-matrix");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(super::file::CONFIG_FILE),
+            "feature-matrix = [\"all\"]\n",
+        )
+        .unwrap();
+
+        let matches = crate::cli::cli().get_matches_from(vec![
+            "cargo-msrv",
+            "--path",
+            dir.to_str().unwrap(),
+            "--workspace",
+        ]);
+        assert!(Config::try_from_matches_and_env(&matches, environment(&[])).is_err());
+
+        let matches = crate::cli::cli().get_matches_from(vec!["cargo-msrv", "--workspace"]);
+        let env = environment(&[("CARGO_MSRV_FEATURE_MATRIX", "default,all")]);
+        assert!(Config::try_from_matches_and_env(&matches, env).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn single_target_selects_toolchains() {
+        let matches = crate::cli::cli().get_matches_from(vec![
+            "cargo-msrv",
+            "--target",
+            "i686-unknown-linux-gnu",
+        ]);
+        let config = Config::try_from_matches_and_env(&matches, environment(&[])).unwrap();
+        assert_eq!(config.target(), "i686-unknown-linux-gnu");
+        assert_eq!(config.compile_target(), None);
+        assert!(config.compile_targets().is_empty());
+
+        let matches = crate::cli::cli().get_matches_from(vec![
+            "cargo-msrv",
+            "--target",
+            "i686-unknown-linux-gnu",
+            "--target",
+            "wasm32-unknown-unknown",
+        ]);
+        let config = Config::try_from_matches_and_env(&matches, environment(&[])).unwrap();
+        assert_eq!(
+            config.compile_targets(),
+            &["i686-unknown-linux-gnu", "wasm32-unknown-unknown"]
+        );
+    }
+
+    #[test]
+    fn compile_target_is_passed_to_check_command() {
+        let config = super::ConfigBuilder::new(super::ModeIntent::Find, "x86_64-unknown-linux-gnu")
+            .check_command(vec!["cargo", "test", "--", "--nocapture"])
+            .compile_target("wasm32-unknown-unknown")
+            .build();
+
+        assert_eq!(config.target(), "x86_64-unknown-linux-gnu");
+        assert_eq!(config.compile_target(), Some("wasm32-unknown-unknown"));
+        assert_eq!(
+            config.check_command(),
+            &vec![
+                "cargo",
+                "test",
+                "--target",
+                "wasm32-unknown-unknown",
+                "--",
+                "--nocapture"
+            ]
+        );
+    }
 }
@@ -1,8 +1,10 @@
 use crate::manifest::bare_version::BareVersion;
 use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct SetCmdConfig {
     pub msrv: BareVersion,
 }
@@ -0,0 +1,58 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Where the value of a configuration option came from.
+///
+/// When an option is set in several places, the command line takes precedence over the
+/// environment variables, which take precedence over the configuration files, which take
+/// precedence over the defaults.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ValueSource {
+    /// The option was not set, so its default value is used
+    Default,
+    /// The standalone `cargo-msrv.toml` configuration file
+    ConfigFile(PathBuf),
+    /// The `[package.metadata.cargo-msrv]` table of the given Cargo manifest
+    PackageMetadata(PathBuf),
+    /// The `[workspace.metadata.cargo-msrv]` table of the given Cargo manifest
+    WorkspaceMetadata(PathBuf),
+    /// The given `CARGO_MSRV_*` environment variable
+    Env(String),
+    /// A command line argument
+    Cli,
+}
+
+impl Default for ValueSource {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+impl ValueSource {
+    /// The configuration file the value was read from, if any.
+    pub fn file(&self) -> Option<&Path> {
+        match self {
+            Self::ConfigFile(path)
+            | Self::PackageMetadata(path)
+            | Self::WorkspaceMetadata(path) => Some(path),
+            Self::Default | Self::Env(_) | Self::Cli => None,
+        }
+    }
+}
+
+impl Display for ValueSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::ConfigFile(path) => write!(f, "'{}'", path.display()),
+            Self::PackageMetadata(path) => {
+                write!(f, "[package.metadata.cargo-msrv] in '{}'", path.display())
+            }
+            Self::WorkspaceMetadata(path) => {
+                write!(f, "[workspace.metadata.cargo-msrv] in '{}'", path.display())
+            }
+            Self::Env(name) => write!(f, "environment variable {}", name),
+            Self::Cli => write!(f, "command line"),
+        }
+    }
+}
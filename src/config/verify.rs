@@ -0,0 +1,30 @@
+use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct VerifyCmdConfig {
+    /// Before verifying, downgrade the locked dependencies which require a Rust version newer than
+    /// the MSRV, using the versions available in this local registry index
+    #[serde(default)]
+    pub resolve_lockfile: Option<PathBuf>,
+}
+
+impl<'a> TryFrom<&'a ArgMatches> for VerifyCmdConfig {
+    type Error = crate::CargoMSRVError;
+
+    fn try_from(args: &'a ArgMatches) -> Result<Self, Self::Error> {
+        use crate::cli::id;
+
+        let resolve_lockfile = if args.is_present(id::SUB_COMMAND_VERIFY_RESOLVE_LOCKFILE) {
+            args.value_of(id::SUB_COMMAND_VERIFY_REGISTRY_INDEX)
+                .map(PathBuf::from)
+        } else {
+            None
+        };
+
+        Ok(Self { resolve_lockfile })
+    }
+}
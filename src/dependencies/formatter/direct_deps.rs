@@ -1,8 +1,8 @@
-use crate::dependencies::formatter::{
-    format_version, get_package_metadata_msrv, parse_manifest_workaround,
-};
+use crate::dependencies::formatter::{format_version, package_msrv};
 use crate::dependencies::DependencyGraph;
+use crate::reporter::json::Event;
 use crate::reporter::Output;
+use serde::Serialize;
 use std::marker::PhantomData;
 
 pub(crate) struct DirectDependenciesFormatter<T: Output> {
@@ -34,29 +34,24 @@ impl<T: Output> DirectDependenciesFormatter<T> {
 
         let graph = &dependency_graph.packages;
 
+        let reached_through = dependency_graph.reached_through();
+
         let neighbors = graph.neighbors_directed(root.into(), petgraph::Direction::Outgoing);
 
         for dep in neighbors {
             let package = &graph[dep];
+            let kinds = reached_through
+                .get(&package.id)
+                .cloned()
+                .unwrap_or_default();
 
-            let msrv = package
-                .rust_version
-                .clone()
-                .map(|req| {
-                    let comparator = &req.comparators[0];
-                    crate::semver::Version::new(
-                        comparator.major,
-                        comparator.minor.unwrap_or_default(),
-                        comparator.patch.unwrap_or_default(),
-                    )
-                })
-                .or_else(|| get_package_metadata_msrv(package))
-                .or_else(|| parse_manifest_workaround(package.manifest_path.as_path())); // todo: add last one as option to config
+            let msrv = package_msrv(package);
 
             let values = Values {
                 name: &package.name,
                 version: &package.version,
-                msrv: format_version(msrv.as_ref()),
+                msrv,
+                kinds: kinds.iter().map(ToString::to_string).collect(),
                 dependencies: package
                     .dependencies
                     .iter()
@@ -84,7 +79,7 @@ impl std::fmt::Display for DirectDependenciesFormatter<crate::reporter::ui::Huma
                     .load_preset(UTF8_FULL)
                     .set_table_width(120) // fallback for ContentArrangement::Dynamic
                     .set_content_arrangement(ContentArrangement::Dynamic)
-                    .set_header(vec!["Dependency", "Version", "MSRV", "Depends on"]);
+                    .set_header(vec!["Dependency", "Version", "MSRV", "Kind", "Depends on"]);
 
                 table
             },
@@ -92,7 +87,8 @@ impl std::fmt::Display for DirectDependenciesFormatter<crate::reporter::ui::Huma
                 acc.add_row(vec![
                     Cell::new(&next.name),
                     Cell::new(&next.version),
-                    Cell::new(&next.msrv),
+                    Cell::new(format_version(next.msrv.as_ref())),
+                    Cell::new(next.kinds.join(", ")),
                     Cell::new(&next.dependencies.join(", ")),
                 ]);
             },
@@ -102,34 +98,94 @@ impl std::fmt::Display for DirectDependenciesFormatter<crate::reporter::ui::Huma
     }
 }
 
+#[derive(Serialize)]
+struct DirectDependenciesEvent<'a> {
+    variant: &'a str,
+    success: bool,
+    list: Vec<DirectDependencyFields>,
+}
+
+#[derive(Serialize)]
+struct DirectDependencyFields {
+    dependency: String,
+    version: String,
+    msrv: Option<String>,
+    kinds: Vec<String>,
+    depends_on: Vec<String>,
+}
+
 impl std::fmt::Display for DirectDependenciesFormatter<crate::reporter::json::JsonPrinter<'_>> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // Table of dependencies
-        use json::object;
-
-        let objects = self.direct_dependencies_msrv(Vec::new, |acc, next| {
-            acc.push(object! {
-                dependency: next.name,
+        let list = self.direct_dependencies_msrv(Vec::new, |acc, next| {
+            acc.push(DirectDependencyFields {
+                dependency: next.name.to_string(),
                 version: format!("{}", next.version),
-                msrv: next.msrv,
+                msrv: next.msrv.as_ref().map(ToString::to_string),
+                kinds: next.kinds,
                 depends_on: next.dependencies,
             });
         });
 
-        let json = object! {
-            reason: "list",
+        let event = DirectDependenciesEvent {
             variant: crate::config::list::DIRECT_DEPS,
             success: true,
-            list: objects,
+            list,
         };
 
-        writeln!(f, "{}", json)
+        writeln!(f, "{}", Event::new("list", event).to_line())
     }
 }
 
 struct Values<'s, 'v> {
     name: &'s str,
     version: &'v crate::semver::Version,
-    msrv: String,
+    msrv: Option<crate::semver::Version>,
+    kinds: Vec<String>,
     dependencies: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::DirectDependenciesFormatter;
+    use crate::dependencies::fixtures;
+    use crate::reporter::json::schema::assert_conforms;
+    use crate::reporter::json::JsonPrinter;
+    use cargo_metadata::DependencyKind;
+
+    #[test]
+    fn json_event() {
+        // root -> a (1.60); root -> b (build); a -> c is not a direct dependency
+        let graph = fixtures::graph(
+            &[
+                ("root", Some("1.56")),
+                ("a", Some("1.60")),
+                ("b", None),
+                ("c", Some("1.65")),
+            ],
+            &[
+                ("root", "a", &[DependencyKind::Normal]),
+                ("root", "b", &[DependencyKind::Build]),
+                ("a", "c", &[DependencyKind::Normal]),
+            ],
+        );
+        let formatter = DirectDependenciesFormatter::<JsonPrinter>::new(graph);
+
+        let event = assert_conforms(&formatter.to_string());
+
+        assert_eq!(event["variant"], "direct-deps");
+        assert_eq!(event["list"].len(), 2);
+
+        let dependency = |name: &str| {
+            event["list"]
+                .members()
+                .find(|dependency| dependency["dependency"] == name)
+                .unwrap()
+        };
+
+        assert_eq!(dependency("a")["version"], "1.0.0");
+        assert_eq!(dependency("a")["msrv"], "1.60.0");
+        assert_eq!(dependency("a")["kinds"][0], "normal");
+        assert!(dependency("b")["msrv"].is_null());
+        assert_eq!(dependency("b")["kinds"][0], "build");
+    }
+}
@@ -0,0 +1,317 @@
+use crate::config::list::GraphFormat;
+use crate::dependencies::formatter::package_msrv;
+use crate::dependencies::DependencyGraph;
+use crate::reporter::json::Event;
+use crate::reporter::Output;
+use petgraph::visit::{Bfs, EdgeRef};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::{Formatter, Write};
+use std::marker::PhantomData;
+
+/// Serializes the dependency graph of the project, as reachable from the root crate.
+///
+/// Each node is a package, with its name, version and declared MSRV. Each edge is a dependency,
+/// with the kinds of dependency, i.e. `normal` and/or `build`.
+///
+/// For example, in the `dot` format:
+///
+/// ```text
+/// digraph dependencies {
+///     n0 [label="my-crate 0.1.0\nMSRV 1.56.0"];
+///     n1 [label="some-dep 1.2.0"];
+///     n0 -> n1 [label="normal"];
+/// }
+/// ```
+pub(crate) struct GraphFormatter<T: Output> {
+    graph: DependencyGraph,
+    format: GraphFormat,
+    output: PhantomData<T>,
+}
+
+impl<T: Output> GraphFormatter<T> {
+    pub fn new(graph: DependencyGraph, format: GraphFormat) -> Self {
+        Self {
+            graph,
+            format,
+            output: PhantomData,
+        }
+    }
+}
+
+impl<T: Output> GraphFormatter<T> {
+    fn nodes_and_edges(&self) -> (Vec<GraphNode<'_>>, Vec<GraphEdge>) {
+        let graph = &self.graph.packages;
+        let root = self.graph.index[&self.graph.root_crate].into();
+
+        let mut ids = HashMap::new();
+        let mut nodes = Vec::new();
+
+        let mut bfs = Bfs::new(graph, root);
+        while let Some(node) = bfs.next(graph) {
+            let package = &graph[node];
+
+            ids.insert(node, nodes.len());
+            nodes.push(GraphNode {
+                name: &package.name,
+                version: &package.version,
+                msrv: package_msrv(package),
+            });
+        }
+
+        let mut edges = ids
+            .keys()
+            .flat_map(|&node| graph.edges_directed(node, petgraph::Direction::Outgoing))
+            .map(|edge| GraphEdge {
+                from: ids[&edge.source()],
+                to: ids[&edge.target()],
+                kinds: edge.weight().iter().map(ToString::to_string).collect(),
+            })
+            .collect::<Vec<_>>();
+        edges.sort_by_key(|edge| (edge.from, edge.to));
+
+        (nodes, edges)
+    }
+
+    fn dot(&self) -> String {
+        let (nodes, edges) = self.nodes_and_edges();
+
+        let mut out = String::from("digraph dependencies {\n");
+
+        for (id, node) in nodes.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    n{} [label=\"{} {}{}\"];",
+                id,
+                node.name,
+                node.version,
+                node.msrv_label("\\n")
+            );
+        }
+
+        for edge in &edges {
+            let _ = writeln!(
+                out,
+                "    n{} -> n{} [label=\"{}\"];",
+                edge.from,
+                edge.to,
+                edge.kinds.join(", ")
+            );
+        }
+
+        out.push('}');
+        out
+    }
+
+    fn mermaid(&self) -> String {
+        let (nodes, edges) = self.nodes_and_edges();
+
+        let mut out = String::from("graph TD");
+
+        for (id, node) in nodes.iter().enumerate() {
+            let _ = write!(
+                out,
+                "\n    n{}[\"{} {}{}\"]",
+                id,
+                node.name,
+                node.version,
+                node.msrv_label("<br/>")
+            );
+        }
+
+        for edge in &edges {
+            let _ = write!(
+                out,
+                "\n    n{} -->|{}| n{}",
+                edge.from,
+                edge.kinds.join(", "),
+                edge.to
+            );
+        }
+
+        out
+    }
+
+    fn fields(&self) -> GraphFields<'_> {
+        let (nodes, edges) = self.nodes_and_edges();
+
+        let nodes = nodes
+            .into_iter()
+            .enumerate()
+            .map(|(id, node)| GraphNodeFields {
+                id,
+                name: node.name,
+                version: format!("{}", node.version),
+                msrv: node.msrv.as_ref().map(ToString::to_string),
+            })
+            .collect();
+
+        GraphFields { nodes, edges }
+    }
+}
+
+impl std::fmt::Display for GraphFormatter<crate::reporter::ui::HumanPrinter<'_>> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.format {
+            GraphFormat::Dot => f.write_str(&self.dot()),
+            GraphFormat::Mermaid => f.write_str(&self.mermaid()),
+            GraphFormat::Json => {
+                let json =
+                    serde_json::to_string_pretty(&self.fields()).map_err(|_| std::fmt::Error)?;
+                f.write_str(&json)
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct GraphEvent<'a> {
+    variant: &'a str,
+    format: &'a str,
+    success: bool,
+    graph: GraphFields<'a>,
+    rendered: Option<String>,
+}
+
+impl std::fmt::Display for GraphFormatter<crate::reporter::json::JsonPrinter<'_>> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // The graph is always included as structured data, so parsers need not understand dot or
+        // mermaid, which are included as rendered text
+        let rendered = match self.format {
+            GraphFormat::Dot => Some(self.dot()),
+            GraphFormat::Mermaid => Some(self.mermaid()),
+            GraphFormat::Json => None,
+        };
+
+        let event = GraphEvent {
+            variant: "graph",
+            format: self.format.as_str(),
+            success: true,
+            graph: self.fields(),
+            rendered,
+        };
+
+        writeln!(f, "{}", Event::new("list", event).to_line())
+    }
+}
+
+#[derive(Serialize)]
+struct GraphFields<'g> {
+    nodes: Vec<GraphNodeFields<'g>>,
+    edges: Vec<GraphEdge>,
+}
+
+#[derive(Serialize)]
+struct GraphNodeFields<'g> {
+    id: usize,
+    name: &'g str,
+    version: String,
+    msrv: Option<String>,
+}
+
+struct GraphNode<'g> {
+    name: &'g str,
+    version: &'g crate::semver::Version,
+    msrv: Option<crate::semver::Version>,
+}
+
+impl GraphNode<'_> {
+    fn msrv_label(&self, separator: &str) -> String {
+        self.msrv
+            .as_ref()
+            .map(|msrv| format!("{}MSRV {}", separator, msrv))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize)]
+struct GraphEdge {
+    from: usize,
+    to: usize,
+    kinds: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GraphFormatter;
+    use crate::config::list::GraphFormat;
+    use crate::dependencies::{fixtures, DependencyGraph};
+    use crate::reporter::json::schema::assert_conforms;
+    use crate::reporter::json::JsonPrinter;
+    use crate::reporter::ui::HumanPrinter;
+    use cargo_metadata::DependencyKind;
+
+    // root -> a (normal, build); root -> b (1.60, normal); unreachable is not a dependency
+    fn graph() -> DependencyGraph {
+        fixtures::graph(
+            &[
+                ("root", None),
+                ("a", None),
+                ("b", Some("1.60")),
+                ("unreachable", None),
+            ],
+            &[
+                (
+                    "root",
+                    "a",
+                    &[DependencyKind::Normal, DependencyKind::Build],
+                ),
+                ("root", "b", &[DependencyKind::Normal]),
+            ],
+        )
+    }
+
+    fn format(format: GraphFormat) -> String {
+        GraphFormatter::<HumanPrinter>::new(graph(), format).to_string()
+    }
+
+    #[test]
+    fn dot() {
+        let dot = format(GraphFormat::Dot);
+
+        assert!(dot.starts_with("digraph dependencies {\n    n0 [label=\"root 1.0.0\"];\n"));
+        assert!(dot.contains("[label=\"b 1.0.0\\nMSRV 1.60.0\"];"));
+        assert!(dot.contains("[label=\"normal, build\"];"));
+        assert!(!dot.contains("unreachable"));
+        assert!(dot.ends_with('}'));
+    }
+
+    #[test]
+    fn mermaid() {
+        let mermaid = format(GraphFormat::Mermaid);
+
+        assert!(mermaid.starts_with("graph TD\n    n0[\"root 1.0.0\"]"));
+        assert!(mermaid.contains("[\"b 1.0.0<br/>MSRV 1.60.0\"]"));
+        assert_eq!(mermaid.matches("-->|").count(), 2);
+    }
+
+    #[test]
+    fn json() {
+        let json = json::parse(&format(GraphFormat::Json)).unwrap();
+
+        assert_eq!(json["nodes"].len(), 3);
+        assert_eq!(json["nodes"][0]["name"], "root");
+        assert!(json["nodes"][0]["msrv"].is_null());
+        assert_eq!(json["edges"].len(), 2);
+
+        let b = json["nodes"]
+            .members()
+            .find(|node| node["name"] == "b")
+            .unwrap();
+        assert_eq!(b["msrv"], "1.60.0");
+    }
+
+    #[test]
+    fn json_event() {
+        let formatter = GraphFormatter::<JsonPrinter>::new(graph(), GraphFormat::Dot);
+
+        let event = assert_conforms(&formatter.to_string());
+
+        assert_eq!(event["format"], "dot");
+        assert_eq!(event["graph"]["nodes"].len(), 3);
+        assert!(event["rendered"]
+            .as_str()
+            .unwrap()
+            .starts_with("digraph dependencies {"));
+    }
+}
@@ -1,14 +1,18 @@
 use crate::manifest::{bare_version::BareVersion, CargoManifest, CargoManifestParser, TomlParser};
 use cargo_metadata::Package;
 pub(crate) use direct_deps::DirectDependenciesFormatter;
+pub(crate) use graph::GraphFormatter;
 pub(crate) use ordered_by_msrv::ByMSRVFormatter;
 use rust_releases::semver::Version;
 use std::convert::TryFrom;
 use std::path::Path;
 use toml_edit::Document;
+pub(crate) use tree::TreeFormatter;
 
 pub mod direct_deps;
+pub mod graph;
 pub mod ordered_by_msrv;
+pub mod tree;
 
 #[allow(unused)]
 pub(super) fn format_version_req(version_req: Option<&crate::semver::VersionReq>) -> String {
@@ -19,6 +23,15 @@ pub(super) fn format_version_req(version_req: Option<&crate::semver::VersionReq>
     }
 }
 
+/// A label which marks a dependency which was not (only) reached as a normal dependency with the
+/// kinds of dependency through which it was reached, e.g. ` (build, dev)`.
+pub(super) fn label_dependency_kinds(kinds: &[cargo_metadata::DependencyKind]) -> String {
+    match kinds {
+        [] | [cargo_metadata::DependencyKind::Normal] => String::new(),
+        kinds => format!(" ({})", crate::dependencies::format_dependency_kinds(kinds)),
+    }
+}
+
 pub(super) fn format_version(version_req: Option<&crate::semver::Version>) -> String {
     if let Some(req) = version_req {
         format!("{}", req)
@@ -46,6 +59,23 @@ pub(super) fn parse_manifest_workaround<P: AsRef<Path>>(path: P) -> Option<crate
     parse(path.as_ref())
 }
 
+/// The MSRV of a package, from its `rust-version`, or `package.metadata.msrv` field.
+pub(crate) fn package_msrv(package: &Package) -> Option<crate::semver::Version> {
+    package
+        .rust_version
+        .clone()
+        .map(|req| {
+            let comparator = &req.comparators[0];
+            crate::semver::Version::new(
+                comparator.major,
+                comparator.minor.unwrap_or_default(),
+                comparator.patch.unwrap_or_default(),
+            )
+        })
+        .or_else(|| get_package_metadata_msrv(package))
+        .or_else(|| parse_manifest_workaround(package.manifest_path.as_path())) // todo: add last one as option to config
+}
+
 pub(super) fn get_package_metadata_msrv(package: &Package) -> Option<crate::semver::Version> {
     package
         .metadata
@@ -1,10 +1,10 @@
-use crate::dependencies::formatter::{
-    format_version, get_package_metadata_msrv, parse_manifest_workaround,
-};
-use crate::dependencies::DependencyGraph;
+use crate::dependencies::formatter::{format_version, label_dependency_kinds, package_msrv};
+use crate::dependencies::{DependencyGraph, DependencyKinds};
+use crate::reporter::json::Event;
 use crate::reporter::Output;
 use cargo_metadata::Package;
 use petgraph::visit::Bfs;
+use serde::Serialize;
 use std::collections::BTreeMap;
 use std::fmt::Formatter;
 use std::marker::PhantomData;
@@ -50,6 +50,8 @@ impl<T: Output> ByMSRVFormatter<T> {
         let root = dependency_graph.index[root];
         let graph = &dependency_graph.packages;
 
+        let reached_through = dependency_graph.reached_through();
+
         let mut bfs = Bfs::new(&graph, root.into());
 
         let mut version_map: BTreeMap<Option<semver::Version>, Vec<&Package>> = BTreeMap::new();
@@ -57,27 +59,19 @@ impl<T: Output> ByMSRVFormatter<T> {
         while let Some(nx) = bfs.next(&graph) {
             let package = &graph[nx];
 
-            let msrv = package
-                .rust_version
-                .clone()
-                .map(|req| {
-                    let comparator = &req.comparators[0];
-                    crate::semver::Version::new(
-                        comparator.major,
-                        comparator.minor.unwrap_or_default(),
-                        comparator.patch.unwrap_or_default(),
-                    )
-                })
-                .or_else(|| get_package_metadata_msrv(package))
-                .or_else(|| parse_manifest_workaround(package.manifest_path.as_path())); // todo: add last one as option to config
+            let msrv = package_msrv(package);
 
             version_map.entry(msrv).or_default().push(package);
         }
 
         for (version, packages) in version_map {
             let values = Values {
-                msrv: format_version(version.as_ref()),
+                msrv: version,
                 dependencies: packages.iter().map(|p| p.name.clone()).collect(),
+                kinds: packages
+                    .iter()
+                    .map(|p| reached_through.get(&p.id).cloned().unwrap_or_default())
+                    .collect(),
             };
 
             f(&mut out, values);
@@ -104,9 +98,16 @@ impl std::fmt::Display for ByMSRVFormatter<crate::reporter::ui::HumanPrinter<'_>
                 table
             },
             |acc, next| {
+                let dependencies = next
+                    .dependencies
+                    .iter()
+                    .zip(&next.kinds)
+                    .map(|(name, kinds)| format!("{}{}", name, label_dependency_kinds(kinds)))
+                    .collect::<Vec<_>>();
+
                 acc.add_row(vec![
-                    Cell::new(&next.msrv),
-                    Cell::new(&next.dependencies.join(", ")),
+                    Cell::new(format_version(next.msrv.as_ref())),
+                    Cell::new(dependencies.join(", ")),
                 ]);
             },
         );
@@ -115,30 +116,96 @@ impl std::fmt::Display for ByMSRVFormatter<crate::reporter::ui::HumanPrinter<'_>
     }
 }
 
+#[derive(Serialize)]
+struct ByMsrvEvent<'a> {
+    variant: &'a str,
+    success: bool,
+    list: Vec<DependenciesWithMsrvFields>,
+}
+
+#[derive(Serialize)]
+struct DependenciesWithMsrvFields {
+    msrv: Option<String>,
+    dependencies: Vec<String>,
+    kinds: Vec<Vec<String>>,
+}
+
 impl std::fmt::Display for ByMSRVFormatter<crate::reporter::json::JsonPrinter<'_>> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         // Table of dependencies sorted by MSRV
-        use json::object;
-
-        let objects = self.dependencies_by_msrv(Vec::new, |acc, next| {
-            acc.push(object! {
-                "msrv": next.msrv,
-                "dependencies": next.dependencies
+        let list = self.dependencies_by_msrv(Vec::new, |acc, next| {
+            acc.push(DependenciesWithMsrvFields {
+                msrv: next.msrv.as_ref().map(ToString::to_string),
+                dependencies: next.dependencies,
+                kinds: next
+                    .kinds
+                    .iter()
+                    .map(|kinds| kinds.iter().map(ToString::to_string).collect())
+                    .collect(),
             });
         });
 
-        let json = object! {
-            reason: "list",
+        let event = ByMsrvEvent {
             variant: crate::config::list::ORDERED_BY_MSRV,
             success: true,
-            list: objects,
+            list,
         };
 
-        write!(f, "{}", json)
+        write!(f, "{}", Event::new("list", event).to_line())
     }
 }
 
 struct Values {
-    msrv: String,
+    msrv: Option<crate::semver::Version>,
     dependencies: Vec<String>,
+    // the kinds of dependency through which each dependency was reached
+    kinds: Vec<DependencyKinds>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ByMSRVFormatter;
+    use crate::dependencies::fixtures;
+    use crate::reporter::json::schema::assert_conforms;
+    use crate::reporter::json::JsonPrinter;
+    use cargo_metadata::DependencyKind;
+
+    #[test]
+    fn json_event() {
+        // root -> a (1.60); root -> b (build); a -> c (1.60)
+        let graph = fixtures::graph(
+            &[
+                ("root", None),
+                ("a", Some("1.60")),
+                ("b", None),
+                ("c", Some("1.60")),
+            ],
+            &[
+                ("root", "a", &[DependencyKind::Normal]),
+                ("root", "b", &[DependencyKind::Build]),
+                ("a", "c", &[DependencyKind::Normal]),
+            ],
+        );
+        let formatter = ByMSRVFormatter::<JsonPrinter>::new(graph);
+
+        let event = assert_conforms(&formatter.to_string());
+
+        assert_eq!(event["variant"], "ordered-by-msrv");
+        assert_eq!(event["list"].len(), 2);
+
+        // packages without an MSRV are listed first
+        let without_msrv = &event["list"][0];
+        assert!(without_msrv["msrv"].is_null());
+        let mut names = without_msrv["dependencies"]
+            .members()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["b", "root"]);
+
+        let with_msrv = &event["list"][1];
+        assert_eq!(with_msrv["msrv"], "1.60.0");
+        assert_eq!(with_msrv["dependencies"].len(), 2);
+        assert_eq!(with_msrv["kinds"][0][0], "normal");
+    }
 }
@@ -0,0 +1,425 @@
+use crate::dependencies::formatter::{label_dependency_kinds, package_msrv};
+use crate::dependencies::{DependencyGraph, DependencyKinds};
+use crate::reporter::json::Event;
+use crate::reporter::Output;
+use crate::semver;
+use cargo_metadata::{Package, PackageId};
+use petgraph::graph::NodeIndex;
+use petgraph::visit::Bfs;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Formatter;
+use std::marker::PhantomData;
+
+type Node = NodeIndex<crate::dependencies::PackageGraphIndex>;
+
+/// Displays the dependencies of the project as a tree, with the MSRV of each dependency.
+///
+/// The chain of dependencies from the root crate to the dependency with the highest MSRV is
+/// highlighted, to show which dependencies pulled it in. Dependencies which occur more than once
+/// in the tree are only expanded at their first occurrence, and marked with `(*)` elsewhere.
+///
+/// For example:
+///
+/// ```text
+/// my-crate v0.1.0 (MSRV 1.56.0)
+/// ┣━━ some-dep v1.2.0 (MSRV 1.60.0)
+/// ┃   ┗━━ pulled-in v2.0.0 (MSRV 1.65.0)
+/// └── other-dep v0.3.0
+///     └── some-dep v1.2.0 (MSRV 1.60.0) (*)
+/// ```
+pub(crate) struct TreeFormatter<T: Output> {
+    graph: DependencyGraph,
+    output: PhantomData<T>,
+}
+
+impl<T: Output> TreeFormatter<T> {
+    pub fn new(graph: DependencyGraph) -> Self {
+        Self {
+            graph,
+            output: PhantomData,
+        }
+    }
+}
+
+impl<T: Output> TreeFormatter<T> {
+    fn root(&self) -> Node {
+        self.graph.index[&self.graph.root_crate].into()
+    }
+
+    /// The chain of packages from the root crate to the dependency with the highest MSRV, or an
+    /// empty chain if none of the dependencies specifies an MSRV.
+    ///
+    /// When several dependencies share the highest MSRV, the one closest to the root crate is
+    /// picked. Of the chains which lead to it, the shortest is returned.
+    fn highest_msrv_path(&self) -> Vec<Node> {
+        let graph = &self.graph.packages;
+        let root = self.root();
+
+        let mut parents = HashMap::new();
+        let mut highest: Option<(Node, semver::Version)> = None;
+
+        let mut bfs = Bfs::new(graph, root);
+        while let Some(node) = bfs.next(graph) {
+            for child in graph.neighbors_directed(node, petgraph::Direction::Outgoing) {
+                parents.entry(child).or_insert(node);
+            }
+
+            if node == root {
+                continue;
+            }
+
+            if let Some(msrv) = package_msrv(&graph[node]) {
+                if highest.as_ref().map_or(true, |(_, max)| &msrv > max) {
+                    highest = Some((node, msrv));
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut next = highest.map(|(node, _)| node);
+
+        while let Some(node) = next {
+            path.push(node);
+            next = if node == root {
+                None
+            } else {
+                parents.get(&node).copied()
+            };
+        }
+
+        path.reverse();
+        path
+    }
+
+    fn tree(&self) -> Tree<'_> {
+        let path = self.highest_msrv_path();
+        let reached_through = self.graph.reached_through();
+        let mut expanded = HashSet::new();
+
+        let root = self.root();
+        let tree = self.subtree(
+            root,
+            path.first() == Some(&root),
+            &path,
+            &reached_through,
+            &mut expanded,
+        );
+
+        let highest_msrv = path
+            .last()
+            .map(|&node| &self.graph.packages[node])
+            .and_then(|package| package_msrv(package).map(|msrv| (package, msrv)))
+            .map(|(package, msrv)| HighestMsrv {
+                package,
+                msrv,
+                path: path
+                    .iter()
+                    .map(|&node| self.graph.packages[node].name.as_str())
+                    .collect(),
+            });
+
+        Tree { tree, highest_msrv }
+    }
+
+    fn subtree<'g>(
+        &'g self,
+        node: Node,
+        on_path: bool,
+        path: &[Node],
+        reached_through: &HashMap<&PackageId, DependencyKinds>,
+        expanded: &mut HashSet<Node>,
+    ) -> TreeNode<'g> {
+        let graph = &self.graph.packages;
+        let package = &graph[node];
+
+        let mut tree_node = TreeNode {
+            package,
+            msrv: package_msrv(package),
+            kinds: reached_through
+                .get(&package.id)
+                .cloned()
+                .unwrap_or_default(),
+            on_path,
+            repeated: !expanded.insert(node),
+            children: Vec::new(),
+        };
+
+        if tree_node.repeated {
+            return tree_node;
+        }
+
+        // the next package of the highlighted chain, if this package is part of it
+        let next_on_path = path
+            .iter()
+            .position(|&n| n == node)
+            .filter(|_| on_path)
+            .and_then(|position| path.get(position + 1))
+            .copied();
+
+        let mut children = graph
+            .neighbors_directed(node, petgraph::Direction::Outgoing)
+            .collect::<Vec<_>>();
+        // the child on the highlighted chain goes first, so it's expanded there
+        children.sort_by_key(|&child| {
+            (
+                Some(child) != next_on_path,
+                &graph[child].name,
+                &graph[child].version,
+            )
+        });
+        children.dedup();
+
+        tree_node.children = children
+            .into_iter()
+            .map(|child| {
+                self.subtree(
+                    child,
+                    Some(child) == next_on_path,
+                    path,
+                    reached_through,
+                    expanded,
+                )
+            })
+            .collect();
+
+        tree_node
+    }
+}
+
+impl std::fmt::Display for TreeFormatter<crate::reporter::ui::HumanPrinter<'_>> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let tree = self.tree();
+
+        writeln!(f, "{}", human_label(&tree.tree))?;
+        write_human_children(f, &tree.tree, "")?;
+
+        if let Some(highest) = tree.highest_msrv {
+            writeln!(
+                f,
+                "\nHighest MSRV: {} v{} requires Rust {}, pulled in by: {}",
+                highest.package.name,
+                highest.package.version,
+                highest.msrv,
+                highest.path.join(" → "),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn human_label(node: &TreeNode<'_>) -> String {
+    let mut label = format!("{} v{}", node.package.name, node.package.version);
+
+    if let Some(msrv) = &node.msrv {
+        label.push_str(&format!(" (MSRV {})", msrv));
+    }
+
+    label.push_str(&label_dependency_kinds(&node.kinds));
+
+    if node.repeated {
+        label.push_str(" (*)");
+    }
+
+    if node.on_path {
+        console::style(label).bold().to_string()
+    } else {
+        label
+    }
+}
+
+fn write_human_children(
+    f: &mut Formatter<'_>,
+    node: &TreeNode<'_>,
+    prefix: &str,
+) -> std::fmt::Result {
+    let last = node.children.len().saturating_sub(1);
+
+    for (i, child) in node.children.iter().enumerate() {
+        let connector = match (child.on_path, i == last) {
+            (true, false) => "┣━━ ",
+            (true, true) => "┗━━ ",
+            (false, false) => "├── ",
+            (false, true) => "└── ",
+        };
+
+        writeln!(f, "{}{}{}", prefix, connector, human_label(child))?;
+
+        let continuation = match (child.on_path, i == last) {
+            (_, true) => "    ",
+            (true, false) => "┃   ",
+            (false, false) => "│   ",
+        };
+
+        write_human_children(f, child, &format!("{}{}", prefix, continuation))?;
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct TreeEvent<'a> {
+    variant: &'a str,
+    success: bool,
+    tree: TreeNodeFields<'a>,
+    highest_msrv: Option<HighestMsrvFields<'a>>,
+}
+
+#[derive(Serialize)]
+struct TreeNodeFields<'a> {
+    dependency: &'a str,
+    version: String,
+    msrv: Option<String>,
+    kinds: Vec<String>,
+    on_highest_msrv_path: bool,
+    repeated: bool,
+    dependencies: Vec<TreeNodeFields<'a>>,
+}
+
+#[derive(Serialize)]
+struct HighestMsrvFields<'a> {
+    dependency: &'a str,
+    version: String,
+    msrv: String,
+    path: Vec<&'a str>,
+}
+
+impl std::fmt::Display for TreeFormatter<crate::reporter::json::JsonPrinter<'_>> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let tree = self.tree();
+
+        let event = TreeEvent {
+            variant: crate::config::list::TREE,
+            success: true,
+            tree: tree_node_fields(&tree.tree),
+            highest_msrv: tree.highest_msrv.map(|highest| HighestMsrvFields {
+                dependency: &highest.package.name,
+                version: format!("{}", highest.package.version),
+                msrv: format!("{}", highest.msrv),
+                path: highest.path,
+            }),
+        };
+
+        writeln!(f, "{}", Event::new("list", event).to_line())
+    }
+}
+
+fn tree_node_fields<'g>(node: &TreeNode<'g>) -> TreeNodeFields<'g> {
+    TreeNodeFields {
+        dependency: &node.package.name,
+        version: format!("{}", node.package.version),
+        msrv: node.msrv.as_ref().map(ToString::to_string),
+        kinds: node.kinds.iter().map(ToString::to_string).collect(),
+        on_highest_msrv_path: node.on_path,
+        repeated: node.repeated,
+        dependencies: node.children.iter().map(tree_node_fields).collect(),
+    }
+}
+
+struct Tree<'g> {
+    tree: TreeNode<'g>,
+    highest_msrv: Option<HighestMsrv<'g>>,
+}
+
+struct TreeNode<'g> {
+    package: &'g Package,
+    msrv: Option<semver::Version>,
+    // the kinds of dependency of the root crate through which the package was reached
+    kinds: DependencyKinds,
+    // whether the edge to this package is part of the chain to the dependency with the highest MSRV
+    on_path: bool,
+    // whether the package was already expanded elsewhere in the tree
+    repeated: bool,
+    children: Vec<TreeNode<'g>>,
+}
+
+struct HighestMsrv<'g> {
+    package: &'g Package,
+    msrv: semver::Version,
+    path: Vec<&'g str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TreeFormatter;
+    use crate::dependencies::{fixtures, DependencyGraph};
+    use crate::reporter::json::schema::assert_conforms;
+    use crate::reporter::json::JsonPrinter;
+    use crate::reporter::ui::HumanPrinter;
+    use cargo_metadata::DependencyKind;
+
+    // root -> a -> c (1.65)
+    // root -> b (1.60) -> a
+    fn graph() -> DependencyGraph {
+        let normal: &[DependencyKind] = &[DependencyKind::Normal];
+
+        fixtures::graph(
+            &[
+                ("root", Some("1.56")),
+                ("a", None),
+                ("b", Some("1.60")),
+                ("c", Some("1.65")),
+            ],
+            &[
+                ("root", "a", normal),
+                ("root", "b", normal),
+                ("b", "a", normal),
+                ("a", "c", normal),
+            ],
+        )
+    }
+
+    #[test]
+    fn path_to_highest_msrv() {
+        let formatter = TreeFormatter::<HumanPrinter>::new(graph());
+
+        let tree = formatter.tree();
+        let highest = tree.highest_msrv.unwrap();
+
+        assert_eq!(highest.package.name, "c");
+        assert_eq!(highest.path, vec!["root", "a", "c"]);
+    }
+
+    #[test]
+    fn render_tree() {
+        console::set_colors_enabled(false);
+        let formatter = TreeFormatter::<HumanPrinter>::new(graph());
+
+        assert_eq!(
+            formatter.to_string(),
+            concat!(
+                "root v1.0.0 (MSRV 1.56.0)\n",
+                "┣━━ a v1.0.0\n",
+                "┃   ┗━━ c v1.0.0 (MSRV 1.65.0)\n",
+                "└── b v1.0.0 (MSRV 1.60.0)\n",
+                "    └── a v1.0.0 (*)\n",
+                "\n",
+                "Highest MSRV: c v1.0.0 requires Rust 1.65.0, pulled in by: root → a → c\n",
+            )
+        );
+    }
+
+    #[test]
+    fn json_event() {
+        let formatter = TreeFormatter::<JsonPrinter>::new(graph());
+
+        let event = assert_conforms(&formatter.to_string());
+
+        let a = &event["tree"]["dependencies"][0];
+        assert_eq!(a["dependency"], "a");
+        assert!(a["msrv"].is_null());
+        assert_eq!(a["on_highest_msrv_path"], true);
+        assert_eq!(event["highest_msrv"]["dependency"], "c");
+    }
+
+    #[test]
+    fn no_dependency_specifies_msrv() {
+        let graph = fixtures::graph(&[("root", None)], &[]);
+
+        let formatter = TreeFormatter::<HumanPrinter>::new(graph);
+
+        assert!(formatter.tree().highest_msrv.is_none());
+        assert_eq!(formatter.to_string(), "root v1.0.0\n");
+    }
+}
@@ -1,14 +1,49 @@
-use cargo_metadata::PackageId;
-use std::collections::HashMap;
+use cargo_metadata::{DependencyKind, PackageId};
+use std::collections::{HashMap, VecDeque};
 
 pub(crate) mod formatter;
+pub(crate) mod msrv_lockfile;
+pub(crate) mod registry_index;
 pub(crate) mod resolver;
+pub(crate) mod verify;
 
 type PackageGraphIndex = usize;
 // NB: stable graph because we need our DependencyGraph::index to be able to bridge between id's
 //  even after removals, which we do to remove dev- and build dependencies.
-type PackageGraph =
-    petgraph::stable_graph::StableDiGraph<cargo_metadata::Package, (), PackageGraphIndex>;
+type PackageGraph = petgraph::stable_graph::StableDiGraph<
+    cargo_metadata::Package,
+    DependencyKinds,
+    PackageGraphIndex,
+>;
+
+/// The kinds of dependency by which a package depends on another package, e.g. as a normal
+/// dependency, and as a build dependency.
+pub(crate) type DependencyKinds = Vec<DependencyKind>;
+
+/// The kinds of dependency which are included in the dependency graph by default: those which
+/// are needed to build the crate.
+pub(crate) const DEFAULT_DEPENDENCY_KINDS: &[DependencyKind] =
+    &[DependencyKind::Normal, DependencyKind::Build];
+
+/// Sort dependency kinds in the order normal, build, dev, and remove duplicates.
+pub(crate) fn sort_dependency_kinds(kinds: &mut DependencyKinds) {
+    kinds.sort_by_key(|kind| match kind {
+        DependencyKind::Normal => 0,
+        DependencyKind::Build => 1,
+        DependencyKind::Development => 2,
+        _ => 3,
+    });
+    kinds.dedup();
+}
+
+/// Formats dependency kinds as a comma separated list, e.g. `normal, dev`.
+pub(crate) fn format_dependency_kinds(kinds: &[DependencyKind]) -> String {
+    kinds
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
 /// A graph of dependencies from a designated root crate
 ///
@@ -41,4 +76,128 @@ impl DependencyGraph {
             root_crate,
         }
     }
+
+    /// The dependencies of the root crate, in breadth first order, and whether each of them is a
+    /// direct dependency of the root crate.
+    pub fn dependencies(&self) -> impl Iterator<Item = (&cargo_metadata::Package, bool)> + '_ {
+        let root = self.index[&self.root_crate].into();
+        let direct = self
+            .packages
+            .neighbors_directed(root, petgraph::Direction::Outgoing)
+            .collect::<Vec<_>>();
+
+        let mut bfs = petgraph::visit::Bfs::new(&self.packages, root);
+        std::iter::from_fn(move || bfs.next(&self.packages))
+            .filter(move |&node| node != root)
+            .map(move |node| (&self.packages[node], direct.contains(&node)))
+    }
+
+    /// The kinds of dependency of the root crate through which each of its dependencies is reached.
+    ///
+    /// A direct dependency is reached through the kinds by which the root crate depends on it. A
+    /// transitive dependency is reached through the kinds of each of the direct dependencies it is
+    /// a dependency of, e.g. the dependencies of a dev-dependency are reached as `dev`.
+    pub fn reached_through(&self) -> HashMap<&PackageId, DependencyKinds> {
+        use petgraph::visit::EdgeRef;
+
+        let root = self.index[&self.root_crate].into();
+        let mut reached = HashMap::<_, DependencyKinds>::new();
+
+        let mut queue = self
+            .packages
+            .edges_directed(root, petgraph::Direction::Outgoing)
+            .map(|edge| (edge.target(), edge.weight().clone()))
+            .collect::<VecDeque<_>>();
+
+        while let Some((node, kinds)) = queue.pop_front() {
+            let known = reached.entry(node).or_default();
+            let new = kinds
+                .into_iter()
+                .filter(|kind| !known.contains(kind))
+                .collect::<Vec<_>>();
+
+            if new.is_empty() {
+                continue;
+            }
+
+            known.extend(new.iter().copied());
+
+            for child in self
+                .packages
+                .neighbors_directed(node, petgraph::Direction::Outgoing)
+            {
+                if child != root {
+                    queue.push_back((child, new.clone()));
+                }
+            }
+        }
+
+        reached
+            .into_iter()
+            .map(|(node, mut kinds)| {
+                sort_dependency_kinds(&mut kinds);
+                (&self.packages[node].id, kinds)
+            })
+            .collect()
+    }
+
+    /// All packages in the graph, including the root crate, in no particular order.
+    pub fn packages(&self) -> impl Iterator<Item = &cargo_metadata::Package> + '_ {
+        self.packages
+            .node_indices()
+            .map(move |node| &self.packages[node])
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::DependencyGraph;
+    use cargo_metadata::{DependencyKind, Package, PackageId};
+
+    /// A package without dependencies, with version `1.0.0`, which is identified by its name.
+    pub(crate) fn package(name: &str, rust_version: Option<&str>) -> Package {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "version": "1.0.0",
+            "id": name,
+            "dependencies": [],
+            "targets": [],
+            "features": {},
+            "manifest_path": format!("/nonexistent/{}/Cargo.toml", name),
+            "rust_version": rust_version,
+        }))
+        .unwrap()
+    }
+
+    /// A dependency graph of the given packages and their MSRV, rooted at the first package, where
+    /// each edge links a package to one of its dependencies by name.
+    pub(crate) fn graph(
+        packages: &[(&str, Option<&str>)],
+        edges: &[(&str, &str, &[DependencyKind])],
+    ) -> DependencyGraph {
+        let mut graph = DependencyGraph::empty(PackageId {
+            repr: packages[0].0.to_string(),
+        });
+
+        for (name, msrv) in packages {
+            let package = package(name, *msrv);
+            let id = package.id.clone();
+            let node = graph.packages.add_node(package);
+            graph.index.insert(id, node.index());
+        }
+
+        for (from, to, kinds) in edges {
+            let from = graph.index[&PackageId {
+                repr: from.to_string(),
+            }];
+            let to = graph.index[&PackageId {
+                repr: to.to_string(),
+            }];
+            graph
+                .packages
+                .add_edge(from.into(), to.into(), kinds.to_vec());
+        }
+
+        graph
+    }
 }
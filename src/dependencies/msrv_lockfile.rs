@@ -0,0 +1,1322 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, ContentArrangement, Table};
+use serde::{Deserialize, Serialize};
+
+use crate::dependencies::formatter::package_msrv;
+use crate::dependencies::registry_index::{IndexDependency, IndexEntry, RegistryIndex};
+use crate::dependencies::DependencyGraph;
+use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
+use crate::formatter::{FormatUserOutput, Human, Json};
+use crate::reporter::json::Event;
+use crate::semver;
+
+/// A package in the lockfile, by its name, version and source. Path packages have no source.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct PackageKey {
+    name: String,
+    version: semver::Version,
+    source: Option<String>,
+}
+
+impl PackageKey {
+    /// The `name version (source)` form, which identifies a package completely.
+    fn full_id(&self) -> String {
+        match &self.source {
+            Some(source) => format!("{} {} ({})", self.name, self.version, source),
+            None => format!("{} {}", self.name, self.version),
+        }
+    }
+}
+
+/// Resolve a lockfile in which the MSRV of each package is at most the given MSRV, by downgrading
+/// the packages which require a newer Rust version.
+///
+/// The MSRV of the packages which are currently locked is taken from the dependency graph; the
+/// versions which are available, and their MSRV, are read from the given registry index, so no
+/// network access is required. Each package which requires a newer Rust version is replaced by
+/// its greatest version which satisfies the requirements of its dependents, and which does not
+/// require a newer Rust version. Versions which were published without a `rust-version` are
+/// assumed to be compatible.
+///
+/// The lockfile is rewritten in place. The resolved lockfile should be confirmed by checking the
+/// crate with the MSRV, since the MSRV's specified by crate authors may be wrong, or absent.
+pub(crate) fn resolve_msrv_lockfile(
+    graph: &DependencyGraph,
+    lockfile: &Path,
+    msrv: &semver::Version,
+    index: &RegistryIndex,
+) -> TResult<LockfileResolution> {
+    let contents = std::fs::read_to_string(lockfile).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFile(lockfile.to_path_buf()),
+    })?;
+
+    let mut lock = Lock::parse(&contents)?;
+
+    for package in graph.packages() {
+        let key = PackageKey {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            source: package.source.as_ref().map(|source| source.repr.clone()),
+        };
+
+        let requirements = package
+            .dependencies
+            .iter()
+            .filter(|dependency| dependency.kind != cargo_metadata::DependencyKind::Development)
+            .map(|dependency| (dependency.name.clone(), dependency.req.clone()))
+            .collect();
+
+        lock.requirements.insert(key.clone(), requirements);
+        lock.rust_versions.insert(key, package_msrv(package));
+    }
+
+    let changes = lock.downgrade(msrv, index)?;
+
+    std::fs::write(lockfile, lock.to_string()).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::WriteFile(lockfile.to_path_buf()),
+    })?;
+
+    Ok(LockfileResolution {
+        lockfile: lockfile.to_path_buf(),
+        msrv: msrv.clone(),
+        changes,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct EncodableLock {
+    version: Option<u32>,
+    #[serde(default)]
+    package: Vec<EncodablePackage>,
+    // lockfiles of the v1 format store the checksums of their packages in this table
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EncodablePackage {
+    name: String,
+    version: semver::Version,
+    source: Option<String>,
+    checksum: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+struct LockedPackage {
+    checksum: Option<String>,
+    dependencies: Vec<PackageKey>,
+}
+
+/// The features which the dependents of a package enable on it.
+#[derive(Debug, Default)]
+struct EnabledFeatures {
+    features: BTreeSet<String>,
+    default_features: bool,
+}
+
+/// A published version which is queued to be locked, with the source from which it is locked,
+/// and the dependencies which are enabled on it.
+type QueuedPackage = (IndexEntry, Option<String>, HashMap<String, Vec<String>>);
+
+/// The packages of a lockfile, and what's known about them.
+#[derive(Debug, Default)]
+struct Lock {
+    // `None` for lockfiles of the v1 format, which do not specify their version
+    version: Option<u32>,
+    packages: BTreeMap<PackageKey, LockedPackage>,
+    // The version requirements of each package on its dependencies, by name of the dependency
+    requirements: HashMap<PackageKey, Vec<(String, semver::VersionReq)>>,
+    // The MSRV of each package, if it specifies one
+    rust_versions: HashMap<PackageKey, Option<semver::Version>>,
+    // The features enabled on each package by the dependents which were locked while resolving
+    features: HashMap<PackageKey, EnabledFeatures>,
+}
+
+impl Lock {
+    fn parse(contents: &str) -> TResult<Self> {
+        let encodable: EncodableLock = toml_edit::easy::from_str(contents).map_err(|err| {
+            CargoMSRVError::GenericMessage(format!("Unable to parse the lockfile: {}", err))
+        })?;
+
+        let keys = encodable
+            .package
+            .iter()
+            .map(|package| PackageKey {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                source: package.source.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        // a dependency is written as `name`, `name version` or `name version (source)`, whichever
+        // is the shortest form which identifies a single package
+        let resolve = |dependency: &str| -> TResult<PackageKey> {
+            let (id, source) = match dependency.split_once(" (") {
+                Some((id, source)) => (id, source.strip_suffix(')')),
+                None => (dependency, None),
+            };
+
+            let mut parts = id.split_whitespace();
+            let name = parts.next().unwrap_or_default();
+            let version = parts
+                .next()
+                .map(str::parse::<semver::Version>)
+                .transpose()?;
+
+            let mut matches = keys.iter().filter(|key| {
+                key.name == name
+                    && version
+                        .as_ref()
+                        .map_or(true, |version| &key.version == version)
+                    && source.map_or(true, |source| key.source.as_deref() == Some(source))
+            });
+
+            match (matches.next(), matches.next()) {
+                (Some(key), None) => Ok(key.clone()),
+                _ => Err(Error::UnresolvedDependency {
+                    dependency: dependency.to_string(),
+                }
+                .into()),
+            }
+        };
+
+        let mut packages = BTreeMap::new();
+        for (package, key) in encodable.package.iter().zip(keys.iter()) {
+            let dependencies = package
+                .dependencies
+                .iter()
+                .map(|dependency| resolve(dependency))
+                .collect::<TResult<Vec<_>>>()?;
+
+            let checksum = package.checksum.clone().or_else(|| {
+                encodable
+                    .metadata
+                    .get(&format!("checksum {}", key.full_id()))
+                    .cloned()
+            });
+
+            packages.insert(
+                key.clone(),
+                LockedPackage {
+                    checksum,
+                    dependencies,
+                },
+            );
+        }
+
+        Ok(Self {
+            version: encodable.version,
+            packages,
+            ..Self::default()
+        })
+    }
+
+    /// Downgrade each registry package with an MSRV greater than the given MSRV, and return what
+    /// was changed.
+    fn downgrade(
+        &mut self,
+        msrv: &semver::Version,
+        index: &RegistryIndex,
+    ) -> TResult<Vec<LockfileChange>> {
+        let incompatible = self
+            .packages
+            .keys()
+            .filter(|key| {
+                is_registry_package(key) && self.rust_version(key).map_or(false, |v| v > msrv)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut changes = Vec::new();
+
+        for key in incompatible {
+            if !self.packages.contains_key(&key) {
+                continue;
+            }
+
+            let requirements = self.requirements_on(&key);
+            let candidate = index
+                .versions(&key.name)?
+                .into_iter()
+                .filter(|entry| {
+                    entry.vers < key.version && is_candidate(entry, msrv, &requirements)
+                })
+                .max_by(|lhs, rhs| lhs.vers.cmp(&rhs.vers))
+                .ok_or_else(|| Error::NoCompatibleVersion {
+                    name: key.name.clone(),
+                    version: key.version.clone(),
+                    msrv: msrv.clone(),
+                })?;
+
+            info!(
+                name = key.name.as_str(),
+                from = %key.version,
+                to = %candidate.vers,
+                "downgrading dependency"
+            );
+
+            let to = candidate.vers.clone();
+            self.replace(&key, candidate, msrv, index)?;
+
+            changes.push(LockfileChange {
+                name: key.name.clone(),
+                from: key.version.clone(),
+                to: Some(to),
+            });
+        }
+
+        let removed = self.remove_unreachable();
+
+        for key in removed {
+            if !changes.iter().any(|change| change.name == key.name) {
+                changes.push(LockfileChange {
+                    name: key.name,
+                    from: key.version,
+                    to: None,
+                });
+            }
+        }
+
+        Ok(changes)
+    }
+
+    fn rust_version(&self, key: &PackageKey) -> Option<&semver::Version> {
+        self.rust_versions.get(key).and_then(Option::as_ref)
+    }
+
+    /// The requirements of the packages which depend on the given locked package.
+    ///
+    /// A lockfile may contain several semver incompatible versions of a crate, like `syn 1` and
+    /// `syn 2`, so only the dependents which locked this exact version are considered, and of
+    /// those only the requirements which selected it.
+    fn requirements_on(&self, key: &PackageKey) -> Vec<semver::VersionReq> {
+        self.packages
+            .iter()
+            .filter(|(_, package)| package.dependencies.contains(key))
+            .flat_map(|(dependent, _)| self.requirements.get(dependent).into_iter().flatten())
+            .filter(|(name, req)| name == &key.name && req.matches(&key.version))
+            .map(|(_, req)| req.clone())
+            .collect()
+    }
+
+    /// Replace the locked package by the given version, and add the dependencies of this version
+    /// which are not locked yet.
+    fn replace(
+        &mut self,
+        key: &PackageKey,
+        entry: IndexEntry,
+        msrv: &semver::Version,
+        index: &RegistryIndex,
+    ) -> TResult<()> {
+        let previous = self.packages.remove(key).ok_or_else(|| Error::NotLocked {
+            name: key.name.clone(),
+            version: key.version.clone(),
+        })?;
+        let new_key = PackageKey {
+            name: entry.name.clone(),
+            version: entry.vers.clone(),
+            source: key.source.clone(),
+        };
+
+        // optional dependencies are only kept when they were enabled for the replaced version
+        let locked = previous
+            .dependencies
+            .iter()
+            .map(|dependency| dependency.name.as_str())
+            .collect::<HashSet<_>>();
+        let enabled = entry
+            .deps
+            .iter()
+            .filter(|dependency| !dependency.optional || locked.contains(dependency.package_name()))
+            .map(|dependency| (dependency.name.clone(), Vec::new()))
+            .collect();
+
+        let mut queue = VecDeque::new();
+        queue.push_back((entry, key.source.clone(), enabled));
+
+        while let Some((entry, source, enabled)) = queue.pop_front() {
+            let key = PackageKey {
+                name: entry.name.clone(),
+                version: entry.vers.clone(),
+                source: source.clone(),
+            };
+            let dependencies = required_dependencies(&entry, &enabled).collect::<Vec<_>>();
+
+            let mut locked_dependencies = Vec::with_capacity(dependencies.len());
+
+            for (dependency, features) in &dependencies {
+                let name = dependency.package_name();
+
+                // the optional dependencies of a package are enabled by the features requested
+                // by its dependents
+                let features = dependency
+                    .features
+                    .iter()
+                    .chain(features.iter())
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                // a package which is queued again keeps the dependencies it locked already
+                let locked = self
+                    .packages
+                    .get(&key)
+                    .and_then(|package| {
+                        package
+                            .dependencies
+                            .iter()
+                            .find(|locked| {
+                                locked.name == name && dependency.req.matches(&locked.version)
+                            })
+                            .cloned()
+                    })
+                    .or_else(|| self.locked_match(name, &dependency.req, &source));
+
+                if let Some(locked) = locked {
+                    self.enable_features(
+                        &locked,
+                        &features,
+                        dependency.default_features,
+                        index,
+                        &mut queue,
+                    )?;
+                    locked_dependencies.push(locked);
+                    continue;
+                }
+
+                let requirements = vec![dependency.req.clone()];
+                let candidate = index
+                    .versions(name)?
+                    .into_iter()
+                    .filter(|candidate| is_candidate(candidate, msrv, &requirements))
+                    .max_by(|lhs, rhs| lhs.vers.cmp(&rhs.vers))
+                    .ok_or_else(|| Error::NoMatchingVersion {
+                        name: name.to_string(),
+                        req: dependency.req.clone(),
+                    })?;
+
+                let candidate_key = PackageKey {
+                    name: candidate.name.clone(),
+                    version: candidate.vers.clone(),
+                    source: source.clone(),
+                };
+
+                // reserve the package, so later dependencies can match it
+                self.packages.insert(
+                    candidate_key.clone(),
+                    LockedPackage {
+                        checksum: Some(candidate.cksum.clone()),
+                        dependencies: Vec::new(),
+                    },
+                );
+                self.features.insert(
+                    candidate_key.clone(),
+                    EnabledFeatures {
+                        features: features.iter().cloned().collect(),
+                        default_features: dependency.default_features,
+                    },
+                );
+                locked_dependencies.push(candidate_key);
+
+                let enabled =
+                    candidate.enabled_dependencies(&features, dependency.default_features);
+                queue.push_back((candidate, source.clone(), enabled));
+            }
+
+            let requirements = self.requirements.entry(key.clone()).or_default();
+            for (dependency, _) in &dependencies {
+                let requirement = (
+                    dependency.package_name().to_string(),
+                    dependency.req.clone(),
+                );
+
+                if !requirements.contains(&requirement) {
+                    requirements.push(requirement);
+                }
+            }
+
+            self.rust_versions.insert(key.clone(), entry.rust_version());
+
+            let package = self.packages.entry(key).or_insert_with(|| LockedPackage {
+                checksum: Some(entry.cksum.clone()),
+                dependencies: Vec::new(),
+            });
+            for dependency in locked_dependencies {
+                if !package.dependencies.contains(&dependency) {
+                    package.dependencies.push(dependency);
+                }
+            }
+        }
+
+        // point the dependents of the replaced package to its replacement
+        for package in self.packages.values_mut() {
+            for dependency in &mut package.dependencies {
+                if dependency == key {
+                    *dependency = new_key.clone();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Enable the given features on a locked registry package. When these were not all enabled
+    /// yet, the package is queued again, so the dependencies which they enable are locked too.
+    fn enable_features(
+        &mut self,
+        key: &PackageKey,
+        features: &[String],
+        default_features: bool,
+        index: &RegistryIndex,
+        queue: &mut VecDeque<QueuedPackage>,
+    ) -> TResult<()> {
+        if !is_registry_package(key) {
+            return Ok(());
+        }
+
+        let enabled = self.features.entry(key.clone()).or_default();
+        let is_enabled = features
+            .iter()
+            .all(|feature| enabled.features.contains(feature))
+            && (enabled.default_features || !default_features);
+
+        if is_enabled {
+            return Ok(());
+        }
+
+        enabled.features.extend(features.iter().cloned());
+        enabled.default_features |= default_features;
+
+        let features = enabled.features.iter().cloned().collect::<Vec<_>>();
+        let default_features = enabled.default_features;
+
+        // without its published dependencies, the package can only be kept as it is locked
+        if let Some(entry) = index
+            .versions(&key.name)?
+            .into_iter()
+            .find(|entry| entry.vers == key.version)
+        {
+            let enabled = entry.enabled_dependencies(&features, default_features);
+            queue.push_back((entry, key.source.clone(), enabled));
+        }
+
+        Ok(())
+    }
+
+    /// The greatest version of the package with the given name which is locked from the given
+    /// source, and which matches the requirement.
+    fn locked_match(
+        &self,
+        name: &str,
+        req: &semver::VersionReq,
+        source: &Option<String>,
+    ) -> Option<PackageKey> {
+        self.packages
+            .keys()
+            .filter(|key| key.name == name && &key.source == source && req.matches(&key.version))
+            .max_by(|lhs, rhs| lhs.version.cmp(&rhs.version))
+            .cloned()
+    }
+
+    /// Remove the packages which can no longer be reached from the workspace members, and return
+    /// them.
+    fn remove_unreachable(&mut self) -> Vec<PackageKey> {
+        let mut reachable = HashSet::new();
+        let mut queue = self
+            .packages
+            .keys()
+            .filter(|key| key.source.is_none())
+            .cloned()
+            .collect::<VecDeque<_>>();
+
+        while let Some(key) = queue.pop_front() {
+            if reachable.insert(key.clone()) {
+                if let Some(package) = self.packages.get(&key) {
+                    queue.extend(package.dependencies.iter().cloned());
+                }
+            }
+        }
+
+        let unreachable = self
+            .packages
+            .keys()
+            .filter(|key| !reachable.contains(*key))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for key in &unreachable {
+            self.packages.remove(key);
+        }
+
+        unreachable
+    }
+
+    fn is_v1(&self) -> bool {
+        self.version.is_none()
+    }
+}
+
+impl std::fmt::Display for Lock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut names = HashMap::<&str, usize>::new();
+        let mut ids = HashMap::<(&str, &semver::Version), usize>::new();
+        for key in self.packages.keys() {
+            *names.entry(&key.name).or_default() += 1;
+            *ids.entry((&key.name, &key.version)).or_default() += 1;
+        }
+
+        writeln!(f, "# This file is automatically @generated by Cargo.")?;
+        writeln!(f, "# It is not intended for manual editing.")?;
+
+        if let Some(version) = self.version {
+            writeln!(f, "version = {}", version)?;
+        }
+
+        for (key, package) in &self.packages {
+            let mut out = String::new();
+
+            writeln!(out, "\n[[package]]")?;
+            writeln!(out, "name = \"{}\"", key.name)?;
+            writeln!(out, "version = \"{}\"", key.version)?;
+
+            if let Some(source) = &key.source {
+                writeln!(out, "source = \"{}\"", source)?;
+            }
+
+            if let Some(checksum) = package.checksum.as_ref().filter(|_| !self.is_v1()) {
+                writeln!(out, "checksum = \"{}\"", checksum)?;
+            }
+
+            if !package.dependencies.is_empty() {
+                // lockfiles of the v1 format always identify a dependency completely
+                let mut dependencies = package
+                    .dependencies
+                    .iter()
+                    .map(|dependency| {
+                        if self.is_v1() || ids[&(dependency.name.as_str(), &dependency.version)] > 1
+                        {
+                            dependency.full_id()
+                        } else if names[dependency.name.as_str()] > 1 {
+                            format!("{} {}", dependency.name, dependency.version)
+                        } else {
+                            dependency.name.clone()
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                dependencies.sort();
+                dependencies.dedup();
+
+                writeln!(out, "dependencies = [")?;
+                for dependency in dependencies {
+                    writeln!(out, " \"{}\",", dependency)?;
+                }
+                writeln!(out, "]")?;
+            }
+
+            f.write_str(&out)?;
+        }
+
+        let checksums = self
+            .packages
+            .iter()
+            .filter(|_| self.is_v1())
+            .filter_map(|(key, package)| Some((key, package.checksum.as_ref()?)))
+            .collect::<Vec<_>>();
+
+        if !checksums.is_empty() {
+            writeln!(f, "\n[metadata]")?;
+
+            for (key, checksum) in checksums {
+                writeln!(f, "\"checksum {}\" = \"{}\"", key.full_id(), checksum)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_registry_package(key: &PackageKey) -> bool {
+    key.source.as_deref().map_or(false, |source| {
+        source.starts_with("registry+") || source.starts_with("sparse+")
+    })
+}
+
+/// Whether the published version may be locked: it must not be yanked, it must match the given
+/// requirements, and it must not require a Rust version newer than the MSRV.
+fn is_candidate(
+    entry: &IndexEntry,
+    msrv: &semver::Version,
+    requirements: &[semver::VersionReq],
+) -> bool {
+    !entry.yanked
+        && requirements.iter().all(|req| req.matches(&entry.vers))
+        && entry
+            .rust_version()
+            .map_or(true, |version| &version <= msrv)
+}
+
+/// The dependencies of a published version which need to be locked, with the features which are
+/// enabled on them. Optional dependencies are only included if they are enabled.
+fn required_dependencies<'e>(
+    entry: &'e IndexEntry,
+    enabled: &'e HashMap<String, Vec<String>>,
+) -> impl Iterator<Item = (&'e IndexDependency, &'e Vec<String>)> + 'e {
+    entry
+        .deps
+        .iter()
+        .filter(|dependency| dependency.is_build_dependency())
+        .filter_map(move |dependency| {
+            enabled
+                .get(&dependency.name)
+                .map(|features| (dependency, features))
+        })
+}
+
+/// The packages which were changed to resolve a lockfile which is compatible with the MSRV.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LockfileResolution {
+    lockfile: PathBuf,
+    msrv: semver::Version,
+    changes: Vec<LockfileChange>,
+}
+
+impl LockfileResolution {
+    pub fn lockfile(&self) -> &Path {
+        &self.lockfile
+    }
+
+    pub fn msrv(&self) -> &semver::Version {
+        &self.msrv
+    }
+
+    pub fn changes(&self) -> &[LockfileChange] {
+        &self.changes
+    }
+}
+
+/// A package which was downgraded, or which was removed because it is no longer depended upon.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LockfileChange {
+    name: String,
+    from: semver::Version,
+    to: Option<semver::Version>,
+}
+
+impl LockfileChange {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn from(&self) -> &semver::Version {
+        &self.from
+    }
+
+    /// The version to which the package was downgraded, or `None` if it was removed.
+    pub fn to(&self) -> Option<&semver::Version> {
+        self.to.as_ref()
+    }
+}
+
+impl FormatUserOutput<Human> for LockfileResolution {
+    fn format_line(&self) -> String {
+        if self.changes.is_empty() {
+            return format!(
+                "All locked dependencies are compatible with MSRV {}",
+                self.msrv
+            );
+        }
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec!["Dependency", "Locked version", "Resolved version"]);
+
+        for change in &self.changes {
+            table.add_row(vec![
+                Cell::new(change.name()),
+                Cell::new(change.from()),
+                Cell::new(
+                    change
+                        .to()
+                        .map(ToString::to_string)
+                        .unwrap_or_else(|| "removed".to_string()),
+                ),
+            ]);
+        }
+
+        format!(
+            "{}\nResolved '{}' for MSRV {}",
+            table,
+            self.lockfile.display(),
+            self.msrv
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct LockfileResolutionEvent<'a> {
+    lockfile: String,
+    msrv: String,
+    changes: Vec<LockfileChangeFields<'a>>,
+}
+
+#[derive(Serialize)]
+struct LockfileChangeFields<'a> {
+    dependency: &'a str,
+    from: String,
+    to: Option<String>,
+}
+
+impl FormatUserOutput<Json> for LockfileResolution {
+    fn format_line(&self) -> String {
+        let changes = self
+            .changes
+            .iter()
+            .map(|change| LockfileChangeFields {
+                dependency: change.name(),
+                from: change.from().to_string(),
+                to: change.to().map(ToString::to_string),
+            })
+            .collect();
+
+        let event = LockfileResolutionEvent {
+            lockfile: format!("{}", self.lockfile.display()),
+            msrv: self.msrv.to_string(),
+            changes,
+        };
+
+        Event::new("msrv-lockfile", event).to_line()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("No version of '{name}' older than {version} is compatible with MSRV {msrv}, and with the requirements of its dependents")]
+    NoCompatibleVersion {
+        name: String,
+        version: semver::Version,
+        msrv: semver::Version,
+    },
+
+    #[error("No version of '{name}' which matches '{req}' was found in the registry index")]
+    NoMatchingVersion {
+        name: String,
+        req: semver::VersionReq,
+    },
+
+    #[error("Dependency '{dependency}' does not identify a single package in the lockfile")]
+    UnresolvedDependency { dependency: String },
+
+    #[error("Package '{name} {version}' is not locked")]
+    NotLocked {
+        name: String,
+        version: semver::Version,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lock, LockfileResolution, PackageKey, RegistryIndex};
+    use crate::dependencies::registry_index::index_file;
+    use crate::formatter::{FormatUserOutput, Json};
+    use crate::reporter::json::schema::assert_conforms;
+    use crate::semver::Version;
+    use std::path::PathBuf;
+
+    const REGISTRY: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
+    fn key(name: &str, version: &str, source: Option<&str>) -> PackageKey {
+        PackageKey {
+            name: name.to_string(),
+            version: version.parse().unwrap(),
+            source: source.map(String::from),
+        }
+    }
+
+    fn registry_index(name: &str, entries: &[&str]) -> PathBuf {
+        let root = std::env::temp_dir()
+            .join("cargo-msrv-test-msrv-lockfile")
+            .join(name);
+        let _ = std::fs::remove_dir_all(&root);
+
+        for entry in entries {
+            let json = json::parse(entry).unwrap();
+            let crate_name = json["name"].as_str().unwrap().to_string();
+            let path = root.join(index_file(&crate_name));
+
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let mut contents = std::fs::read_to_string(&path).unwrap_or_default();
+            contents.push_str(entry);
+            contents.push('\n');
+            std::fs::write(&path, contents).unwrap();
+        }
+
+        root
+    }
+
+    fn lockfile() -> String {
+        format!(
+            r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "fancy",
+ "plain",
+]
+
+[[package]]
+name = "fancy"
+version = "1.2.0"
+source = "{r}"
+checksum = "f12"
+dependencies = [
+ "shiny",
+]
+
+[[package]]
+name = "plain"
+version = "0.3.0"
+source = "{r}"
+checksum = "p03"
+
+[[package]]
+name = "shiny"
+version = "2.0.0"
+source = "{r}"
+checksum = "s20"
+"#,
+            r = REGISTRY
+        )
+    }
+
+    fn lock(msrv_of_fancy: &str) -> Lock {
+        let mut lock = Lock::parse(&lockfile()).unwrap();
+
+        lock.requirements.insert(
+            key("app", "0.1.0", None),
+            vec![
+                ("fancy".to_string(), "^1".parse().unwrap()),
+                ("plain".to_string(), "^0.3".parse().unwrap()),
+            ],
+        );
+        lock.requirements.insert(
+            key("fancy", "1.2.0", Some(REGISTRY)),
+            vec![("shiny".to_string(), "^2".parse().unwrap())],
+        );
+        lock.rust_versions.insert(
+            key("fancy", "1.2.0", Some(REGISTRY)),
+            Some(msrv_of_fancy.parse().unwrap()),
+        );
+
+        lock
+    }
+
+    fn index(name: &str) -> RegistryIndex {
+        RegistryIndex::new(registry_index(
+            name,
+            &[
+                r#"{"name":"fancy","vers":"1.0.0","deps":[{"name":"old","req":"^1","optional":false,"kind":"normal"}],"cksum":"f10","rust_version":"1.40"}"#,
+                r#"{"name":"fancy","vers":"1.1.0","deps":[{"name":"shiny","req":"^2","optional":false,"kind":"normal"},{"name":"extra","req":"^1","optional":true,"kind":"normal"}],"cksum":"f11","rust_version":"1.50"}"#,
+                r#"{"name":"fancy","vers":"1.1.1","deps":[],"cksum":"f111","yanked":true,"rust_version":"1.50"}"#,
+                r#"{"name":"fancy","vers":"1.2.0","deps":[{"name":"shiny","req":"^2","optional":false,"kind":"normal"}],"cksum":"f12","rust_version":"1.60"}"#,
+                r#"{"name":"old","vers":"1.0.0","deps":[],"cksum":"o10"}"#,
+                r#"{"name":"old","vers":"1.0.1","deps":[],"cksum":"o101","rust_version":"1.45"}"#,
+            ],
+        ))
+    }
+
+    #[test]
+    fn parse_and_write_lockfile() {
+        let lock = Lock::parse(&lockfile()).unwrap();
+
+        assert_eq!(lock.packages.len(), 4);
+        assert_eq!(lock.to_string(), lockfile());
+    }
+
+    #[test]
+    fn compatible_lockfile_is_unchanged() {
+        let mut lock = lock("1.50.0");
+
+        let changes = lock
+            .downgrade(&Version::new(1, 56, 0), &index("unchanged"))
+            .unwrap();
+
+        assert!(changes.is_empty());
+        assert_eq!(lock.to_string(), lockfile());
+    }
+
+    #[test]
+    fn downgrade_to_greatest_compatible_version() {
+        let mut lock = lock("1.60.0");
+
+        let changes = lock
+            .downgrade(&Version::new(1, 56, 0), &index("greatest"))
+            .unwrap();
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].name(), "fancy");
+        assert_eq!(changes[0].to(), Some(&Version::new(1, 1, 0)));
+
+        let written = lock.to_string();
+        assert!(written.contains("name = \"fancy\"\nversion = \"1.1.0\""));
+        assert!(written.contains("checksum = \"f11\""));
+        // the optional dependency was not enabled for the locked version
+        assert!(!written.contains("extra"));
+    }
+
+    #[test]
+    fn downgrade_adds_and_removes_dependencies() {
+        let mut lock = lock("1.60.0");
+
+        let changes = lock
+            .downgrade(&Version::new(1, 44, 0), &index("dependencies"))
+            .unwrap();
+
+        let written = lock.to_string();
+        assert!(written.contains("name = \"fancy\"\nversion = \"1.0.0\""));
+        // the greatest version of 'old' requires a newer Rust version
+        assert!(written.contains("name = \"old\"\nversion = \"1.0.0\""));
+        assert!(!written.contains("shiny"));
+        assert!(changes
+            .iter()
+            .any(|change| change.name() == "shiny" && change.to().is_none()));
+
+        let resolution = LockfileResolution {
+            lockfile: PathBuf::from("Cargo.lock"),
+            msrv: Version::new(1, 44, 0),
+            changes,
+        };
+        let event = assert_conforms(&FormatUserOutput::<Json>::format_line(&resolution));
+        assert!(event["changes"]
+            .members()
+            .any(|change| change["dependency"] == "shiny" && change["to"].is_null()));
+    }
+
+    #[test]
+    fn downgrade_adds_optional_dependencies_enabled_by_features() {
+        let mut lock = lock("1.60.0");
+
+        let index = RegistryIndex::new(registry_index(
+            "features",
+            &[
+                r#"{"name":"fancy","vers":"1.0.0","deps":[{"name":"old","req":"^1","features":["fast"],"default_features":false}],"cksum":"f10","rust_version":"1.40"}"#,
+                r#"{"name":"fancy","vers":"1.2.0","deps":[{"name":"shiny","req":"^2"}],"cksum":"f12","rust_version":"1.60"}"#,
+                r#"{"name":"old","vers":"1.0.0","deps":[{"name":"speedy","req":"^1","optional":true},{"name":"logger","req":"^1","optional":true}],"cksum":"o10","features":{"default":["logger"],"fast":["speedy"]}}"#,
+                r#"{"name":"speedy","vers":"1.0.0","deps":[],"cksum":"sp10"}"#,
+                r#"{"name":"logger","vers":"1.0.0","deps":[],"cksum":"l10"}"#,
+            ],
+        ));
+
+        lock.downgrade(&Version::new(1, 44, 0), &index).unwrap();
+
+        let written = lock.to_string();
+        assert!(written.contains("name = \"old\"\nversion = \"1.0.0\""));
+        // enabled by the 'fast' feature which 'fancy' requests
+        assert!(written.contains("name = \"speedy\"\nversion = \"1.0.0\""));
+        // only enabled by the default features, which 'fancy' disables
+        assert!(!written.contains("logger"));
+    }
+
+    #[test]
+    fn downgrade_one_of_several_major_versions() {
+        let contents = format!(
+            r#"version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "fancy",
+ "syn 1.0.0",
+]
+
+[[package]]
+name = "fancy"
+version = "1.2.0"
+source = "{r}"
+checksum = "f12"
+dependencies = [
+ "syn 2.1.0",
+]
+
+[[package]]
+name = "syn"
+version = "1.0.0"
+source = "{r}"
+checksum = "s10"
+
+[[package]]
+name = "syn"
+version = "2.1.0"
+source = "{r}"
+checksum = "s21"
+"#,
+            r = REGISTRY
+        );
+
+        let mut lock = Lock::parse(&contents).unwrap();
+        lock.requirements.insert(
+            key("app", "0.1.0", None),
+            vec![
+                ("fancy".to_string(), "^1".parse().unwrap()),
+                ("syn".to_string(), "^1".parse().unwrap()),
+            ],
+        );
+        lock.requirements.insert(
+            key("fancy", "1.2.0", Some(REGISTRY)),
+            vec![("syn".to_string(), "^2".parse().unwrap())],
+        );
+        lock.rust_versions.insert(
+            key("syn", "2.1.0", Some(REGISTRY)),
+            Some(Version::new(1, 60, 0)),
+        );
+
+        let index = RegistryIndex::new(registry_index(
+            "major-versions",
+            &[
+                r#"{"name":"syn","vers":"1.0.0","deps":[],"cksum":"s10"}"#,
+                r#"{"name":"syn","vers":"2.0.0","deps":[],"cksum":"s20","rust_version":"1.50"}"#,
+                r#"{"name":"syn","vers":"2.1.0","deps":[],"cksum":"s21","rust_version":"1.60"}"#,
+            ],
+        ));
+
+        // the requirement on syn 1 must not be mixed into the requirements on syn 2
+        assert_eq!(
+            lock.requirements_on(&key("syn", "2.1.0", Some(REGISTRY))),
+            vec!["^2".parse().unwrap()]
+        );
+
+        let changes = lock.downgrade(&Version::new(1, 56, 0), &index).unwrap();
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].name(), "syn");
+        assert_eq!(changes[0].to(), Some(&Version::new(2, 0, 0)));
+
+        let written = lock.to_string();
+        assert!(written.contains("name = \"syn\"\nversion = \"1.0.0\""));
+        assert!(written.contains(" \"syn 2.0.0\","));
+    }
+
+    #[test]
+    fn no_compatible_version() {
+        let mut lock = lock("1.60.0");
+
+        assert!(lock
+            .downgrade(&Version::new(1, 30, 0), &index("none"))
+            .is_err());
+    }
+
+    #[test]
+    fn reused_package_gains_enabled_features() {
+        let contents = format!(
+            r#"version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "fancy",
+ "old",
+]
+
+[[package]]
+name = "fancy"
+version = "1.2.0"
+source = "{r}"
+checksum = "f12"
+
+[[package]]
+name = "old"
+version = "1.0.0"
+source = "{r}"
+checksum = "o10"
+"#,
+            r = REGISTRY
+        );
+
+        let mut lock = Lock::parse(&contents).unwrap();
+        lock.requirements.insert(
+            key("app", "0.1.0", None),
+            vec![
+                ("fancy".to_string(), "^1".parse().unwrap()),
+                ("old".to_string(), "^1".parse().unwrap()),
+            ],
+        );
+        lock.rust_versions.insert(
+            key("fancy", "1.2.0", Some(REGISTRY)),
+            Some(Version::new(1, 60, 0)),
+        );
+
+        let index = RegistryIndex::new(registry_index(
+            "reused-features",
+            &[
+                r#"{"name":"fancy","vers":"1.0.0","deps":[{"name":"old","req":"^1","features":["fast"]}],"cksum":"f10","rust_version":"1.40"}"#,
+                r#"{"name":"fancy","vers":"1.2.0","deps":[],"cksum":"f12","rust_version":"1.60"}"#,
+                r#"{"name":"old","vers":"1.0.0","deps":[{"name":"speedy","req":"^1","optional":true}],"cksum":"o10","features":{"fast":["speedy"]}}"#,
+                r#"{"name":"speedy","vers":"1.0.0","deps":[],"cksum":"sp10"}"#,
+            ],
+        ));
+
+        lock.downgrade(&Version::new(1, 44, 0), &index).unwrap();
+
+        // 'old' was locked already, but 'fancy' enables its 'fast' feature
+        let old = &lock.packages[&key("old", "1.0.0", Some(REGISTRY))];
+        assert_eq!(
+            old.dependencies,
+            vec![key("speedy", "1.0.0", Some(REGISTRY))]
+        );
+        assert!(lock
+            .to_string()
+            .contains("name = \"speedy\"\nversion = \"1.0.0\""));
+    }
+
+    #[test]
+    fn dependency_disambiguated_by_source() {
+        let contents = format!(
+            r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "dup 1.0.0 (git+https://example.com/dup#0123)",
+]
+
+[[package]]
+name = "dup"
+version = "1.0.0"
+source = "git+https://example.com/dup#0123"
+
+[[package]]
+name = "dup"
+version = "1.0.0"
+source = "{r}"
+checksum = "d10"
+"#,
+            r = REGISTRY
+        );
+
+        let lock = Lock::parse(&contents).unwrap();
+
+        let app = &lock.packages[&key("app", "0.1.0", None)];
+        assert_eq!(
+            app.dependencies,
+            vec![key(
+                "dup",
+                "1.0.0",
+                Some("git+https://example.com/dup#0123")
+            )]
+        );
+        assert_eq!(lock.to_string(), contents);
+    }
+
+    #[test]
+    fn ambiguous_dependency_is_an_error() {
+        let contents = format!(
+            r#"version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "dup 1.0.0",
+]
+
+[[package]]
+name = "dup"
+version = "1.0.0"
+source = "git+https://example.com/dup#0123"
+
+[[package]]
+name = "dup"
+version = "1.0.0"
+source = "{r}"
+"#,
+            r = REGISTRY
+        );
+
+        assert!(Lock::parse(&contents).is_err());
+    }
+
+    #[test]
+    fn downgrade_v1_lockfile() {
+        let contents = format!(
+            r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "fancy 1.2.0 ({r})",
+ "plain 0.3.0 ({r})",
+]
+
+[[package]]
+name = "fancy"
+version = "1.2.0"
+source = "{r}"
+dependencies = [
+ "shiny 2.0.0 ({r})",
+]
+
+[[package]]
+name = "plain"
+version = "0.3.0"
+source = "{r}"
+
+[[package]]
+name = "shiny"
+version = "2.0.0"
+source = "{r}"
+
+[metadata]
+"checksum fancy 1.2.0 ({r})" = "f12"
+"checksum plain 0.3.0 ({r})" = "p03"
+"checksum shiny 2.0.0 ({r})" = "s20"
+"#,
+            r = REGISTRY
+        );
+
+        let mut lock = Lock::parse(&contents).unwrap();
+        assert_eq!(lock.to_string(), contents);
+
+        lock.requirements.insert(
+            key("app", "0.1.0", None),
+            vec![
+                ("fancy".to_string(), "^1".parse().unwrap()),
+                ("plain".to_string(), "^0.3".parse().unwrap()),
+            ],
+        );
+        lock.rust_versions.insert(
+            key("fancy", "1.2.0", Some(REGISTRY)),
+            Some(Version::new(1, 60, 0)),
+        );
+
+        lock.downgrade(&Version::new(1, 56, 0), &index("v1"))
+            .unwrap();
+
+        let written = lock.to_string();
+        assert!(!written.contains("version = 3"));
+        assert!(written.contains(&format!(" \"fancy 1.1.0 ({})\",", REGISTRY)));
+        assert!(written.contains(&format!(
+            "\"checksum fancy 1.1.0 ({})\" = \"f11\"",
+            REGISTRY
+        )));
+        assert!(!written.contains("checksum = "));
+    }
+}
@@ -0,0 +1,416 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
+use crate::manifest::bare_version::BareVersion;
+use crate::semver;
+
+/// A local copy of a registry index, like a checkout or a mirror of the crates.io index.
+///
+/// The index is laid out as described in the [Cargo book]: each crate has a file, located by the
+/// length of its name, which contains one json object for each published version of the crate.
+///
+/// [Cargo book]: https://doc.rust-lang.org/cargo/reference/registries.html#index-format
+#[derive(Clone, Debug)]
+pub struct RegistryIndex {
+    path: PathBuf,
+}
+
+impl RegistryIndex {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The published versions of the crate, ordered from the oldest to the most recently published
+    /// version. Returns an empty list if the index does not contain the crate.
+    pub fn versions(&self, name: &str) -> TResult<Vec<IndexEntry>> {
+        let path = self.path.join(index_file(name));
+
+        if !path.is_file() {
+            return Ok(Vec::new());
+        }
+
+        let contents = std::fs::read_to_string(&path).map_err(|error| CargoMSRVError::Io {
+            error,
+            source: IoErrorSource::ReadFile(path.clone()),
+        })?;
+
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|err| {
+                    CargoMSRVError::GenericMessage(format!(
+                        "Unable to parse registry index file '{}': {}",
+                        path.display(),
+                        err
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+/// The index cache which Cargo keeps of a registry, in the `.cache` folder of the local copy of
+/// the registry index, e.g. `$CARGO_HOME/registry/index/<registry>/.cache`.
+///
+/// Cargo caches the index file of every crate it looked up, with all published versions of the
+/// crate, including those which are not locked by any crate. Each cache file is laid out like the
+/// index, but consists of a header, followed by NUL separated pairs of a version and the json
+/// index entry of that version.
+#[derive(Clone, Debug)]
+pub struct CargoIndexCache {
+    path: PathBuf,
+}
+
+impl CargoIndexCache {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The index caches of the registries which Cargo used, located in the given Cargo home.
+    pub fn in_cargo_home(cargo_home: &Path) -> Vec<Self> {
+        std::fs::read_dir(cargo_home.join("registry").join("index"))
+            .map(|registries| {
+                registries
+                    .filter_map(Result::ok)
+                    .map(|registry| registry.path().join(".cache"))
+                    .filter(|cache| cache.is_dir())
+                    .map(Self::new)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The cached versions of the crate, in the order in which they were published. Returns an
+    /// empty list if the crate is not cached. Entries which can not be read are skipped.
+    pub fn versions(&self, name: &str) -> TResult<Vec<IndexEntry>> {
+        let path = self.path.join(index_file(name));
+
+        if !path.is_file() {
+            return Ok(Vec::new());
+        }
+
+        let contents = std::fs::read(&path).map_err(|error| CargoMSRVError::Io {
+            error,
+            source: IoErrorSource::ReadFile(path.clone()),
+        })?;
+
+        Ok(cached_entries(&contents)
+            .filter_map(|entry| serde_json::from_slice(entry).ok())
+            .collect())
+    }
+}
+
+/// The json index entries of a cache file. The header consists of the version of the cache
+/// format, which since version 2 is followed by the version of the index format as a 32 bit
+/// integer, and the NUL terminated version of the index at the time it was cached.
+fn cached_entries(contents: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let header = match contents.first() {
+        Some(1) => 1,
+        Some(_) => 5,
+        None => 0,
+    };
+
+    contents
+        .get(header..)
+        .unwrap_or_default()
+        .split(|&byte| byte == 0)
+        // skip the index version, and the version which precedes each entry
+        .skip(2)
+        .step_by(2)
+        .filter(|entry| !entry.is_empty())
+}
+
+/// The contents of a cache file of the Cargo index cache, with the given json index entries.
+#[cfg(test)]
+pub(crate) fn cache_file(entries: &[&str]) -> Vec<u8> {
+    let mut contents = vec![3, 2, 0, 0, 0];
+    contents.extend_from_slice(b"Unknown\0");
+
+    for entry in entries {
+        let version = serde_json::from_str::<IndexEntry>(entry).unwrap().vers;
+        contents.extend_from_slice(version.to_string().as_bytes());
+        contents.push(0);
+        contents.extend_from_slice(entry.as_bytes());
+        contents.push(0);
+    }
+
+    contents
+}
+
+/// The path of the index file of a crate, relative to the root of the index.
+pub(crate) fn index_file(name: &str) -> PathBuf {
+    let name = name.to_lowercase();
+
+    match name.len() {
+        1 => Path::new("1").join(&name),
+        2 => Path::new("2").join(&name),
+        3 => Path::new("3").join(&name[..1]).join(&name),
+        _ => Path::new(&name[..2]).join(&name[2..4]).join(&name),
+    }
+}
+
+/// A published version of a crate.
+#[derive(Clone, Debug, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    pub vers: semver::Version,
+    #[serde(default)]
+    pub deps: Vec<IndexDependency>,
+    pub cksum: String,
+    #[serde(default)]
+    pub yanked: bool,
+    #[serde(default)]
+    rust_version: Option<String>,
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+    /// Features which use the `dep:` or `?` syntax, which older versions of Cargo can't read
+    #[serde(default)]
+    features2: BTreeMap<String, Vec<String>>,
+}
+
+impl IndexEntry {
+    /// The MSRV of this version, if it was published with a `rust-version`.
+    pub fn rust_version(&self) -> Option<semver::Version> {
+        self.rust_version
+            .as_deref()
+            .and_then(|version| BareVersion::from_str(version).ok())
+            .map(|version| version.to_semver_version())
+    }
+
+    /// The dependencies which are needed when this version is used with the given features, by
+    /// the name under which they're depended upon, with the features which are enabled on them.
+    ///
+    /// Optional dependencies are included when a feature enables them, either by `dep:name`, by
+    /// `name/feature`, or by the implicit feature of the dependency.
+    pub fn enabled_dependencies(
+        &self,
+        features: &[String],
+        default_features: bool,
+    ) -> HashMap<String, Vec<String>> {
+        let mut queue = features.iter().map(String::as_str).collect::<Vec<_>>();
+        if default_features {
+            queue.push("default");
+        }
+
+        let mut visited = HashSet::new();
+        let mut enabled = HashSet::new();
+        let mut dependency_features = HashMap::<&str, Vec<String>>::new();
+
+        while let Some(feature) = queue.pop() {
+            if !visited.insert(feature) {
+                continue;
+            }
+
+            if let Some(dependency) = feature.strip_prefix("dep:") {
+                enabled.insert(dependency);
+            } else if let Some((dependency, dependency_feature)) = feature.split_once('/') {
+                // a weak dependency feature, `name?/feature`, doesn't enable the dependency
+                let dependency = match dependency.strip_suffix('?') {
+                    Some(dependency) => dependency,
+                    None => {
+                        queue.push(dependency);
+                        dependency
+                    }
+                };
+
+                dependency_features
+                    .entry(dependency)
+                    .or_default()
+                    .push(dependency_feature.to_string());
+            } else if let Some(enables) = self.feature(feature) {
+                queue.extend(enables.iter().map(String::as_str));
+            } else {
+                // the implicit feature of an optional dependency
+                enabled.insert(feature);
+            }
+        }
+
+        self.deps
+            .iter()
+            .filter(|dependency| !dependency.optional || enabled.contains(dependency.name.as_str()))
+            .map(|dependency| {
+                let features = dependency_features
+                    .get(dependency.name.as_str())
+                    .cloned()
+                    .unwrap_or_default();
+
+                (dependency.name.clone(), features)
+            })
+            .collect()
+    }
+
+    fn feature(&self, name: &str) -> Option<&Vec<String>> {
+        self.features.get(name).or_else(|| self.features2.get(name))
+    }
+}
+
+/// A dependency of a published version of a crate.
+#[derive(Clone, Debug, Deserialize)]
+pub struct IndexDependency {
+    /// The name of the dependency, or the name under which it was renamed
+    pub name: String,
+    pub req: semver::VersionReq,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// The name of the dependency, if it was renamed
+    #[serde(default)]
+    pub package: Option<String>,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default = "default_features")]
+    pub default_features: bool,
+}
+
+fn default_features() -> bool {
+    true
+}
+
+impl IndexDependency {
+    /// The name of the depended upon crate.
+    pub fn package_name(&self) -> &str {
+        self.package.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Whether the dependency is needed to build the crate, i.e. it's not a dev-dependency.
+    pub fn is_build_dependency(&self) -> bool {
+        self.kind.as_deref() != Some("dev")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cache_file, index_file, CargoIndexCache, IndexEntry, RegistryIndex};
+    use crate::semver::Version;
+    use std::path::PathBuf;
+
+    #[yare::parameterized(
+        one = { "a", "1/a" },
+        two = { "ab", "2/ab" },
+        three = { "abc", "3/a/abc" },
+        four = { "abcd", "ab/cd/abcd" },
+        upper_case = { "Serde", "se/rd/serde" },
+    )]
+    fn index_file_of_crate(name: &str, expected: &str) {
+        assert_eq!(index_file(name), PathBuf::from(expected));
+    }
+
+    #[test]
+    fn read_versions() {
+        let root = std::env::temp_dir().join("cargo-msrv-test-registry-index");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("3").join("f")).unwrap();
+        std::fs::write(
+            root.join("3").join("f").join("foo"),
+            concat!(
+                r#"{"name":"foo","vers":"1.0.0","deps":[],"cksum":"00","features":{},"yanked":false}"#,
+                "\n",
+                r#"{"name":"foo","vers":"1.1.0","deps":[{"name":"bar","req":"^0.2","features":[],"optional":false,"default_features":true,"target":null,"kind":"normal"}],"cksum":"01","features":{},"yanked":true,"rust_version":"1.60"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let index = RegistryIndex::new(&root);
+        let versions = index.versions("foo").unwrap();
+
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].vers, Version::new(1, 0, 0));
+        assert_eq!(versions[0].rust_version(), None);
+        assert!(versions[1].yanked);
+        assert_eq!(versions[1].rust_version(), Some(Version::new(1, 60, 0)));
+        assert_eq!(versions[1].deps[0].package_name(), "bar");
+        assert!(index.versions("unknown").unwrap().is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn read_cached_versions() {
+        let cargo_home = std::env::temp_dir().join("cargo-msrv-test-index-cache");
+        let _ = std::fs::remove_dir_all(&cargo_home);
+        let cache = cargo_home
+            .join("registry")
+            .join("index")
+            .join("index.crates.io-6f17d22bba15001f")
+            .join(".cache");
+        std::fs::create_dir_all(cache.join("3").join("f")).unwrap();
+
+        let contents = cache_file(&[
+            r#"{"name":"foo","vers":"1.0.0","deps":[],"cksum":"00","features":{}}"#,
+            r#"{"name":"foo","vers":"1.1.0","deps":[],"cksum":"01","features":{},"rust_version":"1.60"}"#,
+        ]);
+        std::fs::write(cache.join("3").join("f").join("foo"), contents).unwrap();
+
+        let caches = CargoIndexCache::in_cargo_home(&cargo_home);
+        assert_eq!(caches.len(), 1);
+
+        let versions = caches[0].versions("foo").unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].vers, Version::new(1, 0, 0));
+        assert_eq!(versions[1].rust_version(), Some(Version::new(1, 60, 0)));
+        assert!(caches[0].versions("unknown").unwrap().is_empty());
+
+        std::fs::remove_dir_all(&cargo_home).unwrap();
+    }
+
+    #[yare::parameterized(
+        no_default_features = { &[], false, &[("core", &[])] },
+        default_features = { &[], true, &[("core", &[]), ("log", &["std"])] },
+        dep_syntax = { &["tls"], false, &[("core", &[]), ("rustls", &[])] },
+        implicit_feature = { &["serde"], false, &[("core", &[]), ("serde", &[])] },
+        weak_feature = { &["serde-std"], false, &[("core", &[])] },
+        weak_feature_of_enabled = { &["serde", "serde-std"], false, &[("core", &[]), ("serde", &["std"])] },
+    )]
+    fn enabled_dependencies(
+        features: &[&str],
+        default_features: bool,
+        expected: &[(&str, &[&str])],
+    ) {
+        let entry: IndexEntry = serde_json::from_str(
+            r#"{
+                "name": "foo",
+                "vers": "1.0.0",
+                "deps": [
+                    {"name":"core","req":"^1","optional":false},
+                    {"name":"log","req":"^0.4","optional":true},
+                    {"name":"serde","req":"^1","optional":true},
+                    {"name":"rustls","req":"^0.20","optional":true,"package":"rustls-fork"}
+                ],
+                "cksum": "00",
+                "features": {"default": ["logging"], "logging": ["log/std"]},
+                "features2": {"tls": ["dep:rustls"], "serde-std": ["serde?/std"]}
+            }"#,
+        )
+        .unwrap();
+
+        let features = features.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        let mut enabled = entry
+            .enabled_dependencies(&features, default_features)
+            .into_iter()
+            .collect::<Vec<_>>();
+        enabled.sort();
+
+        let expected = expected
+            .iter()
+            .map(|(name, features)| {
+                (
+                    name.to_string(),
+                    features.iter().map(|f| f.to_string()).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(enabled, expected);
+    }
+}
@@ -1,8 +1,11 @@
 use crate::config::Config;
-use crate::dependencies::DependencyGraph;
+use crate::dependencies::{
+    sort_dependency_kinds, DependencyGraph, DependencyKinds, DEFAULT_DEPENDENCY_KINDS,
+};
 use crate::errors::{CargoMSRVError, TResult};
+use crate::feature_matrix::FeatureMatrixEntry;
 use crate::paths::crate_root_folder;
-use cargo_metadata::MetadataCommand;
+use cargo_metadata::{CargoOpt, DependencyKind, MetadataCommand, PackageId};
 
 pub(crate) trait DependencyResolver {
     fn resolve(&self) -> TResult<DependencyGraph>;
@@ -10,6 +13,7 @@ pub(crate) trait DependencyResolver {
 
 pub(crate) struct CargoMetadataResolver {
     metadata_command: MetadataCommand,
+    dependency_kinds: DependencyKinds,
 }
 
 impl CargoMetadataResolver {
@@ -19,7 +23,58 @@ impl CargoMetadataResolver {
         let mut metadata_command = MetadataCommand::new();
         metadata_command.manifest_path(crate_root.join("Cargo.toml"));
 
-        Ok(Self { metadata_command })
+        Ok(Self {
+            metadata_command,
+            dependency_kinds: DEFAULT_DEPENDENCY_KINDS.to_vec(),
+        })
+    }
+
+    /// Only include the dependencies of the given kinds in the dependency graph. By default, the
+    /// normal and build dependencies are included.
+    pub fn with_dependency_kinds(mut self, kinds: &[DependencyKind]) -> Self {
+        self.dependency_kinds = kinds.to_vec();
+        self
+    }
+
+    /// Only include the dependencies which are active on one of the given platforms (target
+    /// triples), like `cargo metadata --filter-platform`. When no platforms are given, the
+    /// dependencies of all platforms are included.
+    pub fn with_platforms(mut self, platforms: &[&str]) -> Self {
+        self.metadata_command
+            .other_options(filter_platform_options(platforms));
+        self
+    }
+
+    /// Resolve the dependencies for the given feature matrix, instead of the default features.
+    pub fn with_features(mut self, feature_matrix: &[FeatureMatrixEntry]) -> Self {
+        for option in feature_options(feature_matrix) {
+            self.metadata_command.features(option);
+        }
+        self
+    }
+}
+
+fn filter_platform_options(platforms: &[&str]) -> Vec<String> {
+    platforms
+        .iter()
+        .flat_map(|platform| vec!["--filter-platform".to_string(), platform.to_string()])
+        .collect()
+}
+
+/// The feature flags which resolve the dependencies of the features of the feature matrix.
+///
+/// A single feature set is resolved as given. When the matrix consists of several feature sets,
+/// or of each feature by itself, all features are enabled, so the dependencies of every feature
+/// set are included.
+fn feature_options(feature_matrix: &[FeatureMatrixEntry]) -> Vec<CargoOpt> {
+    match feature_matrix {
+        [] | [FeatureMatrixEntry::Default] => Vec::new(),
+        [FeatureMatrixEntry::NoDefault] => vec![CargoOpt::NoDefaultFeatures],
+        [FeatureMatrixEntry::Set(features)] => vec![
+            CargoOpt::NoDefaultFeatures,
+            CargoOpt::SomeFeatures(features.clone()),
+        ],
+        _ => vec![CargoOpt::AllFeatures],
     }
 }
 
@@ -40,6 +95,8 @@ impl DependencyResolver for CargoMetadataResolver {
                 &mut graph,
                 result.packages.into_iter(),
                 dependencies.nodes.into_iter(),
+                &result.workspace_members,
+                &self.dependency_kinds,
             );
 
             Ok(graph)
@@ -49,9 +106,20 @@ impl DependencyResolver for CargoMetadataResolver {
     }
 }
 
-/// Builds a package graph from  1) a set of packages and 2) a given dependency graph.
-fn build_package_graph<Ip, Id>(graph: &mut DependencyGraph, packages: Ip, dependencies: Id)
-where
+/// Builds a package graph from  1) a set of packages and 2) a given dependency graph, where the
+/// 3) workspace members only depend on the dependencies of the 4) given kinds.
+///
+/// The kinds only select the dependencies of the workspace members. The normal and build
+/// dependencies of every other package are always included, since they're compiled regardless
+/// of the kind by which the package itself was depended upon, while their dev-dependencies never
+/// are.
+fn build_package_graph<Ip, Id>(
+    graph: &mut DependencyGraph,
+    packages: Ip,
+    dependencies: Id,
+    members: &[PackageId],
+    dependency_kinds: &[DependencyKind],
+) where
     Ip: IntoIterator<Item = cargo_metadata::Package>,
     Id: IntoIterator<Item = cargo_metadata::Node>,
 {
@@ -63,21 +131,193 @@ where
     }
 
     for dependency in dependencies {
+        let dependency_kinds = if members.contains(&dependency.id) {
+            dependency_kinds
+        } else {
+            DEFAULT_DEPENDENCY_KINDS
+        };
+
         for child in dependency.deps {
-            use cargo_metadata::DependencyKind;
-            // do not include dev-dependencies
-            // you need normal and build dependencies to build crates, but not dev
-            if child
+            // only keep the kinds of dependency we're interested in; e.g. by default we need
+            // normal and build dependencies to build crates, but not dev
+            let mut kinds = child
                 .dep_kinds
                 .iter()
-                .all(|k| k.kind == DependencyKind::Normal || k.kind == DependencyKind::Build)
-            {
+                .map(|k| k.kind)
+                .filter(|kind| dependency_kinds.contains(kind))
+                .collect::<Vec<_>>();
+
+            // cargo versions prior to 1.41 do not report the kinds of dependency
+            if child.dep_kinds.is_empty() && dependency_kinds.contains(&DependencyKind::Normal) {
+                kinds.push(DependencyKind::Normal);
+            }
+
+            if !kinds.is_empty() {
+                sort_dependency_kinds(&mut kinds);
+
                 let child = graph.index[&child.pkg];
                 let ancestor = graph.index[&dependency.id];
 
                 // add link
-                graph.packages.add_edge(ancestor.into(), child.into(), ());
+                graph
+                    .packages
+                    .add_edge(ancestor.into(), child.into(), kinds);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{build_package_graph, feature_options, filter_platform_options};
+    use crate::dependencies::fixtures::package;
+    use crate::dependencies::DependencyGraph;
+    use crate::feature_matrix::FeatureMatrixEntry;
+    use cargo_metadata::{DependencyKind, Node, PackageId};
+
+    fn node(name: &str, deps: &[(&str, &[&str])]) -> Node {
+        let deps = deps
+            .iter()
+            .map(|(dep, kinds)| {
+                serde_json::json!({
+                    "name": dep,
+                    "pkg": dep,
+                    "dep_kinds": kinds
+                        .iter()
+                        .map(|kind| serde_json::json!({ "kind": kind, "target": null }))
+                        .collect::<Vec<_>>(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::from_value(serde_json::json!({
+            "id": name,
+            "deps": deps,
+            "dependencies": [],
+        }))
+        .unwrap()
+    }
+
+    // root -> a (normal), root -> b (dev), root -> c (normal, dev), root -> f (build)
+    // b -> d (normal), b -> e (dev), f -> g (normal)
+    fn graph(kinds: &[DependencyKind]) -> DependencyGraph {
+        let root = PackageId {
+            repr: "root".to_string(),
+        };
+        let mut graph = DependencyGraph::empty(root.clone());
+
+        build_package_graph(
+            &mut graph,
+            ["root", "a", "b", "c", "d", "e", "f", "g"]
+                .iter()
+                .map(|name| package(name, None)),
+            vec![
+                node(
+                    "root",
+                    &[
+                        ("a", &["normal"]),
+                        ("b", &["dev"]),
+                        ("c", &["normal", "dev"]),
+                        ("f", &["build"]),
+                    ],
+                ),
+                node("b", &[("d", &["normal"]), ("e", &["dev"])]),
+                node("f", &[("g", &["normal"])]),
+            ],
+            &[root],
+            kinds,
+        );
+
+        graph
+    }
+
+    fn dependencies(graph: &DependencyGraph) -> Vec<String> {
+        let mut names = graph
+            .dependencies()
+            .map(|(package, _)| package.name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn exclude_dev_dependencies_by_default() {
+        let graph = graph(crate::dependencies::DEFAULT_DEPENDENCY_KINDS);
+
+        assert_eq!(dependencies(&graph), vec!["a", "c", "f", "g"]);
+    }
+
+    #[test]
+    fn include_dev_dependencies() {
+        let graph = graph(&[DependencyKind::Normal, DependencyKind::Development]);
+
+        // e is a dev-dependency of a dependency, which is never compiled
+        assert_eq!(dependencies(&graph), vec!["a", "b", "c", "d"]);
+
+        let reached = graph.reached_through();
+        let kinds = |name: &str| {
+            reached[&PackageId {
+                repr: name.to_string(),
+            }]
+                .clone()
+        };
+
+        assert_eq!(kinds("a"), vec![DependencyKind::Normal]);
+        assert_eq!(kinds("b"), vec![DependencyKind::Development]);
+        assert_eq!(
+            kinds("c"),
+            vec![DependencyKind::Normal, DependencyKind::Development]
+        );
+        // a dependency of a dev-dependency is reached as dev
+        assert_eq!(kinds("d"), vec![DependencyKind::Development]);
+    }
+
+    #[test]
+    fn only_dev_dependencies() {
+        let graph = graph(&[DependencyKind::Development]);
+
+        // d is a normal dependency of the dev-dependency b, so it is compiled along with b
+        assert_eq!(dependencies(&graph), vec!["b", "c", "d"]);
+    }
+
+    #[test]
+    fn only_build_dependencies() {
+        let graph = graph(&[DependencyKind::Build]);
+
+        // g is a normal dependency of the build dependency f
+        assert_eq!(dependencies(&graph), vec!["f", "g"]);
+
+        let reached = graph.reached_through();
+        let g = PackageId {
+            repr: "g".to_string(),
+        };
+        assert_eq!(reached[&g], vec![DependencyKind::Build]);
+    }
+
+    #[test]
+    fn filter_platforms() {
+        assert!(filter_platform_options(&[]).is_empty());
+        assert_eq!(
+            filter_platform_options(&["x86_64-unknown-linux-gnu", "wasm32-unknown-unknown"]),
+            vec![
+                "--filter-platform",
+                "x86_64-unknown-linux-gnu",
+                "--filter-platform",
+                "wasm32-unknown-unknown"
+            ]
+        );
+    }
+
+    #[yare::parameterized(
+        none = { vec![], "[]" },
+        default = { vec![FeatureMatrixEntry::Default], "[]" },
+        no_default = { vec![FeatureMatrixEntry::NoDefault], "[NoDefaultFeatures]" },
+        set = { vec![FeatureMatrixEntry::Set(vec!["a".to_string()])], r#"[NoDefaultFeatures, SomeFeatures(["a"])]"# },
+        all = { vec![FeatureMatrixEntry::All], "[AllFeatures]" },
+        each = { vec![FeatureMatrixEntry::Each], "[AllFeatures]" },
+        several = { vec![FeatureMatrixEntry::Default, FeatureMatrixEntry::NoDefault], "[AllFeatures]" },
+    )]
+    fn features_of_feature_matrix(matrix: Vec<FeatureMatrixEntry>, expected: &str) {
+        assert_eq!(format!("{:?}", feature_options(&matrix)), expected);
+    }
+}
@@ -0,0 +1,696 @@
+use std::path::Path;
+
+use cargo_metadata::Package;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, ContentArrangement, Table};
+use rust_releases::{semver, Release, ReleaseIndex};
+use serde::Serialize;
+use toml_edit::{table, value, Document, Item, TableLike};
+
+use crate::cache::CheckCache;
+use crate::check::{CachedCheck, Check, RecordChecks, RunCheck};
+use crate::compatibility_report::{write_compatibility_report, CheckLog};
+use crate::config::{parse_version, Config, ConfigBuilder, ModeIntent};
+use crate::dependencies::formatter::{format_version, package_msrv};
+use crate::dependencies::DependencyGraph;
+use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
+use crate::formatter::{FormatUserOutput, Human, Json};
+use crate::lockfile::CARGO_LOCK;
+use crate::manifest::DEPENDENCY_TABLES;
+use crate::paths::crate_root_folder;
+use crate::reporter::json::Event;
+use crate::reporter::Output;
+use crate::result::MinimalCompatibility;
+use crate::subcommands::find;
+use crate::toolchain::{OwnedToolchainSpec, ToolchainSpec};
+
+/// The MSRV of a dependency, as found by checking the dependency by itself.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DependencyCompatibility {
+    name: String,
+    version: semver::Version,
+    declared_msrv: Option<semver::Version>,
+    declared_msrv_compatible: Option<bool>,
+    compatibility: MinimalCompatibility,
+}
+
+impl DependencyCompatibility {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> &semver::Version {
+        &self.version
+    }
+
+    /// The MSRV specified by the authors of the dependency, if any.
+    pub fn declared_msrv(&self) -> Option<&semver::Version> {
+        self.declared_msrv.as_ref()
+    }
+
+    /// The MSRV of the dependency. When the declared MSRV is compatible, it is also taken to be
+    /// the real MSRV.
+    ///
+    /// Returns `None` if no compatible toolchain could be found.
+    pub fn msrv(&self) -> Option<&semver::Version> {
+        match &self.compatibility {
+            MinimalCompatibility::CapableToolchain { toolchain } => Some(toolchain.version()),
+            MinimalCompatibility::NoCompatibleToolchains => None,
+        }
+    }
+
+    /// Whether the dependency can be built with the MSRV specified by its authors. Returns `None`
+    /// if no MSRV was specified.
+    pub fn is_declared_msrv_compatible(&self) -> Option<bool> {
+        self.declared_msrv_compatible
+    }
+}
+
+/// The MSRV of each dependency of a crate, as found by checking each dependency by itself.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DependenciesCompatibility {
+    crate_msrv: Option<semver::Version>,
+    dependencies: Vec<DependencyCompatibility>,
+}
+
+impl DependenciesCompatibility {
+    pub fn new(
+        crate_msrv: Option<semver::Version>,
+        dependencies: Vec<DependencyCompatibility>,
+    ) -> Self {
+        Self {
+            crate_msrv,
+            dependencies,
+        }
+    }
+
+    /// The MSRV specified in the Cargo manifest of the crate itself, if any.
+    pub fn crate_msrv(&self) -> Option<&semver::Version> {
+        self.crate_msrv.as_ref()
+    }
+
+    pub fn dependencies(&self) -> &[DependencyCompatibility] {
+        &self.dependencies
+    }
+
+    /// The least version which can build every dependency, which is the greatest MSRV of the
+    /// dependencies.
+    ///
+    /// Returns `None` if there are no dependencies, or if no compatible toolchain could be found
+    /// for at least one of them.
+    pub fn msrv(&self) -> Option<&semver::Version> {
+        self.dependencies
+            .iter()
+            .map(DependencyCompatibility::msrv)
+            .try_fold(None, |max: Option<&semver::Version>, version| {
+                version.map(|version| max.max(Some(version)))
+            })
+            .flatten()
+    }
+
+    /// Whether the MSRV of the dependency is greater than the MSRV of the crate, or whether no
+    /// compatible toolchain could be found for the dependency at all.
+    ///
+    /// Always `false` if the crate does not specify an MSRV.
+    pub fn exceeds_crate_msrv(&self, dependency: &DependencyCompatibility) -> bool {
+        match (self.crate_msrv(), dependency.msrv()) {
+            (Some(crate_msrv), Some(msrv)) => msrv > crate_msrv,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// The dependencies which can not be built with the MSRV of the crate.
+    pub fn exceeding_crate_msrv(&self) -> impl Iterator<Item = &DependencyCompatibility> {
+        self.dependencies
+            .iter()
+            .filter(move |dependency| self.exceeds_crate_msrv(dependency))
+    }
+}
+
+/// Check each dependency in the dependency graph by itself. When a dependency specifies an MSRV,
+/// it is checked with that MSRV; when it does not, or when it can not be built with it, its MSRV
+/// is searched for instead.
+///
+/// Each dependency is checked from a copy of its sources, located in `target/cargo-msrv/dependencies`
+/// of the crate, so the sources in the cargo registry are left untouched. The lockfile of the crate
+/// is copied along, so the dependencies of each dependency resolve to the same versions as they
+/// do for the crate.
+///
+/// The checks of all dependencies are written to a single compatibility report, if one was
+/// requested.
+pub(crate) fn verify_dependencies(
+    config: &Config,
+    output: &impl Output,
+    index: &ReleaseIndex,
+    graph: &DependencyGraph,
+    crate_msrv: Option<semver::Version>,
+) -> TResult<DependenciesCompatibility> {
+    let crate_root = crate_root_folder(config)?;
+    let work_folder = crate_root
+        .join("target")
+        .join("cargo-msrv")
+        .join("dependencies");
+
+    let mut dependencies = Vec::new();
+    let checks = CheckLog::default();
+
+    for (package, _) in graph.dependencies() {
+        info!(
+            dependency = package.name.as_str(),
+            version = %package.version,
+            "verifying msrv of dependency"
+        );
+
+        checks.set_scope(Some(format!("{} {}", package.name, package.version)));
+
+        let folder = work_folder.join(format!("{}-{}", package.name, package.version));
+        copy_package(package, &crate_root, &folder, config.ignore_lockfile())?;
+
+        let dependency_config = ConfigBuilder::from_config(config)
+            .crate_path(Some(&folder))
+            .build();
+
+        let declared_msrv = package_msrv(package);
+
+        let declared_check = declared_msrv
+            .as_ref()
+            .map(|declared| {
+                check_declared_msrv(&dependency_config, output, index, &checks, declared)
+            })
+            .transpose()?;
+        let declared_msrv_compatible = declared_check.as_ref().map(|(compatible, _)| *compatible);
+
+        let compatibility = match declared_check {
+            Some((true, toolchain)) => MinimalCompatibility::CapableToolchain { toolchain },
+            // the msrv of the dependency is greater than its declared msrv
+            Some((false, toolchain)) => search_msrv(
+                &dependency_config,
+                output,
+                index,
+                &checks,
+                toolchain.version(),
+            )?,
+            // the msrv of a dependency is at least the first release which supports its edition
+            None => search_msrv(
+                &dependency_config,
+                output,
+                index,
+                &checks,
+                &parse_version(&package.edition)?,
+            )?,
+        };
+
+        dependencies.push(DependencyCompatibility {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            declared_msrv,
+            declared_msrv_compatible,
+            compatibility,
+        });
+    }
+
+    let compatibility = DependenciesCompatibility::new(crate_msrv, dependencies);
+    write_compatibility_report(config, &checks, compatibility.msrv().cloned())?;
+
+    Ok(compatibility)
+}
+
+/// Check whether the dependency can be built with its declared MSRV, and with which toolchain
+/// it was checked.
+fn check_declared_msrv(
+    config: &Config,
+    output: &impl Output,
+    index: &ReleaseIndex,
+    checks: &CheckLog,
+    declared: &semver::Version,
+) -> TResult<(bool, OwnedToolchainSpec)> {
+    // the declared msrv may be a two component version, e.g. '1.56' for '1.56.1'
+    let version = index
+        .releases()
+        .iter()
+        .map(Release::version)
+        .filter(|version| version.major == declared.major && version.minor == declared.minor)
+        .max()
+        .unwrap_or(declared);
+
+    let toolchain = ToolchainSpec::new(version, config.target());
+    let runner = RunCheck::new(output);
+
+    output.mode(ModeIntent::Verify);
+    let outcome = if config.no_cache() {
+        RecordChecks::new(runner, checks).check(config, &toolchain)?
+    } else {
+        let cache = CheckCache::from_config(config)?;
+        RecordChecks::new(CachedCheck::new(runner, cache, output), checks)
+            .check(config, &toolchain)?
+    };
+
+    Ok((outcome.is_success(), toolchain.to_owned()))
+}
+
+fn search_msrv(
+    config: &Config,
+    output: &impl Output,
+    index: &ReleaseIndex,
+    checks: &CheckLog,
+    minimum_version: &semver::Version,
+) -> TResult<MinimalCompatibility> {
+    let config = ConfigBuilder::from_config(config)
+        .minimum_version(minimum_version.clone())
+        .build();
+
+    find::search_msrv(&config, output, index, checks)
+}
+
+/// Copy the sources of the package to the given folder, along with the lockfile of the crate.
+///
+/// The sources are copied on each run, since the sources of a path dependency may have changed
+/// since the previous run. The `target` folder of the copy is kept, so its build artifacts can be
+/// reused.
+///
+/// Since the copy is located within the folder of the crate, its manifest is detached from the
+/// workspace of the crate, see [`detach_manifest`].
+fn copy_package(
+    package: &Package,
+    crate_root: &Path,
+    destination: &Path,
+    ignore_lockfile: bool,
+) -> TResult<()> {
+    let source = package
+        .manifest_path
+        .parent()
+        .ok_or(CargoMSRVError::NoCrateRootFound)?;
+
+    copy_sources(source.as_std_path(), destination)?;
+    detach_manifest(&destination.join("Cargo.toml"), source.as_std_path())?;
+
+    let lockfile = crate_root.join(CARGO_LOCK);
+    if !ignore_lockfile && lockfile.is_file() {
+        let copy = destination.join(CARGO_LOCK);
+
+        std::fs::copy(&lockfile, &copy).map_err(|error| CargoMSRVError::Io {
+            error,
+            source: IoErrorSource::WriteFile(copy),
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Make the copied manifest of a package independent of the location it was copied from.
+///
+/// Cargo looks for the workspace of a package in the folders above it, so the copy would be taken
+/// for a member of the workspace of the crate, which doesn't list it. An empty `[workspace]`
+/// table makes the copy the root of its own workspace instead. Relative paths of path
+/// dependencies are resolved against the original folder of the package, so they still point
+/// to the same packages.
+fn detach_manifest(manifest_path: &Path, source: &Path) -> TResult<()> {
+    let contents = std::fs::read_to_string(manifest_path).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFile(manifest_path.to_path_buf()),
+    })?;
+
+    let mut manifest = contents.parse::<Document>()?;
+
+    if !manifest.contains_key("workspace") {
+        manifest["workspace"] = table();
+    }
+
+    absolute_path_dependencies(manifest.as_table_mut(), source);
+
+    if let Some(targets) = manifest.get_mut("target").and_then(Item::as_table_like_mut) {
+        for (_, target) in targets.iter_mut() {
+            if let Some(target) = target.as_table_like_mut() {
+                absolute_path_dependencies(target, source);
+            }
+        }
+    }
+
+    std::fs::write(manifest_path, manifest.to_string()).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::WriteFile(manifest_path.to_path_buf()),
+    })
+}
+
+fn absolute_path_dependencies(table: &mut dyn TableLike, source: &Path) {
+    for name in DEPENDENCY_TABLES {
+        let dependencies = match table.get_mut(name).and_then(Item::as_table_like_mut) {
+            Some(dependencies) => dependencies,
+            None => continue,
+        };
+
+        for (_, dependency) in dependencies.iter_mut() {
+            let dependency = match dependency.as_table_like_mut() {
+                Some(dependency) => dependency,
+                None => continue,
+            };
+
+            let path = dependency
+                .get("path")
+                .and_then(Item::as_str)
+                .map(Path::new)
+                .filter(|path| path.is_relative())
+                .map(|path| source.join(path));
+
+            if let Some(path) = path {
+                dependency.insert("path", value(path.display().to_string()));
+            }
+        }
+    }
+}
+
+/// Replace the sources in the destination folder with those of the source folder, except for the
+/// `target` folder.
+fn copy_sources(source: &Path, destination: &Path) -> TResult<()> {
+    if destination.is_dir() {
+        remove_sources(destination)?;
+    }
+
+    copy_folder(source, destination)
+}
+
+fn remove_sources(folder: &Path) -> TResult<()> {
+    let entries = std::fs::read_dir(folder).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFolder(folder.to_path_buf()),
+    })?;
+
+    for entry in entries {
+        let entry = entry.map_err(|error| CargoMSRVError::Io {
+            error,
+            source: IoErrorSource::ReadFolder(folder.to_path_buf()),
+        })?;
+
+        let path = entry.path();
+
+        if path.is_dir() {
+            if entry.file_name() != "target" {
+                std::fs::remove_dir_all(&path).map_err(|error| CargoMSRVError::Io {
+                    error,
+                    source: IoErrorSource::RemoveFolder(path.clone()),
+                })?;
+            }
+        } else {
+            std::fs::remove_file(&path).map_err(|error| CargoMSRVError::Io {
+                error,
+                source: IoErrorSource::RemoveFile(path.clone()),
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+fn copy_folder(source: &Path, destination: &Path) -> TResult<()> {
+    std::fs::create_dir_all(destination).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::CreateFolder(destination.to_path_buf()),
+    })?;
+
+    let entries = std::fs::read_dir(source).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFolder(source.to_path_buf()),
+    })?;
+
+    for entry in entries {
+        let entry = entry.map_err(|error| CargoMSRVError::Io {
+            error,
+            source: IoErrorSource::ReadFolder(source.to_path_buf()),
+        })?;
+
+        let path = entry.path();
+        let target = destination.join(entry.file_name());
+
+        if path.is_dir() {
+            // skip build artifacts of path dependencies
+            if entry.file_name() != "target" {
+                copy_folder(&path, &target)?;
+            }
+        } else {
+            std::fs::copy(&path, &target).map_err(|error| CargoMSRVError::Io {
+                error,
+                source: IoErrorSource::WriteFile(target),
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+fn format_exceeds(exceeds: bool) -> &'static str {
+    if exceeds {
+        "exceeds crate MSRV"
+    } else {
+        ""
+    }
+}
+
+impl FormatUserOutput<Human> for DependenciesCompatibility {
+    fn format_line(&self) -> String {
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec!["Dependency", "Version", "Declared MSRV", "MSRV", ""]);
+
+        for dependency in &self.dependencies {
+            table.add_row(vec![
+                Cell::new(dependency.name()),
+                Cell::new(dependency.version()),
+                Cell::new(format_version(dependency.declared_msrv())),
+                Cell::new(format_version(dependency.msrv())),
+                Cell::new(format_exceeds(self.exceeds_crate_msrv(dependency))),
+            ]);
+        }
+
+        let summary = match (self.crate_msrv(), self.exceeding_crate_msrv().count()) {
+            (None, _) => "The crate does not specify an MSRV".to_string(),
+            (Some(msrv), 0) => format!("All dependencies are compatible with MSRV {}", msrv),
+            (Some(msrv), n) => format!("{} dependencies are not compatible with MSRV {}", n, msrv),
+        };
+
+        format!("{}\n{}", table, summary)
+    }
+}
+
+#[derive(Serialize)]
+struct VerifiedDependenciesEvent<'a> {
+    variant: &'a str,
+    success: bool,
+    crate_msrv: Option<String>,
+    list: Vec<VerifiedDependencyFields<'a>>,
+}
+
+#[derive(Serialize)]
+struct VerifiedDependencyFields<'a> {
+    dependency: &'a str,
+    version: String,
+    declared_msrv: Option<String>,
+    declared_msrv_compatible: Option<bool>,
+    msrv: Option<String>,
+    exceeds_crate_msrv: bool,
+}
+
+impl FormatUserOutput<Json> for DependenciesCompatibility {
+    fn format_line(&self) -> String {
+        let list = self
+            .dependencies
+            .iter()
+            .map(|dependency| VerifiedDependencyFields {
+                dependency: dependency.name(),
+                version: dependency.version().to_string(),
+                declared_msrv: dependency.declared_msrv().map(ToString::to_string),
+                declared_msrv_compatible: dependency.is_declared_msrv_compatible(),
+                msrv: dependency.msrv().map(ToString::to_string),
+                exceeds_crate_msrv: self.exceeds_crate_msrv(dependency),
+            })
+            .collect();
+
+        let event = VerifiedDependenciesEvent {
+            variant: "verify",
+            success: self.exceeding_crate_msrv().next().is_none(),
+            crate_msrv: self.crate_msrv().map(ToString::to_string),
+            list,
+        };
+
+        Event::new("list", event).to_line()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{copy_package, copy_sources, DependenciesCompatibility, DependencyCompatibility};
+    use crate::formatter::{FormatUserOutput, Json};
+    use crate::reporter::json::schema::assert_conforms;
+    use crate::result::MinimalCompatibility;
+    use crate::semver::Version;
+    use crate::toolchain::OwnedToolchainSpec;
+    use cargo_metadata::MetadataCommand;
+    use std::path::PathBuf;
+
+    fn dependency(declared: Option<Version>, msrv: Option<Version>) -> DependencyCompatibility {
+        DependencyCompatibility {
+            name: "dependency".to_string(),
+            version: Version::new(1, 0, 0),
+            declared_msrv_compatible: declared
+                .as_ref()
+                .map(|declared| msrv.as_ref() == Some(declared)),
+            declared_msrv: declared,
+            compatibility: match msrv {
+                Some(version) => MinimalCompatibility::CapableToolchain {
+                    toolchain: OwnedToolchainSpec::new(&version, "x86_64-unknown-linux-gnu"),
+                },
+                None => MinimalCompatibility::NoCompatibleToolchains,
+            },
+        }
+    }
+
+    #[test]
+    fn declared_msrv_compatibility() {
+        let compatible = dependency(Some(Version::new(1, 40, 0)), Some(Version::new(1, 40, 0)));
+        let incompatible = dependency(Some(Version::new(1, 40, 0)), Some(Version::new(1, 45, 0)));
+        let undeclared = dependency(None, Some(Version::new(1, 45, 0)));
+
+        assert_eq!(compatible.is_declared_msrv_compatible(), Some(true));
+        assert_eq!(incompatible.is_declared_msrv_compatible(), Some(false));
+        assert_eq!(undeclared.is_declared_msrv_compatible(), None);
+    }
+
+    #[yare::parameterized(
+        lower = { Some(Version::new(1, 38, 0)), false },
+        equal = { Some(Version::new(1, 40, 0)), false },
+        greater = { Some(Version::new(1, 45, 0)), true },
+        no_compatible_toolchain = { None, true },
+    )]
+    fn exceeds_crate_msrv(msrv: Option<Version>, expected: bool) {
+        let compatibility = DependenciesCompatibility::new(
+            Some(Version::new(1, 40, 0)),
+            vec![dependency(None, msrv)],
+        );
+
+        let dependency = &compatibility.dependencies()[0];
+        assert_eq!(compatibility.exceeds_crate_msrv(dependency), expected);
+        assert_eq!(
+            compatibility.exceeding_crate_msrv().count(),
+            expected as usize
+        );
+    }
+
+    #[test]
+    fn without_crate_msrv_nothing_exceeds() {
+        let compatibility = DependenciesCompatibility::new(
+            None,
+            vec![dependency(None, Some(Version::new(1, 58, 0)))],
+        );
+
+        assert_eq!(compatibility.exceeding_crate_msrv().count(), 0);
+    }
+
+    #[test]
+    fn msrv_is_greatest_msrv_of_dependencies() {
+        let compatibility = DependenciesCompatibility::new(
+            None,
+            vec![
+                dependency(None, Some(Version::new(1, 58, 0))),
+                dependency(None, Some(Version::new(1, 40, 0))),
+            ],
+        );
+
+        assert_eq!(compatibility.msrv(), Some(&Version::new(1, 58, 0)));
+
+        let compatibility = DependenciesCompatibility::new(
+            None,
+            vec![
+                dependency(None, Some(Version::new(1, 58, 0))),
+                dependency(None, None),
+            ],
+        );
+
+        assert_eq!(compatibility.msrv(), None);
+    }
+
+    #[test]
+    fn json_event() {
+        let compatibility = DependenciesCompatibility::new(
+            Some(Version::new(1, 40, 0)),
+            vec![
+                dependency(Some(Version::new(1, 40, 0)), Some(Version::new(1, 40, 0))),
+                dependency(None, None),
+            ],
+        );
+
+        let event = assert_conforms(&FormatUserOutput::<Json>::format_line(&compatibility));
+
+        assert_eq!(event["success"], false);
+        assert_eq!(event["list"][0]["declared_msrv_compatible"], true);
+        assert!(event["list"][1]["declared_msrv_compatible"].is_null());
+        assert!(event["list"][1]["msrv"].is_null());
+    }
+
+    #[test]
+    fn copy_of_sources_is_refreshed() {
+        let root = std::env::temp_dir().join("cargo-msrv-test-verify-copy");
+        let _ = std::fs::remove_dir_all(&root);
+
+        let source = root.join("source");
+        let destination = root.join("destination");
+        std::fs::create_dir_all(source.join("src")).unwrap();
+        std::fs::write(source.join("src").join("lib.rs"), "// first").unwrap();
+        std::fs::write(source.join("src").join("removed.rs"), "").unwrap();
+
+        copy_sources(&source, &destination).unwrap();
+
+        std::fs::create_dir_all(destination.join("target")).unwrap();
+        std::fs::write(destination.join("target").join("artifact"), "").unwrap();
+        std::fs::write(source.join("src").join("lib.rs"), "// second").unwrap();
+        std::fs::remove_file(source.join("src").join("removed.rs")).unwrap();
+
+        copy_sources(&source, &destination).unwrap();
+
+        let lib = std::fs::read_to_string(destination.join("src").join("lib.rs")).unwrap();
+        assert_eq!(lib, "// second");
+        assert!(!destination.join("src").join("removed.rs").exists());
+        // build artifacts are kept
+        assert!(destination.join("target").join("artifact").is_file());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn copy_of_workspace_member_is_detached_from_workspace() {
+        let workspace = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("workspace-path-dependencies");
+        let offline = vec!["--offline".to_string()];
+
+        let metadata = MetadataCommand::new()
+            .manifest_path(workspace.join("app").join("Cargo.toml"))
+            .other_options(offline.clone())
+            .exec()
+            .unwrap();
+        let dep = metadata.packages.iter().find(|p| p.name == "dep").unwrap();
+
+        let copy = workspace
+            .join("target")
+            .join("cargo-msrv")
+            .join("dependencies")
+            .join("dep-0.1.0");
+        copy_package(dep, &workspace, &copy, true).unwrap();
+
+        // the copy is the root of its own workspace, and its path dependency still resolves
+        let metadata = MetadataCommand::new()
+            .manifest_path(copy.join("Cargo.toml"))
+            .other_options(offline)
+            .exec()
+            .unwrap();
+        assert_eq!(metadata.workspace_root.as_std_path(), copy);
+
+        let util = metadata.packages.iter().find(|p| p.name == "util").unwrap();
+        assert_eq!(
+            util.manifest_path.as_std_path(),
+            workspace.join("util").join("Cargo.toml")
+        );
+    }
+}
@@ -0,0 +1,593 @@
+use std::fmt;
+use std::sync::Mutex;
+
+use json::JsonValue;
+use rust_releases::semver;
+use serde::Serialize;
+
+use crate::formatter::{FormatUserOutput, Human, Json};
+use crate::outcome::FailureOutcome;
+use crate::reporter::json::Event;
+
+/// The failed checks of a search, from which the failure of the toolchain just below the MSRV
+/// can be taken afterwards. Failures may be recorded by concurrent checks.
+#[derive(Debug, Default)]
+pub struct FailureLog {
+    failures: Mutex<Vec<FailureOutcome>>,
+}
+
+impl FailureLog {
+    pub fn record(&self, failure: &FailureOutcome) {
+        self.failures.lock().unwrap().push(failure.clone());
+    }
+
+    /// The failure of the most recent toolchain which is older than the MSRV.
+    pub fn failure_below(&self, msrv: &semver::Version) -> Option<FailureOutcome> {
+        self.failures
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|failure| failure.toolchain_spec.version() < msrv)
+            .max_by(|lhs, rhs| {
+                lhs.toolchain_spec
+                    .version()
+                    .cmp(rhs.toolchain_spec.version())
+            })
+            .cloned()
+    }
+}
+
+/// A diagnostic reported by the compiler, as collected from the `compiler-message` records of
+/// cargo's `--message-format=json` output.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct CompilerDiagnostic {
+    /// The severity, e.g. `error` or `warning`.
+    pub level: String,
+    /// The rustc error code, e.g. `E0658`.
+    pub code: Option<String>,
+    pub message: String,
+    /// The diagnostic as rendered by rustc for human consumption.
+    pub rendered: Option<String>,
+    pub spans: Vec<DiagnosticSpan>,
+    /// The messages of the notes and help messages attached to the diagnostic.
+    pub children: Vec<String>,
+}
+
+impl CompilerDiagnostic {
+    /// Parse the `message` of a `compiler-message` record.
+    fn from_compiler_message(message: &JsonValue) -> Option<Self> {
+        Some(Self {
+            level: message["level"].as_str()?.to_string(),
+            code: message["code"]["code"].as_str().map(String::from),
+            message: message["message"].as_str()?.to_string(),
+            rendered: message["rendered"].as_str().map(String::from),
+            spans: message["spans"]
+                .members()
+                .filter_map(DiagnosticSpan::from_json)
+                .collect(),
+            children: message["children"]
+                .members()
+                .filter_map(|child| child["message"].as_str())
+                .map(String::from)
+                .collect(),
+        })
+    }
+
+    /// Parse a diagnostic which was serialized with [`CompilerDiagnostic::to_json`].
+    pub fn from_json(value: &JsonValue) -> Option<Self> {
+        Some(Self {
+            level: value["level"].as_str()?.to_string(),
+            code: value["code"].as_str().map(String::from),
+            message: value["message"].as_str()?.to_string(),
+            rendered: value["rendered"].as_str().map(String::from),
+            spans: value["spans"]
+                .members()
+                .filter_map(DiagnosticSpan::from_json)
+                .collect(),
+            children: value["children"]
+                .members()
+                .filter_map(JsonValue::as_str)
+                .map(String::from)
+                .collect(),
+        })
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        json::object! {
+            level: self.level.as_str(),
+            code: self.code.as_deref(),
+            message: self.message.as_str(),
+            rendered: self.rendered.as_deref(),
+            spans: self.spans.iter().map(DiagnosticSpan::to_json).collect::<Vec<_>>(),
+            children: self.children.as_slice(),
+        }
+    }
+
+    /// The span which the diagnostic is primarily about.
+    pub fn primary_span(&self) -> Option<&DiagnosticSpan> {
+        self.spans.iter().find(|span| span.is_primary)
+    }
+}
+
+/// A region of source code to which a [`CompilerDiagnostic`] refers.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct DiagnosticSpan {
+    pub file_name: String,
+    pub line_start: u32,
+    pub line_end: u32,
+    pub column_start: u32,
+    pub column_end: u32,
+    pub is_primary: bool,
+    pub label: Option<String>,
+}
+
+impl DiagnosticSpan {
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        Some(Self {
+            file_name: value["file_name"].as_str()?.to_string(),
+            line_start: value["line_start"].as_u32()?,
+            line_end: value["line_end"].as_u32()?,
+            column_start: value["column_start"].as_u32()?,
+            column_end: value["column_end"].as_u32()?,
+            is_primary: value["is_primary"].as_bool().unwrap_or_default(),
+            label: value["label"].as_str().map(String::from),
+        })
+    }
+
+    fn to_json(&self) -> JsonValue {
+        json::object! {
+            file_name: self.file_name.as_str(),
+            line_start: self.line_start,
+            line_end: self.line_end,
+            column_start: self.column_start,
+            column_end: self.column_end,
+            is_primary: self.is_primary,
+            label: self.label.as_deref(),
+        }
+    }
+}
+
+/// Collect the errors from cargo's `--message-format=json` output, which consists of one JSON
+/// record per line.
+///
+/// Only errors are collected, since warnings do not cause a check to fail. Summary errors, like
+/// "aborting due to previous error", are skipped.
+pub fn parse_compiler_messages(stdout: &str) -> Vec<CompilerDiagnostic> {
+    stdout
+        .lines()
+        .filter_map(|line| json::parse(line).ok())
+        .filter(|record| record["reason"] == "compiler-message")
+        .filter_map(|record| CompilerDiagnostic::from_compiler_message(&record["message"]))
+        .filter(|diagnostic| diagnostic.level == "error" && !is_summary_error(&diagnostic.message))
+        .collect()
+}
+
+/// A location in the source code of the crate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourceLocation {
+    pub path: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.path, self.line)
+    }
+}
+
+/// A compiler error which explains why a toolchain is not compatible.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cause {
+    /// The rustc error code, e.g. `E0658`.
+    pub code: Option<String>,
+    /// The error message of the compiler.
+    pub message: String,
+    /// The unstable feature which is required, e.g. `let_else`, if it could be determined.
+    pub feature: Option<String>,
+    /// Where the error was reported, if it was reported for a location in the source code.
+    pub location: Option<SourceLocation>,
+}
+
+impl Cause {
+    fn from_diagnostic(diagnostic: &CompilerDiagnostic) -> Self {
+        let feature = diagnostic
+            .children
+            .iter()
+            .find_map(|child| feature_from_help(child))
+            .or_else(|| feature_from_message(&diagnostic.message));
+
+        let location = diagnostic.primary_span().map(|span| SourceLocation {
+            path: span.file_name.clone(),
+            line: span.line_start,
+            column: span.column_start,
+        });
+
+        Self {
+            code: diagnostic.code.clone(),
+            message: diagnostic.message.clone(),
+            feature,
+            location,
+        }
+    }
+
+    /// A short description, preferring the name of the required feature over the full
+    /// error message.
+    pub fn summary(&self) -> String {
+        let what = self.feature.as_deref().unwrap_or(&self.message);
+
+        match &self.location {
+            Some(location) => format!("{} at {}", what, location),
+            None => what.to_string(),
+        }
+    }
+}
+
+/// Why the toolchain just below the MSRV failed its check.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnosis {
+    msrv: semver::Version,
+    failed: semver::Version,
+    causes: Vec<Cause>,
+}
+
+impl Diagnosis {
+    /// Diagnose a failure from its structured diagnostics, if these were collected, and from the
+    /// output of the check command otherwise.
+    pub fn new(msrv: semver::Version, failure: &FailureOutcome) -> Self {
+        let causes = if failure.diagnostics.is_empty() {
+            parse_causes(&failure.error_message)
+        } else {
+            let mut causes: Vec<Cause> = Vec::new();
+
+            for cause in failure.diagnostics.iter().map(Cause::from_diagnostic) {
+                if !causes.contains(&cause) {
+                    causes.push(cause);
+                }
+            }
+
+            causes
+        };
+
+        Self {
+            msrv,
+            failed: failure.toolchain_spec.version().clone(),
+            causes,
+        }
+    }
+
+    pub fn causes(&self) -> &[Cause] {
+        &self.causes
+    }
+}
+
+impl FormatUserOutput<Human> for Diagnosis {
+    fn format_line(&self) -> String {
+        let causes = self
+            .causes
+            .iter()
+            .map(Cause::summary)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("MSRV is {} because of: {}", self.msrv, causes)
+    }
+}
+
+#[derive(Serialize)]
+struct DiagnosisEvent<'a> {
+    msrv: String,
+    failed_version: String,
+    causes: Vec<CauseFields<'a>>,
+}
+
+#[derive(Serialize)]
+struct CauseFields<'a> {
+    code: Option<&'a str>,
+    message: &'a str,
+    feature: Option<&'a str>,
+    file: Option<&'a str>,
+    line: Option<u32>,
+    column: Option<u32>,
+}
+
+impl FormatUserOutput<Json> for Diagnosis {
+    fn format_line(&self) -> String {
+        let causes = self
+            .causes
+            .iter()
+            .map(|cause| CauseFields {
+                code: cause.code.as_deref(),
+                message: &cause.message,
+                feature: cause.feature.as_deref(),
+                file: cause
+                    .location
+                    .as_ref()
+                    .map(|location| location.path.as_str()),
+                line: cause.location.as_ref().map(|location| location.line),
+                column: cause.location.as_ref().map(|location| location.column),
+            })
+            .collect();
+
+        let event = DiagnosisEvent {
+            msrv: self.msrv.to_string(),
+            failed_version: self.failed.to_string(),
+            causes,
+        };
+
+        Event::new("msrv-diagnosis", event).to_line()
+    }
+}
+
+/// Parse the errors in the (human readable) output of rustc and cargo.
+///
+/// Summary errors, like "aborting due to previous error" and "could not compile", are skipped.
+/// Identical causes are only included once.
+pub fn parse_causes(stderr: &str) -> Vec<Cause> {
+    let mut causes: Vec<Cause> = Vec::new();
+    let mut current: Option<Cause> = None;
+
+    let mut push = |cause: Cause| {
+        if !causes.contains(&cause) {
+            causes.push(cause);
+        }
+    };
+
+    for line in stderr.lines() {
+        let trimmed = line.trim();
+
+        if let Some((code, message)) = parse_error_line(line) {
+            if let Some(cause) = current.take() {
+                push(cause);
+            }
+
+            if is_summary_error(message) {
+                continue;
+            }
+
+            current = Some(Cause {
+                code,
+                message: message.to_string(),
+                feature: feature_from_message(message),
+                location: None,
+            });
+        } else if let Some(cause) = current.as_mut() {
+            if let Some(location) = trimmed.strip_prefix("--> ") {
+                if cause.location.is_none() {
+                    cause.location = parse_location(location);
+                }
+            } else if let Some(feature) = feature_from_help(trimmed) {
+                cause.feature = Some(feature);
+            }
+        }
+    }
+
+    if let Some(cause) = current {
+        push(cause);
+    }
+
+    causes
+}
+
+/// Parses `error[E0658]: message` and `error: message`.
+fn parse_error_line(line: &str) -> Option<(Option<String>, &str)> {
+    let rest = line.strip_prefix("error")?;
+
+    if let Some(message) = rest.strip_prefix(": ") {
+        return Some((None, message.trim()));
+    }
+
+    let rest = rest.strip_prefix('[')?;
+    let end = rest.find("]: ")?;
+
+    Some((Some(rest[..end].to_string()), rest[end + 3..].trim()))
+}
+
+fn is_summary_error(message: &str) -> bool {
+    message.starts_with("aborting due to")
+        || message.starts_with("could not compile")
+        || message.starts_with("Could not compile")
+        || message.starts_with("build failed")
+}
+
+/// Parses `src/foo.rs:12:5`.
+fn parse_location(location: &str) -> Option<SourceLocation> {
+    let mut parts = location.trim().rsplitn(3, ':');
+
+    let column = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    let path = parts.next()?.to_string();
+
+    Some(SourceLocation { path, line, column })
+}
+
+/// Parses the help message which rustc gives for unstable features, e.g.
+/// ``= help: add `#![feature(let_else)]` to the crate attributes to enable``.
+fn feature_from_help(line: &str) -> Option<String> {
+    let start = line.find("#![feature(")? + "#![feature(".len();
+    let end = line[start..].find(")]")?;
+
+    Some(line[start..start + end].to_string())
+}
+
+/// Finds the unstable feature in messages like:
+/// * ``use of unstable library feature 'int_abs_diff'``
+/// * ``feature `edition2021` is required``
+/// * ``edition 2021 is unstable and only available with -Z unstable-options``
+fn feature_from_message(message: &str) -> Option<String> {
+    if let Some(rest) = message.strip_prefix("use of unstable library feature ") {
+        return quoted(rest);
+    }
+
+    if let Some(rest) = message.strip_prefix("feature ") {
+        if rest.contains("is required") {
+            return quoted(rest);
+        }
+    }
+
+    if let Some(rest) = message.strip_prefix("edition ") {
+        if rest.contains("is unstable") {
+            let edition = rest.split_ascii_whitespace().next()?;
+            return Some(format!("edition{}", edition));
+        }
+    }
+
+    None
+}
+
+/// The text between the first pair of quotes (`'` or `` ` ``).
+fn quoted(text: &str) -> Option<String> {
+    let start = text.find(|c| c == '\'' || c == '`')?;
+    let quote = text[start..].chars().next()?;
+    let rest = &text[start + 1..];
+    let end = rest.find(quote)?;
+
+    Some(rest[..end].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_causes, parse_compiler_messages, Cause, CompilerDiagnostic, Diagnosis, FailureLog,
+        SourceLocation,
+    };
+    use crate::formatter::{FormatUserOutput, Human};
+    use crate::outcome::FailureOutcome;
+    use crate::semver::Version;
+    use crate::toolchain::OwnedToolchainSpec;
+
+    const LET_ELSE: &str = r#"    Checking example v0.1.0 (/tmp/example)
+error[E0658]: `let...else` statements are unstable
+  --> src/foo.rs:12:5
+   |
+12 |     let Some(x) = y else { return };
+   |     ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+   |
+   = note: see issue #87335 <https://github.com/rust-lang/rust/issues/87335> for more information
+   = help: add `#![feature(let_else)]` to the crate attributes to enable
+
+error: aborting due to previous error
+
+For more information about this error, try `rustc --explain E0658`.
+error: could not compile `example`
+"#;
+
+    fn failure(version: Version, error_message: &str) -> FailureOutcome {
+        FailureOutcome {
+            toolchain_spec: OwnedToolchainSpec::new(&version, "x86_64-unknown-linux-gnu"),
+            error_message: error_message.to_string(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parse_feature_gate_error() {
+        let causes = parse_causes(LET_ELSE);
+
+        assert_eq!(
+            causes,
+            vec![Cause {
+                code: Some("E0658".to_string()),
+                message: "`let...else` statements are unstable".to_string(),
+                feature: Some("let_else".to_string()),
+                location: Some(SourceLocation {
+                    path: "src/foo.rs".to_string(),
+                    line: 12,
+                    column: 5,
+                }),
+            }]
+        );
+    }
+
+    #[yare::parameterized(
+        library_feature = { "error[E0658]: use of unstable library feature 'int_abs_diff'", "int_abs_diff" },
+        library_feature_backticks = { "error[E0658]: use of unstable library feature `int_abs_diff`", "int_abs_diff" },
+        cargo_feature = { "error: feature `edition2021` is required", "edition2021" },
+        edition = { "error: edition 2021 is unstable and only available with -Z unstable-options.", "edition2021" },
+    )]
+    fn parse_feature_from_message(stderr: &str, expected: &str) {
+        let causes = parse_causes(stderr);
+
+        assert_eq!(causes.len(), 1);
+        assert_eq!(causes[0].feature.as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn parse_without_errors() {
+        assert!(parse_causes("error: aborting due to previous error\n").is_empty());
+    }
+
+    #[test]
+    fn failure_just_below_msrv() {
+        let log = FailureLog::default();
+        log.record(&failure(Version::new(1, 40, 0), ""));
+        log.record(&failure(Version::new(1, 55, 0), LET_ELSE));
+        log.record(&failure(Version::new(1, 50, 0), ""));
+
+        let failure = log.failure_below(&Version::new(1, 56, 0)).unwrap();
+        assert_eq!(failure.toolchain_spec.version(), &Version::new(1, 55, 0));
+
+        assert!(log.failure_below(&Version::new(1, 40, 0)).is_none());
+    }
+
+    #[test]
+    fn human_summary() {
+        let diagnosis = Diagnosis::new(
+            Version::new(1, 65, 0),
+            &failure(Version::new(1, 64, 0), LET_ELSE),
+        );
+
+        assert_eq!(
+            FormatUserOutput::<Human>::format_line(&diagnosis),
+            "MSRV is 1.65.0 because of: let_else at src/foo.rs:12"
+        );
+    }
+
+    const LET_ELSE_JSON: &str = r#"{"reason":"compiler-artifact","package_id":"libc 0.2.119 (registry+https://github.com/rust-lang/crates.io-index)","fresh":true}
+{"reason":"compiler-message","package_id":"example 0.1.0 (path+file:///tmp/example)","message":{"rendered":"error[E0658]: `let...else` statements are unstable\n","children":[{"children":[],"code":null,"level":"note","message":"see issue #87335 <https://github.com/rust-lang/rust/issues/87335> for more information","rendered":null,"spans":[]},{"children":[],"code":null,"level":"help","message":"add `#![feature(let_else)]` to the crate attributes to enable","rendered":null,"spans":[]}],"code":{"code":"E0658","explanation":"An unstable feature was used.\n"},"level":"error","message":"`let...else` statements are unstable","spans":[{"byte_end":310,"byte_start":278,"column_end":37,"column_start":5,"expansion":null,"file_name":"src/foo.rs","is_primary":true,"label":null,"line_end":12,"line_start":12,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}
+{"reason":"compiler-message","package_id":"example 0.1.0 (path+file:///tmp/example)","message":{"rendered":"warning: unused variable: `x`\n","children":[],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `x`","spans":[]}}
+{"reason":"compiler-message","package_id":"example 0.1.0 (path+file:///tmp/example)","message":{"rendered":"error: aborting due to previous error\n","children":[],"code":null,"level":"error","message":"aborting due to previous error","spans":[]}}
+{"reason":"build-finished","success":false}
+"#;
+
+    #[test]
+    fn parse_compiler_message_records() {
+        let diagnostics = parse_compiler_messages(LET_ELSE_JSON);
+
+        assert_eq!(diagnostics.len(), 1);
+
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.code.as_deref(), Some("E0658"));
+        assert_eq!(diagnostic.message, "`let...else` statements are unstable");
+        assert_eq!(diagnostic.children.len(), 2);
+
+        let span = diagnostic.primary_span().unwrap();
+        assert_eq!(span.file_name, "src/foo.rs");
+        assert_eq!((span.line_start, span.column_start), (12, 5));
+        assert_eq!((span.line_end, span.column_end), (12, 37));
+    }
+
+    #[test]
+    fn diagnostic_json_round_trip() {
+        let diagnostics = parse_compiler_messages(LET_ELSE_JSON);
+        let json = diagnostics[0].to_json();
+
+        assert_eq!(
+            CompilerDiagnostic::from_json(&json).as_ref(),
+            Some(&diagnostics[0])
+        );
+    }
+
+    #[test]
+    fn diagnose_from_compiler_messages() {
+        let mut failure = failure(Version::new(1, 64, 0), "");
+        failure.diagnostics = parse_compiler_messages(LET_ELSE_JSON);
+
+        let diagnosis = Diagnosis::new(Version::new(1, 65, 0), &failure);
+
+        assert_eq!(
+            FormatUserOutput::<Human>::format_line(&diagnosis),
+            "MSRV is 1.65.0 because of: let_else at src/foo.rs:12"
+        );
+    }
+}
@@ -1,9 +1,14 @@
+use std::ffi::OsString;
+
 use crate::command::RustupCommand;
 use crate::toolchain::ToolchainSpec;
 use crate::{CargoMSRVError, Output, ProgressAction, TResult};
 
 pub trait DownloadToolchain {
     fn download(&self, toolchain: &ToolchainSpec) -> TResult<()>;
+
+    /// Add the `rust-std` component of the given target to an installed toolchain.
+    fn add_target(&self, toolchain: &ToolchainSpec, target: &str) -> TResult<()>;
 }
 
 #[derive(Debug)]
@@ -48,4 +53,34 @@ impl<'reporter, R: Output> DownloadToolchain for ToolchainDownloader<'reporter,
 
         Ok(())
     }
+
+    #[instrument(skip(toolchain))]
+    fn add_target(&self, toolchain: &ToolchainSpec, target: &str) -> TResult<()> {
+        info!(toolchain = toolchain.spec(), target, "adding target");
+
+        let rustup = RustupCommand::new()
+            .with_stdout()
+            .with_stderr()
+            .with_args(["add", "--toolchain", toolchain.spec(), target])
+            .execute(OsString::from("target"))?;
+
+        let status = rustup.exit_status();
+
+        if !status.success() {
+            error!(
+                toolchain = toolchain.spec(),
+                target,
+                stdout = rustup.stdout(),
+                stderr = rustup.stderr(),
+                "rustup failed to add target"
+            );
+
+            return Err(CargoMSRVError::RustupAddTargetFailed {
+                target: target.to_string(),
+                toolchain: toolchain.spec().to_string(),
+            });
+        }
+
+        Ok(())
+    }
 }
@@ -6,7 +6,7 @@ use std::string::FromUtf8Error;
 
 use crate::fetch::ToolchainSpecifier;
 use crate::manifest::bare_version::NoVersionMatchesManifestMsrvError;
-use crate::subcommands::verify;
+use crate::subcommands::{bump, verify};
 
 pub type TResult<T> = Result<T, CargoMSRVError>;
 
@@ -45,6 +45,9 @@ pub enum CargoMSRVError {
     #[error("No crate root found for given crate")]
     NoCrateRootFound,
 
+    #[error(transparent)]
+    MsrvLockfile(#[from] crate::dependencies::msrv_lockfile::Error),
+
     #[error(transparent)]
     NoVersionMatchesManifestMSRV(#[from] NoVersionMatchesManifestMsrvError),
 
@@ -63,15 +66,36 @@ pub enum CargoMSRVError {
     #[error("Unable to parse rust-releases source from '{0}'")]
     RustReleasesSourceParseError(String),
 
+    #[error("Unable to parse release channel from '{0}'")]
+    ReleaseChannelParseError(String),
+
+    #[error("Unable to list the installed toolchains with `rustup toolchain list`.")]
+    RustupToolchainListFailed,
+
     #[error("Unable to install toolchain with `rustup install {0}`.")]
     RustupInstallFailed(ToolchainSpecifier),
 
+    #[error(
+        "Toolchain '{}' is not available, and could not be installed.",
+        crate::toolchain::toolchain_name(.0)
+    )]
+    UnavailableDatedToolchain(rust_releases::semver::Version),
+
+    #[error("Unable to add target with `rustup target add --toolchain {toolchain} {target}`.")]
+    RustupAddTargetFailed {
+        target: String,
+        toolchain: ToolchainSpecifier,
+    },
+
     #[error("Check toolchain (with `rustup run <toolchain> <command>`) failed.")]
     RustupRunWithCommandFailed,
 
     #[error(transparent)]
     SemverError(#[from] rust_releases::semver::Error),
 
+    #[error(transparent)]
+    SubCommandBump(#[from] bump::Error),
+
     #[error(transparent)]
     SubCommandVerify(#[from] verify::Error),
 
@@ -87,6 +111,9 @@ pub enum CargoMSRVError {
     #[error("Unable to access log folder, run with --no-log to try again without logging.")]
     UnableToAccessLogFolder,
 
+    #[error("Unable to access cache folder, run with --no-cache to try again without caching.")]
+    UnableToAccessCacheFolder,
+
     #[error("Unable to get or store the channel manifest on disk.")]
     UnableToCacheChannelManifest,
 
@@ -126,9 +153,15 @@ pub enum IoErrorSource {
     #[error("Unable to determine current working directory")]
     CurrentDir,
 
+    #[error("Unable to create folder '{0}'")]
+    CreateFolder(PathBuf),
+
     #[error("Unable to open file '{0}'")]
     OpenFile(PathBuf),
 
+    #[error("Unable to read folder '{0}'")]
+    ReadFolder(PathBuf),
+
     #[error("Unable to read file '{0}'")]
     ReadFile(PathBuf),
 
@@ -138,6 +171,9 @@ pub enum IoErrorSource {
     #[error("Unable to remove file '{0}'")]
     RemoveFile(PathBuf),
 
+    #[error("Unable to remove folder '{0}'")]
+    RemoveFolder(PathBuf),
+
     #[error("Unable to rename file '{0}'")]
     RenameFile(PathBuf),
 
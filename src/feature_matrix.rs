@@ -0,0 +1,325 @@
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use std::convert::TryFrom;
+
+use cargo_metadata::MetadataCommand;
+
+use crate::config::{inject_check_args, Config, ConfigBuilder};
+use crate::errors::{CargoMSRVError, TResult};
+use crate::msrv_per_key::{MsrvKey, MsrvPerKey};
+use crate::paths::crate_root_folder;
+
+/// A group of feature combinations for which the MSRV should be determined.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FeatureMatrixEntry {
+    /// The default features, i.e. no feature flags are passed to the check command.
+    Default,
+    /// No features at all, i.e. `--no-default-features`.
+    NoDefault,
+    /// Every feature, i.e. `--all-features`.
+    All,
+    /// Each feature of the package by itself, without the default features.
+    Each,
+    /// The given features, without the default features.
+    Set(Vec<String>),
+}
+
+impl FeatureMatrixEntry {
+    pub const DEFAULT: &'static str = "default";
+    pub const NO_DEFAULT: &'static str = "no-default";
+    pub const ALL: &'static str = "all";
+    pub const EACH: &'static str = "each";
+
+    /// Parse a comma or space separated list of features, as given to `--feature-set`.
+    pub fn from_feature_set(features: &str) -> Self {
+        let features = features
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|feature| !feature.is_empty())
+            .map(ToString::to_string)
+            .collect();
+
+        Self::Set(features)
+    }
+}
+
+impl TryFrom<&str> for FeatureMatrixEntry {
+    type Error = CargoMSRVError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            Self::DEFAULT => Ok(Self::Default),
+            Self::NO_DEFAULT => Ok(Self::NoDefault),
+            Self::ALL => Ok(Self::All),
+            Self::EACH => Ok(Self::Each),
+            s => Err(CargoMSRVError::InvalidConfig(format!(
+                "Unknown feature matrix entry '{}'",
+                s
+            ))),
+        }
+    }
+}
+
+/// A single combination of features, and the cargo flags which enable it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeatureCombination {
+    flags: Vec<String>,
+}
+
+impl FeatureCombination {
+    fn new(flags: Vec<String>) -> Self {
+        Self { flags }
+    }
+
+    fn with_features(features: &[String]) -> Self {
+        Self::new(vec![
+            "--no-default-features".to_string(),
+            "--features".to_string(),
+            features.join(","),
+        ])
+    }
+
+    /// The flags which are passed on to the check command.
+    pub fn flags(&self) -> &[String] {
+        &self.flags
+    }
+
+    /// A human readable name of the combination.
+    pub fn name(&self) -> String {
+        if self.flags.is_empty() {
+            "default".to_string()
+        } else {
+            self.flags.join(" ")
+        }
+    }
+}
+
+/// Expand the feature matrix entries into feature combinations, given the features of the package.
+///
+/// Combinations are ordered like their entries, and duplicates are removed.
+pub fn feature_combinations(
+    entries: &[FeatureMatrixEntry],
+    package_features: &[String],
+) -> Vec<FeatureCombination> {
+    let mut combinations: Vec<FeatureCombination> = Vec::new();
+
+    let mut push = |combination: FeatureCombination| {
+        if !combinations.contains(&combination) {
+            combinations.push(combination);
+        }
+    };
+
+    for entry in entries {
+        match entry {
+            FeatureMatrixEntry::Default => push(FeatureCombination::new(vec![])),
+            FeatureMatrixEntry::NoDefault => push(FeatureCombination::new(vec![
+                "--no-default-features".to_string(),
+            ])),
+            FeatureMatrixEntry::All => {
+                push(FeatureCombination::new(vec!["--all-features".to_string()]))
+            }
+            FeatureMatrixEntry::Each => {
+                for feature in package_features {
+                    push(FeatureCombination::with_features(std::slice::from_ref(
+                        feature,
+                    )));
+                }
+            }
+            FeatureMatrixEntry::Set(features) => push(FeatureCombination::with_features(features)),
+        }
+    }
+
+    combinations
+}
+
+/// Insert the flags of a feature combination into a check command.
+///
+/// The flags are inserted before an argument separator (`--`), if the check command has one,
+/// and appended otherwise.
+pub fn inject_feature_flags(
+    check_command: &[String],
+    combination: &FeatureCombination,
+) -> Vec<String> {
+    inject_check_args(check_command, combination.flags().iter().cloned())
+}
+
+/// The features of the package located at the crate root, ordered by name.
+///
+/// The `default` feature is not included, since it is covered by the default combination.
+pub(crate) fn package_features(config: &Config) -> TResult<Vec<String>> {
+    let crate_root = crate_root_folder(config)?;
+    let manifest_path = crate_root.join("Cargo.toml");
+
+    let metadata = MetadataCommand::new()
+        .manifest_path(&manifest_path)
+        .no_deps()
+        .exec()?;
+
+    // cargo metadata reports absolute manifest paths
+    let manifest_path = manifest_path.canonicalize().unwrap_or(manifest_path);
+
+    let mut features: Vec<String> = metadata
+        .packages
+        .into_iter()
+        .find(|package| package.manifest_path.as_std_path() == manifest_path)
+        .map(|package| {
+            package
+                .features
+                .into_iter()
+                .map(|(feature, _)| feature)
+                .filter(|feature| feature != "default")
+                .collect()
+        })
+        .unwrap_or_default();
+
+    features.sort();
+
+    Ok(features)
+}
+
+impl MsrvKey for FeatureCombination {
+    const HEADER: &'static str = "Features";
+    const ALL: &'static str = "all feature combinations";
+    const REASON: &'static str = "feature-matrix-msrv-complete";
+    const FIELD: &'static str = "combinations";
+
+    fn keys(config: &Config) -> TResult<Vec<Self>> {
+        let features = package_features(config)?;
+
+        Ok(feature_combinations(config.feature_matrix(), &features))
+    }
+
+    /// Adds the feature flags of the combination to the check command.
+    fn config(&self, config: &Config) -> Config {
+        ConfigBuilder::from_config(config)
+            .feature_combination(self)
+            .build()
+    }
+
+    fn name(&self) -> String {
+        FeatureCombination::name(self)
+    }
+}
+
+impl Serialize for FeatureCombination {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut combination = serializer.serialize_struct("FeatureCombination", 2)?;
+        combination.serialize_field("name", &self.name())?;
+        combination.serialize_field("flags", &self.flags)?;
+        combination.end()
+    }
+}
+
+/// The minimal compatibility of each feature combination of a package.
+pub type FeatureMatrixCompatibility = MsrvPerKey<FeatureCombination>;
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        feature_combinations, inject_feature_flags, package_features, FeatureCombination,
+        FeatureMatrixCompatibility, FeatureMatrixEntry,
+    };
+    use crate::config::{ConfigBuilder, ModeIntent};
+    use crate::formatter::{FormatUserOutput, Json};
+    use crate::reporter::json::schema::assert_conforms;
+    use crate::result::MinimalCompatibility;
+    use crate::semver::Version;
+    use crate::toolchain::OwnedToolchainSpec;
+    use std::path::PathBuf;
+
+    fn names(combinations: &[FeatureCombination]) -> Vec<String> {
+        combinations.iter().map(FeatureCombination::name).collect()
+    }
+
+    #[test]
+    fn features_of_package() {
+        let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("feature-matrix");
+
+        let config = ConfigBuilder::new(ModeIntent::Find, "")
+            .crate_path(Some(&fixture))
+            .build();
+
+        assert_eq!(package_features(&config).unwrap(), vec!["std", "unstable"]);
+    }
+
+    #[test]
+    fn expand_matrix() {
+        let features = vec!["serde".to_string(), "std".to_string()];
+        let entries = vec![
+            FeatureMatrixEntry::Default,
+            FeatureMatrixEntry::NoDefault,
+            FeatureMatrixEntry::All,
+            FeatureMatrixEntry::Each,
+            FeatureMatrixEntry::from_feature_set("serde, std"),
+        ];
+
+        let combinations = feature_combinations(&entries, &features);
+
+        assert_eq!(
+            names(&combinations),
+            vec![
+                "default",
+                "--no-default-features",
+                "--all-features",
+                "--no-default-features --features serde",
+                "--no-default-features --features std",
+                "--no-default-features --features serde,std",
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_matrix_removes_duplicates() {
+        let features = vec!["std".to_string()];
+        let entries = vec![
+            FeatureMatrixEntry::Each,
+            FeatureMatrixEntry::Set(vec!["std".to_string()]),
+            FeatureMatrixEntry::Default,
+            FeatureMatrixEntry::Default,
+        ];
+
+        let combinations = feature_combinations(&entries, &features);
+
+        assert_eq!(
+            names(&combinations),
+            vec!["--no-default-features --features std", "default"]
+        );
+    }
+
+    #[yare::parameterized(
+        append = { &["cargo", "check"], &["cargo", "check", "--all-features"] },
+        before_separator = { &["cargo", "test", "--", "--nocapture"], &["cargo", "test", "--all-features", "--", "--nocapture"] },
+    )]
+    fn inject_flags(check_command: &[&str], expected: &[&str]) {
+        let check_command = check_command
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>();
+        let combination = FeatureCombination::new(vec!["--all-features".to_string()]);
+
+        assert_eq!(inject_feature_flags(&check_command, &combination), expected);
+    }
+
+    #[test]
+    fn json_output() {
+        let compatibility = FeatureMatrixCompatibility::new(vec![(
+            FeatureCombination::new(vec!["--all-features".to_string()]),
+            MinimalCompatibility::CapableToolchain {
+                toolchain: OwnedToolchainSpec::new(
+                    &Version::new(1, 56, 0),
+                    "x86_64-unknown-linux-gnu",
+                ),
+            },
+        )]);
+
+        let output = assert_conforms(&FormatUserOutput::<Json>::format_line(&compatibility));
+
+        assert_eq!(output["reason"], "feature-matrix-msrv-complete");
+        assert_eq!(output["msrv"], "1.56.0");
+        assert_eq!(output["combinations"][0]["flags"][0], "--all-features");
+        assert_eq!(output["combinations"][0]["msrv"], "1.56.0");
+    }
+}
@@ -0,0 +1,96 @@
+use rust_releases::semver;
+
+/// Language features, by the name of their feature gate, and the Rust release in which they were
+/// stabilized.
+///
+/// This is not an exhaustive list, but covers the features which crates most commonly raise
+/// their MSRV for.
+const STABILIZED_FEATURES: &[(&str, (u64, u64))] = &[
+    ("question_mark", (1, 13)),
+    ("field_init_shorthand", (1, 17)),
+    ("pub_restricted", (1, 18)),
+    ("loop_break_value", (1, 19)),
+    ("untagged_unions", (1, 19)),
+    ("associated_consts", (1, 20)),
+    ("conservative_impl_trait", (1, 26)),
+    ("universal_impl_trait", (1, 26)),
+    ("match_default_bindings", (1, 26)),
+    ("dyn_trait", (1, 27)),
+    ("min_const_fn", (1, 31)),
+    ("unrestricted_attribute_tokens", (1, 34)),
+    ("async_await", (1, 39)),
+    ("bind_by_move_pattern_guards", (1, 39)),
+    ("non_exhaustive", (1, 40)),
+    ("min_const_generics", (1, 51)),
+    ("or_patterns", (1, 53)),
+    ("extended_key_value_attributes", (1, 54)),
+    ("bindings_after_at", (1, 56)),
+    ("const_panic", (1, 57)),
+    ("format_args_capture", (1, 58)),
+    ("asm", (1, 59)),
+    ("const_generics_defaults", (1, 59)),
+    ("destructuring_assignment", (1, 59)),
+    ("const_fn_trait_bound", (1, 61)),
+    ("generic_associated_types", (1, 65)),
+    ("label_break_value", (1, 65)),
+    ("let_else", (1, 65)),
+    ("arbitrary_enum_discriminant", (1, 66)),
+    ("impl_trait_projections", (1, 74)),
+    ("async_fn_in_trait", (1, 75)),
+    ("return_position_impl_trait_in_trait", (1, 75)),
+    ("c_str_literals", (1, 77)),
+    ("offset_of", (1, 77)),
+    ("diagnostic_namespace", (1, 78)),
+    ("associated_type_bounds", (1, 79)),
+    ("inline_const", (1, 79)),
+    ("exclusive_range_pattern", (1, 80)),
+    ("lint_reasons", (1, 81)),
+    ("precise_capturing", (1, 82)),
+    ("raw_ref_op", (1, 82)),
+    ("async_closure", (1, 85)),
+    ("trait_upcasting", (1, 86)),
+    ("let_chains", (1, 88)),
+];
+
+/// The Rust release in which the language feature with the given feature gate name was
+/// stabilized, if it is known.
+pub fn stabilized_in(feature: &str) -> Option<semver::Version> {
+    STABILIZED_FEATURES
+        .iter()
+        .find(|(name, _)| *name == feature)
+        .map(|(_, (major, minor))| semver::Version::new(*major, *minor, 0))
+}
+
+/// The names of the known stabilized language features.
+pub fn known_features() -> impl Iterator<Item = &'static str> {
+    STABILIZED_FEATURES.iter().map(|(name, _)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{known_features, stabilized_in};
+    use rust_releases::semver::Version;
+
+    #[yare::parameterized(
+        let_else = { "let_else", Version::new(1, 65, 0) },
+        async_await = { "async_await", Version::new(1, 39, 0) },
+        min_const_generics = { "min_const_generics", Version::new(1, 51, 0) },
+    )]
+    fn stabilized(feature: &str, expected: Version) {
+        assert_eq!(stabilized_in(feature), Some(expected));
+    }
+
+    #[test]
+    fn unknown_feature() {
+        assert!(stabilized_in("never_type").is_none());
+    }
+
+    #[test]
+    fn features_are_unique() {
+        let mut features = known_features().collect::<Vec<_>>();
+        features.sort_unstable();
+        features.dedup();
+
+        assert_eq!(features.len(), known_features().count());
+    }
+}
@@ -10,39 +10,72 @@ use rust_releases::{
 
 use crate::config::{Config, ModeIntent, ReleaseSource};
 use crate::errors::{CargoMSRVError, TResult};
+use crate::release_index::{
+    index_from_channel, index_from_file, index_from_installed_toolchains, IndexSnapshot,
+};
 use crate::reporter::{Output, ProgressAction};
 
+use crate::subcommands::bump::run_bump_msrv;
+use crate::subcommands::find::run_static_msrv_action;
 use crate::subcommands::list::run_list_msrv;
 use crate::subcommands::set::run_set_msrv;
 use crate::subcommands::show::run_show_msrv;
 pub use crate::{
-    result::MinimalCompatibility, subcommands::find::find_msrv,
-    subcommands::find::run_find_msrv_action, subcommands::verify::run_verify_msrv_action,
+    dependencies::msrv_lockfile::LockfileChange, dependencies::msrv_lockfile::LockfileResolution,
+    dependencies::verify::DependenciesCompatibility, dependencies::verify::DependencyCompatibility,
+    feature_matrix::FeatureCombination, feature_matrix::FeatureMatrixCompatibility,
+    feature_matrix::FeatureMatrixEntry, manifest::bare_version::BareVersion, msrv_per_key::MsrvKey,
+    msrv_per_key::MsrvPerKey, multi_target::CompileTarget, multi_target::TargetCompatibility,
+    result::MinimalCompatibility, static_msrv::FeatureGate, static_msrv::FeatureUsage,
+    static_msrv::StaticMsrv, static_msrv::UnstableFeatures,
+    subcommands::find::find_feature_matrix_msrv, subcommands::find::find_msrv,
+    subcommands::find::find_msrv_per_key, subcommands::find::find_targets_msrv,
+    subcommands::find::find_workspace_msrv, subcommands::find::run_find_msrv_action,
+    subcommands::find::FindReport, subcommands::list::DependencyMsrv,
+    subcommands::list::ListReport, subcommands::set::SetReport, subcommands::show::ShowReport,
+    subcommands::verify::run_verify_msrv_action, subcommands::verify::VerifyReport,
+    workspace::WorkspaceCompatibility, workspace::WorkspaceMember,
 };
 
+pub mod api;
+pub(crate) mod cache;
 pub mod check;
 pub mod cli;
 pub(crate) mod command;
+pub(crate) mod compatibility_report;
 pub mod config;
 pub(crate) mod dependencies;
+pub(crate) mod diagnose;
 pub(crate) mod download;
 pub mod errors;
 pub mod exit_code;
+pub(crate) mod feature_matrix;
 pub(crate) mod fetch;
 pub(crate) mod formatter;
+pub(crate) mod language_features;
 pub(crate) mod lockfile;
 pub(crate) mod manifest;
+pub(crate) mod msrv_per_key;
+pub(crate) mod multi_target;
 pub(crate) mod outcome;
 pub(crate) mod paths;
+pub(crate) mod release_index;
 pub(crate) mod releases;
 pub mod reporter;
 pub(crate) mod result;
 pub(crate) mod search_methods;
+pub(crate) mod static_msrv;
 pub(crate) mod subcommands;
 pub(crate) mod toolchain;
 pub(crate) mod toolchain_file;
+pub(crate) mod workspace;
 
 pub fn run_app<R: Output>(config: &Config, reporter: &R) -> TResult<()> {
+    // The static analysis only reads the crate sources, so it does not need the release index
+    if let (ModeIntent::Find, true) = (config.action_intent(), config.static_msrv()) {
+        return run_static_msrv_action(config, reporter);
+    }
+
     reporter.progress(ProgressAction::FetchingIndex);
 
     let index = fetch_index(config)?;
@@ -50,6 +83,18 @@ pub fn run_app<R: Output>(config: &Config, reporter: &R) -> TResult<()> {
 }
 
 fn fetch_index(config: &Config) -> TResult<ReleaseIndex> {
+    let channel = config.release_channel();
+
+    // The dated toolchains of the beta and nightly channels are not part of the release index
+    if channel.is_dated() {
+        info!(
+            channel = Into::<&'static str>::into(channel),
+            "building index of dated toolchains"
+        );
+
+        return Ok(index_from_channel(channel));
+    }
+
     let source = config.release_source();
 
     info!(
@@ -57,11 +102,53 @@ fn fetch_index(config: &Config) -> TResult<ReleaseIndex> {
         "fetching index"
     );
 
-    let index = match config.release_source() {
+    match source {
+        ReleaseSource::File => {
+            let path = config.release_index_file().ok_or_else(|| {
+                CargoMSRVError::InvalidConfig("No release index file given".into())
+            })?;
+
+            return index_from_file(path);
+        }
+        ReleaseSource::Installed => return index_from_installed_toolchains(config.target()),
+        ReleaseSource::RustChangelog | ReleaseSource::RustDist => {}
+    }
+
+    let snapshot = IndexSnapshot::try_default();
+
+    match fetch_remote_index(source) {
+        Ok(index) => {
+            if let Some(Err(err)) = snapshot.map(|snapshot| snapshot.store(&index)) {
+                warn!(%err, "unable to store snapshot of release index");
+            }
+
+            Ok(index)
+        }
+        // Fall back to the most recently fetched index, so we can also run without network access
+        Err(err) => match snapshot {
+            Some(snapshot) if snapshot.path().is_file() => {
+                warn!(
+                    %err,
+                    snapshot = %snapshot.path().display(),
+                    "unable to fetch index, using snapshot of previously fetched index instead"
+                );
+
+                snapshot.load()
+            }
+            _ => Err(err),
+        },
+    }
+}
+
+fn fetch_remote_index(source: ReleaseSource) -> TResult<ReleaseIndex> {
+    let index = match source {
         ReleaseSource::RustChangelog => {
             RustChangelog::fetch_channel(Channel::Stable)?.build_index()?
         }
         ReleaseSource::RustDist => RustDist::fetch_channel(Channel::Stable)?.build_index()?,
+        ReleaseSource::File | ReleaseSource::Installed => {
+            unreachable!("local release index is not fetched")
+        }
     };
 
     Ok(index)
@@ -78,8 +165,9 @@ fn run_action<R: Output>(config: &Config, index: &ReleaseIndex, reporter: &R) ->
     match action {
         ModeIntent::Find => run_find_msrv_action(config, reporter, index),
         ModeIntent::Verify => run_verify_msrv_action(config, reporter, index),
-        ModeIntent::List => run_list_msrv(config, reporter),
+        ModeIntent::List => run_list_msrv(config, reporter, index),
         ModeIntent::Set => run_set_msrv(config, reporter),
         ModeIntent::Show => run_show_msrv(config, reporter),
+        ModeIntent::Bump => run_bump_msrv(config, reporter, index),
     }
 }
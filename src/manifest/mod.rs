@@ -4,6 +4,15 @@ use toml_edit::{Document, Item, TomlError};
 
 pub(crate) mod bare_version;
 
+/// The tables of a manifest, or of a `[target.*]` table, in which dependencies are listed.
+pub(crate) const DEPENDENCY_TABLES: &[&str] = &[
+    "dependencies",
+    "dev-dependencies",
+    "dev_dependencies",
+    "build-dependencies",
+    "build_dependencies",
+];
+
 pub trait TomlParser {
     type Error;
 
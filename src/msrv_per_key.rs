@@ -0,0 +1,235 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, ContentArrangement, Table};
+use rust_releases::semver;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::config::Config;
+use crate::errors::TResult;
+use crate::formatter::{FormatUserOutput, Human, Json};
+use crate::reporter::json::Event;
+use crate::result::MinimalCompatibility;
+
+/// Something for which the MSRV of a crate is determined separately, like a compile target, a
+/// feature combination or a member of a workspace.
+///
+/// A key serializes to the fields which describe it in the JSON output.
+pub trait MsrvKey: Serialize {
+    /// The header of the column of the keys, in the table of the human readable output.
+    const HEADER: &'static str;
+    /// How the keys are referred to as a whole, like `all targets`.
+    const ALL: &'static str;
+    /// The reason of the JSON message which reports the MSRV of each key.
+    const REASON: &'static str;
+    /// The field of the JSON message which holds the MSRV of each key.
+    const FIELD: &'static str;
+
+    /// The keys for which the MSRV should be determined.
+    fn keys(config: &Config) -> TResult<Vec<Self>>
+    where
+        Self: Sized;
+
+    /// The configuration with which the MSRV of this key is determined.
+    fn config(&self, config: &Config) -> Config;
+
+    /// The name of the key, as shown in the human readable output.
+    fn name(&self) -> String;
+}
+
+/// The minimal compatibility of a crate for each key, like each target it was checked against.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MsrvPerKey<K> {
+    entries: Vec<(K, MinimalCompatibility)>,
+}
+
+impl<K> MsrvPerKey<K> {
+    pub fn new(entries: Vec<(K, MinimalCompatibility)>) -> Self {
+        Self { entries }
+    }
+
+    pub fn entries(&self) -> &[(K, MinimalCompatibility)] {
+        &self.entries
+    }
+
+    /// The MSRV which supports every key, which is the greatest MSRV of the keys.
+    ///
+    /// Returns `None` if there are no keys, or if no compatible toolchain could be found for at
+    /// least one of the keys.
+    pub fn msrv(&self) -> Option<&semver::Version> {
+        self.entries
+            .iter()
+            .map(|(_, compatibility)| match compatibility {
+                MinimalCompatibility::CapableToolchain { toolchain } => Some(toolchain.version()),
+                MinimalCompatibility::NoCompatibleToolchains => None,
+            })
+            .try_fold(None, |max: Option<&semver::Version>, version| {
+                version.map(|version| max.max(Some(version)))
+            })
+            .flatten()
+    }
+
+    /// The keys for which no compatible toolchain could be found.
+    pub fn incompatible(&self) -> impl Iterator<Item = &K> {
+        self.entries
+            .iter()
+            .filter(|(_, compatibility)| {
+                matches!(compatibility, MinimalCompatibility::NoCompatibleToolchains)
+            })
+            .map(|(key, _)| key)
+    }
+}
+
+fn format_compatibility(compatibility: &MinimalCompatibility) -> Option<String> {
+    match compatibility {
+        MinimalCompatibility::CapableToolchain { toolchain } => {
+            Some(toolchain.version().to_string())
+        }
+        MinimalCompatibility::NoCompatibleToolchains => None,
+    }
+}
+
+impl<K: MsrvKey> FormatUserOutput<Human> for MsrvPerKey<K> {
+    fn format_line(&self) -> String {
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![K::HEADER, "MSRV"]);
+
+        for (key, compatibility) in &self.entries {
+            table.add_row(vec![
+                Cell::new(key.name()),
+                Cell::new(format_compatibility(compatibility).unwrap_or_default()),
+            ]);
+        }
+
+        let summary = match self.msrv() {
+            Some(version) => format!("MSRV of {}: {}", K::ALL, version),
+            None => format!("Unable to find an MSRV for {}", K::ALL),
+        };
+
+        format!("{}\n{}", table, summary)
+    }
+}
+
+#[derive(Serialize)]
+struct KeyFields<'a, K> {
+    #[serde(flatten)]
+    key: &'a K,
+    msrv: Option<String>,
+}
+
+/// The event which reports the MSRV of each key, in the field named by [`MsrvKey::FIELD`].
+struct MsrvPerKeyEvent<'a, K> {
+    compatibility: &'a MsrvPerKey<K>,
+}
+
+impl<K: MsrvKey> Serialize for MsrvPerKeyEvent<'_, K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let msrv = self.compatibility.msrv();
+        let entries = self
+            .compatibility
+            .entries
+            .iter()
+            .map(|(key, compatibility)| KeyFields {
+                key,
+                msrv: format_compatibility(compatibility),
+            })
+            .collect::<Vec<_>>();
+
+        let mut event = serializer.serialize_struct("MsrvPerKeyEvent", 3)?;
+        event.serialize_field("success", &msrv.is_some())?;
+        event.serialize_field("msrv", &msrv.map(ToString::to_string))?;
+        event.serialize_field(K::FIELD, &entries)?;
+        event.end()
+    }
+}
+
+impl<K: MsrvKey> FormatUserOutput<Json> for MsrvPerKey<K> {
+    fn format_line(&self) -> String {
+        Event::new(
+            K::REASON,
+            MsrvPerKeyEvent {
+                compatibility: self,
+            },
+        )
+        .to_line()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MsrvPerKey;
+    use crate::formatter::{FormatUserOutput, Human};
+    use crate::multi_target::{CompileTarget, TargetCompatibility};
+    use crate::result::MinimalCompatibility;
+    use crate::semver::Version;
+    use crate::toolchain::OwnedToolchainSpec;
+
+    fn capable(version: Version) -> MinimalCompatibility {
+        MinimalCompatibility::CapableToolchain {
+            toolchain: OwnedToolchainSpec::new(&version, "x86_64-unknown-linux-gnu"),
+        }
+    }
+
+    fn target(triple: &str) -> CompileTarget {
+        CompileTarget::new(triple)
+    }
+
+    #[test]
+    fn msrv_is_greatest_msrv_of_keys() {
+        let compatibility = TargetCompatibility::new(vec![
+            (
+                target("x86_64-unknown-linux-gnu"),
+                capable(Version::new(1, 40, 0)),
+            ),
+            (
+                target("thumbv7em-none-eabihf"),
+                capable(Version::new(1, 56, 1)),
+            ),
+        ]);
+
+        assert_eq!(compatibility.msrv(), Some(&Version::new(1, 56, 1)));
+        assert_eq!(compatibility.incompatible().count(), 0);
+    }
+
+    #[test]
+    fn msrv_with_incompatible_key() {
+        let compatibility = TargetCompatibility::new(vec![
+            (
+                target("x86_64-unknown-linux-gnu"),
+                capable(Version::new(1, 40, 0)),
+            ),
+            (
+                target("wasm32-unknown-unknown"),
+                MinimalCompatibility::NoCompatibleToolchains,
+            ),
+        ]);
+
+        assert!(compatibility.msrv().is_none());
+        assert_eq!(
+            compatibility.incompatible().collect::<Vec<_>>(),
+            vec![&target("wasm32-unknown-unknown")]
+        );
+    }
+
+    #[test]
+    fn msrv_without_keys() {
+        let compatibility = MsrvPerKey::<CompileTarget>::new(vec![]);
+
+        assert!(compatibility.msrv().is_none());
+    }
+
+    #[test]
+    fn human_output_summary() {
+        let compatibility = TargetCompatibility::new(vec![(
+            target("wasm32-unknown-unknown"),
+            capable(Version::new(1, 56, 1)),
+        )]);
+
+        let output = FormatUserOutput::<Human>::format_line(&compatibility);
+
+        assert!(output.contains("wasm32-unknown-unknown"));
+        assert!(output.ends_with("MSRV of all targets: 1.56.1"));
+    }
+}
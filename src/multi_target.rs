@@ -0,0 +1,84 @@
+use serde::Serialize;
+
+use crate::config::{Config, ConfigBuilder};
+use crate::errors::TResult;
+use crate::msrv_per_key::{MsrvKey, MsrvPerKey};
+
+/// A target to which the crate is compiled, when the MSRV is determined for several targets.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct CompileTarget {
+    #[serde(rename = "target")]
+    triple: String,
+}
+
+impl CompileTarget {
+    pub fn new(triple: &str) -> Self {
+        Self {
+            triple: triple.to_string(),
+        }
+    }
+
+    /// The target triple, like `wasm32-unknown-unknown`.
+    pub fn triple(&self) -> &str {
+        &self.triple
+    }
+}
+
+impl MsrvKey for CompileTarget {
+    const HEADER: &'static str = "Target";
+    const ALL: &'static str = "all targets";
+    const REASON: &'static str = "targets-msrv-complete";
+    const FIELD: &'static str = "targets";
+
+    fn keys(config: &Config) -> TResult<Vec<Self>> {
+        Ok(config
+            .compile_targets()
+            .iter()
+            .map(|triple| Self::new(triple))
+            .collect())
+    }
+
+    /// Adds `--target <target>` to the check command.
+    fn config(&self, config: &Config) -> Config {
+        ConfigBuilder::from_config(config)
+            .compile_target(&self.triple)
+            .build()
+    }
+
+    fn name(&self) -> String {
+        self.triple.clone()
+    }
+}
+
+/// The minimal compatibility of a crate for each of the targets it was checked against.
+pub type TargetCompatibility = MsrvPerKey<CompileTarget>;
+
+#[cfg(test)]
+mod tests {
+    use super::{CompileTarget, TargetCompatibility};
+    use crate::formatter::{FormatUserOutput, Json};
+    use crate::reporter::json::schema::assert_conforms;
+    use crate::result::MinimalCompatibility;
+    use crate::semver::Version;
+    use crate::toolchain::OwnedToolchainSpec;
+
+    #[test]
+    fn json_output() {
+        let compatibility = TargetCompatibility::new(vec![(
+            CompileTarget::new("wasm32-unknown-unknown"),
+            MinimalCompatibility::CapableToolchain {
+                toolchain: OwnedToolchainSpec::new(
+                    &Version::new(1, 56, 1),
+                    "x86_64-unknown-linux-gnu",
+                ),
+            },
+        )]);
+
+        let output = assert_conforms(&FormatUserOutput::<Json>::format_line(&compatibility));
+
+        assert_eq!(output["reason"], "targets-msrv-complete");
+        assert_eq!(output["msrv"], "1.56.1");
+        assert_eq!(output["targets"][0]["target"], "wasm32-unknown-unknown");
+        assert_eq!(output["targets"][0]["msrv"], "1.56.1");
+    }
+}
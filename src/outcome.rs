@@ -1,8 +1,11 @@
+use crate::diagnose::CompilerDiagnostic;
 use crate::formatter::{FormatUserOutput, Human, Json};
+use crate::reporter::json::Event;
 use crate::toolchain::OwnedToolchainSpec;
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::{ContentArrangement, Table};
 use rust_releases::semver;
+use serde::Serialize;
 
 #[derive(Clone, Debug)]
 pub enum Outcome {
@@ -16,9 +19,18 @@ impl Outcome {
     }
 
     pub fn new_failure(toolchain_spec: OwnedToolchainSpec, error_message: String) -> Self {
+        Self::new_failure_with_diagnostics(toolchain_spec, error_message, Vec::new())
+    }
+
+    pub fn new_failure_with_diagnostics(
+        toolchain_spec: OwnedToolchainSpec,
+        error_message: String,
+        diagnostics: Vec<CompilerDiagnostic>,
+    ) -> Self {
         Self::Failure(FailureOutcome {
             toolchain_spec,
             error_message,
+            diagnostics,
         })
     }
 
@@ -58,20 +70,23 @@ impl FormatUserOutput<Human> for SuccessOutcome {
     }
 }
 
+#[derive(Serialize)]
+struct CheckSuccessEvent<'a> {
+    version: String,
+    // Message is more unstable other messages and will likely change in the future
+    experimental: bool,
+    toolchain: &'a str,
+}
+
 impl FormatUserOutput<Json> for SuccessOutcome {
     fn format_line(&self) -> String {
-        let version = self.toolchain_spec.version();
-        let toolchain = self.toolchain_spec.spec();
+        let event = CheckSuccessEvent {
+            version: self.toolchain_spec.version().to_string(),
+            experimental: true,
+            toolchain: self.toolchain_spec.spec(),
+        };
 
-        format!(
-            "{}",
-            json::object! {
-                reason: "last-check-success-message",
-                version: format!("{}", version),
-                experimental: true, // Message is more unstable other messages and will likely change in the future
-                toolchain: toolchain,
-            }
-        )
+        Event::new("last-check-success-message", event).to_line()
     }
 }
 
@@ -79,6 +94,9 @@ impl FormatUserOutput<Json> for SuccessOutcome {
 pub struct FailureOutcome {
     pub(crate) toolchain_spec: OwnedToolchainSpec,
     pub(crate) error_message: String,
+    /// The errors reported by the compiler, when the check command was run with
+    /// `--message-format=json`.
+    pub(crate) diagnostics: Vec<CompilerDiagnostic>,
 }
 
 impl FormatUserOutput<Human> for FailureOutcome {
@@ -97,21 +115,26 @@ impl FormatUserOutput<Human> for FailureOutcome {
     }
 }
 
+#[derive(Serialize)]
+struct CheckFailureEvent<'a> {
+    version: String,
+    // Message is more unstable other messages and will likely change in the future
+    experimental: bool,
+    toolchain: &'a str,
+    error_message: &'a str,
+    diagnostics: &'a [CompilerDiagnostic],
+}
+
 impl FormatUserOutput<Json> for FailureOutcome {
     fn format_line(&self) -> String {
-        let version = self.toolchain_spec.version();
-        let toolchain = self.toolchain_spec.spec();
-        let error_message = self.error_message.as_str();
+        let event = CheckFailureEvent {
+            version: self.toolchain_spec.version().to_string(),
+            experimental: true,
+            toolchain: self.toolchain_spec.spec(),
+            error_message: &self.error_message,
+            diagnostics: &self.diagnostics,
+        };
 
-        format!(
-            "{}",
-            json::object! {
-                reason: "last-check-failure-message",
-                version: format!("{}", version),
-                experimental: true,  // Message is more unstable other messages and will likely change in the future
-                toolchain: toolchain,
-                error_message: error_message,
-            }
-        )
+        Event::new("last-check-failure-message", event).to_line()
     }
 }
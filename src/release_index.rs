@@ -0,0 +1,372 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rust_releases::{semver, Release, ReleaseIndex};
+
+use crate::command::RustupCommand;
+use crate::config::ReleaseChannel;
+use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
+use crate::manifest::bare_version::BareVersion;
+use crate::toolchain::{DatedToolchain, RUST_1_0};
+
+const SNAPSHOT_FILE: &str = "release-index.txt";
+
+/// Build a release index from a local file.
+///
+/// Two formats are supported:
+/// * A Rust changelog, i.e. a saved copy of the `RELEASES.md` file from the Rust repository. Only
+///   lines which start with `Version` are considered, and versions with a release date in the
+///   future are skipped.
+/// * A plain list of Rust versions, with one version per line. Empty lines and lines starting
+///   with `#` are ignored. Versions may consist of two or three components, e.g. `1.56` or `1.56.1`.
+///
+/// The order of the versions in the file does not matter.
+pub fn index_from_file(path: &Path) -> TResult<ReleaseIndex> {
+    let contents = std::fs::read_to_string(path).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFile(path.to_path_buf()),
+    })?;
+
+    let versions = if is_changelog(&contents) {
+        parse_changelog(&contents, today())
+    } else {
+        parse_version_list(&contents)?
+    };
+
+    Ok(index_from_versions(versions))
+}
+
+/// Build a release index from the stable toolchains installed with rustup for the given target.
+///
+/// Only toolchains with a three component version are considered, e.g. `1.56.1-x86_64-unknown-linux-gnu`,
+/// since these match the toolchains cargo-msrv would otherwise install. Channels like `stable`
+/// and `nightly` are skipped.
+pub fn index_from_installed_toolchains(target: &str) -> TResult<ReleaseIndex> {
+    let output = RustupCommand::new()
+        .with_stdout()
+        .with_args(["list"])
+        .execute(OsString::from("toolchain"))?;
+
+    if !output.exit_status().success() {
+        return Err(CargoMSRVError::RustupToolchainListFailed);
+    }
+
+    let versions = parse_installed_toolchains(output.stdout(), target);
+
+    Ok(index_from_versions(versions))
+}
+
+/// Build a release index from the dated toolchains of the beta or nightly channel.
+///
+/// The index contains a toolchain for every day from the release of Rust 1.0 up to and including
+/// yesterday, since the toolchain of today may not have been published yet. Each toolchain is
+/// represented by the version of [`DatedToolchain::to_version`].
+pub fn index_from_channel(channel: ReleaseChannel) -> ReleaseIndex {
+    index_from_versions(dated_versions(channel, today()))
+}
+
+fn dated_versions(channel: ReleaseChannel, today: Days) -> Vec<semver::Version> {
+    (RUST_1_0..today)
+        .filter_map(|days| DatedToolchain::new(channel, days))
+        .map(|toolchain| toolchain.to_version())
+        .collect()
+}
+
+/// Parses the output of `rustup toolchain list`, where each toolchain is listed on a single
+/// line, optionally followed by `(default)` or `(override)`.
+fn parse_installed_toolchains(output: &str, target: &str) -> Vec<semver::Version> {
+    let suffix = format!("-{}", target);
+
+    output
+        .lines()
+        .filter_map(|line| line.split_ascii_whitespace().next())
+        .filter_map(|toolchain| toolchain.strip_suffix(&suffix))
+        .filter_map(|version| semver::Version::parse(version).ok())
+        .collect()
+}
+
+fn is_changelog(contents: &str) -> bool {
+    contents.lines().any(|line| line.starts_with("Version "))
+}
+
+/// Parses lines like `Version 1.56.0 (2021-10-21)`.
+fn parse_changelog(contents: &str, today: Days) -> Vec<semver::Version> {
+    let mut versions = Vec::new();
+
+    for line in contents.lines().filter(|line| line.starts_with("Version ")) {
+        let mut parts = line.split_ascii_whitespace().skip(1);
+
+        let version = match parts.next().map(semver::Version::parse) {
+            Some(Ok(version)) if version.pre.is_empty() => version,
+            // Versions prior to 1.0 did not always use three components, and aren't useful to us
+            _ => continue,
+        };
+
+        let released = parts
+            .next()
+            .map(|date| date.trim_matches(|c| c == '(' || c == ')'))
+            .and_then(days_from_date)
+            .map_or(true, |date| date <= today);
+
+        if released {
+            versions.push(version);
+        }
+    }
+
+    versions
+}
+
+fn parse_version_list(contents: &str) -> TResult<Vec<semver::Version>> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            BareVersion::from_str(line)
+                .map(|version| version.to_semver_version())
+                .map_err(CargoMSRVError::from)
+        })
+        .collect()
+}
+
+fn index_from_versions(mut versions: Vec<semver::Version>) -> ReleaseIndex {
+    // The release index must be ordered from the most to the least recent release
+    versions.sort_by(|lhs, rhs| rhs.cmp(lhs));
+    versions.dedup();
+
+    versions.into_iter().map(Release::new_stable).collect()
+}
+
+/// Days since the Unix epoch
+pub(crate) type Days = i64;
+
+pub(crate) fn today() -> Days {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    (seconds / 86_400) as Days
+}
+
+/// Parses a `YYYY-MM-DD` date to the days since the Unix epoch.
+pub(crate) fn days_from_date(date: &str) -> Option<Days> {
+    let mut parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+
+    let year = parts.next()??;
+    let month = parts.next()??;
+    let day = parts.next()??;
+
+    // See: http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+/// Formats the days since the Unix epoch as a `YYYY-MM-DD` date.
+pub(crate) fn date_from_days(days: Days) -> String {
+    // See: http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days + 719_468;
+    let era = (if days >= 0 { days } else { days - 146_096 }) / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// A copy of the most recently fetched release index, stored on disk.
+///
+/// When the release index can not be fetched, for example because there is no network connection,
+/// the snapshot is used instead. The snapshot uses the plain list format of [`index_from_file`].
+#[derive(Debug)]
+pub struct IndexSnapshot {
+    path: PathBuf,
+}
+
+impl IndexSnapshot {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Use the default snapshot location, which is located in the cargo-msrv data folder.
+    pub fn try_default() -> Option<Self> {
+        dirs::data_local_dir().map(|path| Self::new(path.join("cargo-msrv").join(SNAPSHOT_FILE)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn store(&self, index: &ReleaseIndex) -> TResult<()> {
+        if let Some(folder) = self.path.parent() {
+            std::fs::create_dir_all(folder).map_err(|error| CargoMSRVError::Io {
+                error,
+                source: IoErrorSource::CreateFolder(folder.to_path_buf()),
+            })?;
+        }
+
+        let contents = index
+            .releases()
+            .iter()
+            .map(|release| format!("{}\n", release.version()))
+            .collect::<String>();
+
+        std::fs::write(&self.path, contents).map_err(|error| CargoMSRVError::Io {
+            error,
+            source: IoErrorSource::WriteFile(self.path.clone()),
+        })
+    }
+
+    pub fn load(&self) -> TResult<ReleaseIndex> {
+        index_from_file(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        date_from_days, dated_versions, days_from_date, parse_changelog,
+        parse_installed_toolchains, parse_version_list, IndexSnapshot,
+    };
+    use crate::config::ReleaseChannel;
+    use crate::semver::Version;
+    use rust_releases::{Release, ReleaseIndex};
+
+    #[test]
+    fn parse_plain_version_list() {
+        let contents = r#"
+# versions available on our build hosts
+1.58.1
+1.56
+
+1.38.0
+"#;
+
+        let versions = parse_version_list(contents).unwrap();
+
+        assert_eq!(
+            versions,
+            vec![
+                Version::new(1, 58, 1),
+                Version::new(1, 56, 0),
+                Version::new(1, 38, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_rustup_toolchain_list() {
+        let output = r#"stable-x86_64-unknown-linux-gnu (default)
+nightly-2022-02-01-x86_64-unknown-linux-gnu
+1.38.0-x86_64-unknown-linux-gnu
+1.56-x86_64-unknown-linux-gnu
+1.56.1-x86_64-unknown-linux-gnu
+1.58.1-x86_64-pc-windows-msvc
+1.58.1-x86_64-unknown-linux-gnu (override)
+"#;
+
+        let versions = parse_installed_toolchains(output, "x86_64-unknown-linux-gnu");
+
+        assert_eq!(
+            versions,
+            vec![
+                Version::new(1, 38, 0),
+                Version::new(1, 56, 1),
+                Version::new(1, 58, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_invalid_version_list() {
+        assert!(parse_version_list("1.58.1\nstable\n").is_err());
+    }
+
+    #[test]
+    fn parse_changelog_skips_unreleased_and_ancient_versions() {
+        let contents = r#"
+Version 1.59.0 (2022-02-24)
+==========================
+
+Language
+--------
+- [Stabilize default arguments for const generics][90207]
+
+Version 1.58.1 (2022-01-19)
+===========================
+
+Version 1.58.0 (2022-01-13)
+==========================
+
+Version 0.10 (2014-04-03)
+=========================
+"#;
+
+        let today = days_from_date("2022-02-01").unwrap();
+        let versions = parse_changelog(contents, today);
+
+        assert_eq!(
+            versions,
+            vec![Version::new(1, 58, 1), Version::new(1, 58, 0)]
+        );
+    }
+
+    #[yare::parameterized(
+        epoch = { "1970-01-01", 0 },
+        day_after_epoch = { "1970-01-02", 1 },
+        leap_day = { "2020-02-29", 18_321 },
+        rust_1_0 = { "2015-05-15", 16_570 },
+    )]
+    fn days_since_epoch(date: &str, expected: i64) {
+        assert_eq!(days_from_date(date), Some(expected));
+        assert_eq!(date_from_days(expected), date);
+    }
+
+    #[test]
+    fn dated_versions_until_yesterday() {
+        let today = days_from_date("2023-04-14").unwrap();
+        let versions = dated_versions(ReleaseChannel::Nightly, today);
+
+        assert_eq!(
+            versions.first(),
+            Some(&Version::parse("1.2.0-nightly.2015-05-15").unwrap())
+        );
+        assert_eq!(
+            versions.last(),
+            Some(&Version::parse("1.70.0-nightly.2023-04-13").unwrap())
+        );
+        assert!(dated_versions(ReleaseChannel::Stable, today).is_empty());
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let path = std::env::temp_dir()
+            .join("cargo-msrv-release-index-tests")
+            .join("release-index.txt");
+        let snapshot = IndexSnapshot::new(path);
+
+        let index: ReleaseIndex = vec![
+            Release::new_stable(Version::new(1, 58, 1)),
+            Release::new_stable(Version::new(1, 56, 1)),
+        ]
+        .into_iter()
+        .collect();
+
+        snapshot.store(&index).unwrap();
+        let loaded = snapshot.load().unwrap();
+
+        assert_eq!(loaded.releases(), index.releases());
+    }
+}
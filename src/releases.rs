@@ -1,27 +1,48 @@
+use crate::config::ReleaseChannel;
 use crate::{semver, Config};
 use rust_releases::linear::LatestStableReleases;
 use rust_releases::Release;
 
 pub fn filter_releases(config: &Config, releases: &[Release]) -> Vec<Release> {
-    let releases = if config.include_all_patch_releases() {
+    let channel = config.release_channel();
+
+    // Dated toolchains share their version, so they must not be reduced to the latest patch release
+    let releases = if config.include_all_patch_releases() || channel.is_dated() {
         releases.to_vec()
     } else {
         releases.iter().cloned().latest_stable_releases().collect()
     };
 
+    let minimum_version = config
+        .minimum_version()
+        .map(|min| lower_bound_on_channel(min, channel));
+
     // Pre-filter the [min-version:max-version] range
     releases
         .into_iter()
         .filter(|release| {
             include_version(
                 release.version(),
-                config.minimum_version(),
+                minimum_version.as_ref(),
                 config.maximum_version(),
             )
         })
         .collect::<Vec<_>>()
 }
 
+/// The dated toolchains of a Rust version are pre-releases, which precede the version itself.
+/// When a Rust version is given as the minimum version of a dated channel, the search starts at the
+/// first dated toolchain of that version instead.
+fn lower_bound_on_channel(min: &semver::Version, channel: ReleaseChannel) -> semver::Version {
+    if !channel.is_dated() || !min.pre.is_empty() {
+        return min.clone();
+    }
+
+    let mut min = min.clone();
+    min.pre = semver::Prerelease::new(channel.into()).expect("channel is a valid identifier");
+    min
+}
+
 fn include_version(
     current: &semver::Version,
     min_version: Option<&semver::Version>,
@@ -102,4 +123,46 @@ mod tests {
             max_version.as_ref()
         ));
     }
+
+    #[parameterized(min = {
+        "1.69.0",             // all dated toolchains of 1.69 and later
+        "nightly-2023-03-01", // from a dated toolchain
+    }, expected = {
+        &["2023-04-12", "2023-03-01", "2023-02-28"],
+        &["2023-04-12", "2023-03-01"],
+    })]
+    fn test_filter_dated_toolchains(min: &str, expected: &[&str]) {
+        use crate::config::{parse_version, ConfigBuilder, ModeIntent, ReleaseChannel};
+
+        let config = ConfigBuilder::new(ModeIntent::Find, "x86_64-unknown-linux-gnu")
+            .release_channel(ReleaseChannel::Nightly)
+            .minimum_version(parse_version(min).unwrap())
+            .maximum_version(parse_version("nightly-2023-04-12").unwrap())
+            .build();
+
+        let releases = [
+            "1.70.0-nightly.2023-04-13",
+            "1.70.0-nightly.2023-04-12",
+            "1.69.0-nightly.2023-03-01",
+            "1.69.0-nightly.2023-02-28",
+            "1.68.0-nightly.2023-01-19",
+        ]
+        .iter()
+        .map(|version| Release::new_stable(Version::parse(version).unwrap()))
+        .collect::<Vec<_>>();
+
+        let dates = filter_releases(&config, &releases)
+            .iter()
+            .map(|release| {
+                release
+                    .version()
+                    .pre
+                    .as_str()
+                    .trim_start_matches("nightly.")
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(dates, expected);
+    }
 }
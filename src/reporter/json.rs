@@ -1,11 +1,89 @@
 use std::cell::Cell;
 
-use json::object::Object;
-use json::JsonValue;
 use rust_releases::semver;
+use serde::Serialize;
 
 use crate::config::ModeIntent;
 use crate::reporter::ProgressAction;
+use crate::toolchain::toolchain_name;
+
+/// The version of the schema of the JSON events. It's raised whenever an event changes in a way
+/// which may break a parser, i.e. when a field is removed or renamed, or when its type changes.
+///
+/// The schema is published as `book/src/output-formats/json-events.schema.json`.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// An event of the JSON output format. Each event is serialized as a single line, which starts
+/// with the reason that identifies the kind of event, followed by the version of the schema and
+/// the fields of the event.
+#[derive(Debug, Serialize)]
+pub struct Event<'a, T> {
+    reason: &'a str,
+    schema_version: u64,
+    #[serde(flatten)]
+    fields: T,
+}
+
+impl<'a, T: Serialize> Event<'a, T> {
+    pub fn new(reason: &'a str, fields: T) -> Self {
+        Self {
+            reason,
+            schema_version: SCHEMA_VERSION,
+            fields,
+        }
+    }
+
+    /// The event, serialized as a single line of JSON.
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).expect("events serialize to JSON objects")
+    }
+}
+
+/// The toolchain and check command, which are shared by most events.
+#[derive(Debug, Serialize)]
+struct ToolchainFields<'a> {
+    toolchain: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    check_cmd: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct ModeEvent<'a> {
+    mode: &'a str,
+    #[serde(flatten)]
+    toolchain: ToolchainFields<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct FetchingIndexEvent {}
+
+#[derive(Debug, Serialize)]
+struct ProgressEvent<'a> {
+    version: String,
+    step: u64,
+    total: u64,
+    #[serde(flatten)]
+    toolchain: ToolchainFields<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct CheckCompleteEvent<'a> {
+    version: String,
+    step: u64,
+    total: u64,
+    success: bool,
+    #[serde(flatten)]
+    toolchain: ToolchainFields<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct ModeCompleteEvent<'a> {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    msrv: Option<String>,
+    #[serde(flatten)]
+    toolchain: ToolchainFields<'a>,
+}
 
 #[derive(Debug)]
 pub struct JsonPrinter<'a> {
@@ -32,24 +110,78 @@ impl<'a> JsonPrinter<'a> {
             ModeIntent::List => "list-complete",
             ModeIntent::Set => "set-complete",
             ModeIntent::Show => "show-complete",
+            ModeIntent::Bump => "bump-complete",
         }
     }
-}
 
-impl<'a> crate::Output for JsonPrinter<'a> {
-    fn mode(&self, mode: ModeIntent) {
+    fn toolchain_fields(&self) -> ToolchainFields<'a> {
+        ToolchainFields {
+            toolchain: self.toolchain,
+            check_cmd: self.cmd,
+        }
+    }
+
+    fn mode_event(&self, mode: ModeIntent) -> String {
         let mode: &str = mode.into();
 
-        let mut object = Object::new();
-        object.insert("reason", "mode".into());
-        object.insert("mode", mode.into());
-        object.insert("toolchain", self.toolchain.into());
+        let event = ModeEvent {
+            mode,
+            toolchain: self.toolchain_fields(),
+        };
+
+        Event::new("mode", event).to_line()
+    }
 
-        if let Some(cmd) = self.cmd {
-            object.insert("check_cmd", cmd.into());
-        }
+    fn progress_event(&self, action: ProgressAction) -> String {
+        let (reason, version) = match action {
+            ProgressAction::Installing(version) => ("installing", version),
+            ProgressAction::Checking(version) => ("checking", version),
+            ProgressAction::FetchingIndex => {
+                return Event::new("fetching-index", FetchingIndexEvent {}).to_line()
+            }
+        };
+
+        let event = ProgressEvent {
+            version: version.to_string(),
+            step: self.finished.get(),
+            total: self.steps.get(),
+            toolchain: self.toolchain_fields(),
+        };
 
-        println!("{}", JsonValue::Object(object));
+        Event::new(reason, event).to_line()
+    }
+
+    fn complete_step_event(&self, version: &semver::Version, success: bool) -> String {
+        let event = CheckCompleteEvent {
+            version: version.to_string(),
+            step: self.finished.get(),
+            total: self.steps.get(),
+            success,
+            toolchain: self.toolchain_fields(),
+        };
+
+        Event::new("check-complete", event).to_line()
+    }
+
+    fn finish_event(
+        &self,
+        mode: ModeIntent,
+        success: bool,
+        msrv: Option<&semver::Version>,
+    ) -> String {
+        let event = ModeCompleteEvent {
+            success,
+            msrv: msrv.map(toolchain_name),
+            toolchain: self.toolchain_fields(),
+        };
+
+        Event::new(Self::reason(mode), event).to_line()
+    }
+}
+
+impl<'a> crate::Output for JsonPrinter<'a> {
+    fn mode(&self, mode: ModeIntent) {
+        println!("{}", self.mode_event(mode));
     }
 
     fn set_steps(&self, steps: u64) {
@@ -57,88 +189,320 @@ impl<'a> crate::Output for JsonPrinter<'a> {
     }
 
     fn progress(&self, action: crate::ProgressAction) {
-        let action_str = match action {
-            ProgressAction::Installing(_) => "installing",
-            ProgressAction::Checking(_) => "checking",
-            ProgressAction::FetchingIndex => "fetching-index",
-        };
+        println!("{}", self.progress_event(action));
+    }
+
+    fn complete_step(&self, version: &semver::Version, success: bool) {
+        println!("{}", self.complete_step_event(version, success));
+        self.finished.set(self.finished.get() + 1);
+    }
+
+    fn finish_success(&self, mode: ModeIntent, version: Option<&semver::Version>) {
+        println!("{}", self.finish_event(mode, true, version));
+    }
+
+    fn finish_failure(&self, mode: ModeIntent, _: Option<&str>) {
+        println!("{}", self.finish_event(mode, false, None));
+    }
+
+    /// Writes an event, which was serialized with [`Event::to_line`].
+    fn write_line(&self, content: &str) {
+        println!("{}", content.trim_end());
+    }
+}
+
+/// Validation of events against the published JSON Schema, for the tests of the modules which
+/// define events.
+#[cfg(test)]
+pub(crate) mod schema {
+    use json::JsonValue;
 
-        match action {
-            ProgressAction::Installing(version) | ProgressAction::Checking(version) => {
-                let mut object = Object::new();
-                object.insert("reason", action_str.into());
-                object.insert("version", version.to_string().into());
-                object.insert("step", self.finished.get().into());
-                object.insert("total", self.steps.get().into());
-                object.insert("toolchain", self.toolchain.into());
-
-                if let Some(cmd) = self.cmd {
-                    object.insert("check_cmd", cmd.into());
+    const SCHEMA: &str = include_str!("../../book/src/output-formats/json-events.schema.json");
+
+    /// The published schema of the events.
+    pub(crate) fn schema() -> JsonValue {
+        json::parse(SCHEMA).unwrap()
+    }
+
+    /// Parses a line of JSON output, and asserts that it's an event which conforms to the schema,
+    /// including its nested fields.
+    pub(crate) fn assert_conforms(line: &str) -> JsonValue {
+        let event =
+            json::parse(line).unwrap_or_else(|err| panic!("'{}' is not JSON: {}", line, err));
+        let schema = schema();
+
+        // validate against the definition of the reason first, for a more precise error
+        let reason = &event["reason"];
+        let definition = schema["$defs"]
+            .entries()
+            .map(|(_, definition)| definition)
+            .find(|definition| {
+                let property = &definition["properties"]["reason"];
+                &property["const"] == reason || property["enum"].members().any(|r| r == reason)
+            })
+            .unwrap_or_else(|| panic!("no definition for reason {}", reason));
+
+        for schema_of_event in [definition, &schema] {
+            if let Err(err) = validate(&schema, schema_of_event, &event, "event") {
+                panic!("{} does not conform to the schema: {}", line, err);
+            }
+        }
+
+        event
+    }
+
+    /// Validates a value against a (sub)schema, for the keywords which the schema uses.
+    fn validate(
+        root: &JsonValue,
+        schema: &JsonValue,
+        value: &JsonValue,
+        path: &str,
+    ) -> Result<(), String> {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference
+                .strip_prefix("#/$defs/")
+                .unwrap_or_else(|| panic!("unsupported reference '{}'", reference));
+            validate(root, &root["$defs"][name], value, path)?;
+        }
+
+        if !schema["const"].is_null() && &schema["const"] != value {
+            return Err(format!("{} must be {}", path, schema["const"]));
+        }
+
+        if schema["enum"].is_array() && !schema["enum"].members().any(|member| member == value) {
+            return Err(format!("{} must be one of {}", path, schema["enum"]));
+        }
+
+        let types = &schema["type"];
+        let type_of_value = type_of(value);
+        if !(types.is_null()
+            || types == type_of_value
+            || types.members().any(|t| t == type_of_value))
+        {
+            return Err(format!("{} may not be of type {}", path, type_of_value));
+        }
+
+        if schema["oneOf"].is_array() {
+            let matches = schema["oneOf"]
+                .members()
+                .filter(|option| validate(root, option, value, path).is_ok())
+                .count();
+            if matches != 1 {
+                return Err(format!(
+                    "{} matches {} of the options of oneOf",
+                    path, matches
+                ));
+            }
+        }
+
+        if schema["anyOf"].is_array()
+            && !schema["anyOf"]
+                .members()
+                .any(|option| validate(root, option, value, path).is_ok())
+        {
+            return Err(format!("{} matches none of the options of anyOf", path));
+        }
+
+        if value.is_object() {
+            for required in schema["required"].members() {
+                let key = required.as_str().unwrap();
+                if !value.has_key(key) {
+                    return Err(format!("{} requires field '{}'", path, key));
+                }
+            }
+
+            for (key, field) in value.entries() {
+                let property = &schema["properties"][key];
+                if property.is_null() && schema["additionalProperties"] == false {
+                    return Err(format!("{} has no field '{}'", path, key));
                 }
+                validate(root, property, field, &format!("{}.{}", path, key))?;
+            }
+        }
 
-                println!("{}", JsonValue::Object(object));
+        if value.is_array() && !schema["items"].is_null() {
+            for (i, item) in value.members().enumerate() {
+                validate(root, &schema["items"], item, &format!("{}[{}]", path, i))?;
             }
-            ProgressAction::FetchingIndex => println!(
+        }
+
+        Ok(())
+    }
+
+    fn type_of(value: &JsonValue) -> &'static str {
+        match value {
+            JsonValue::Null => "null",
+            JsonValue::Short(_) | JsonValue::String(_) => "string",
+            JsonValue::Number(number) if f64::from(*number).fract() == 0.0 => "integer",
+            JsonValue::Number(_) => "number",
+            JsonValue::Boolean(_) => "boolean",
+            JsonValue::Object(_) => "object",
+            JsonValue::Array(_) => "array",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::schema::{assert_conforms, schema};
+    use super::{Event, JsonPrinter, SCHEMA_VERSION};
+    use crate::config::ModeIntent;
+    use crate::diagnose::{CompilerDiagnostic, Diagnosis, DiagnosticSpan};
+    use crate::formatter::{FormatUserOutput, Json};
+    use crate::outcome::{FailureOutcome, Outcome};
+    use crate::reporter::ProgressAction;
+    use crate::semver::Version;
+    use crate::static_msrv::UnstableFeatures;
+    use crate::toolchain::OwnedToolchainSpec;
+
+    #[test]
+    fn schema_version_of_schema() {
+        let schema = schema();
+
+        let events = schema["$defs"]
+            .entries()
+            .filter(|(_, definition)| definition["properties"].has_key("reason"));
+
+        for (name, definition) in events {
+            assert_eq!(
+                definition["properties"]["schema_version"]["const"], SCHEMA_VERSION,
                 "{}",
-                json::object! {
-                    reason: action_str,
-                }
-            ),
-        };
+                name
+            );
+        }
     }
 
-    fn complete_step(&self, version: &semver::Version, success: bool) {
-        println!(
-            "{}",
-            json::object! {
-                reason: "check-complete",
-                version: version.to_string(),
-                step: self.finished.get(),
-                total_steps: self.steps.get(),
-                success: success,
-                toolchain: self.toolchain,
-                check_cmd: self.cmd,
-            }
+    #[test]
+    fn event_starts_with_reason_and_schema_version() {
+        #[derive(serde::Serialize)]
+        struct Fields {
+            msrv: Option<String>,
+        }
+
+        assert_eq!(
+            Event::new("static-msrv", Fields { msrv: None }).to_line(),
+            format!(
+                "{{\"reason\":\"static-msrv\",\"schema_version\":{},\"msrv\":null}}",
+                SCHEMA_VERSION
+            )
         );
-        self.finished.set(self.finished.get() + 1);
     }
 
-    fn finish_success(&self, mode: ModeIntent, version: Option<&semver::Version>) {
-        let reason = Self::reason(mode);
+    #[test]
+    fn printer_events_conform_to_schema() {
+        let printer = JsonPrinter::new(3, "x86_64-unknown-linux-gnu", Some("cargo check"));
+        let version = Version::new(1, 56, 0);
 
-        let mut object = Object::new();
-        object.insert("reason", reason.into());
-        object.insert("success", true.into());
+        let mut events = vec![
+            printer.mode_event(ModeIntent::Find),
+            printer.progress_event(ProgressAction::FetchingIndex),
+            printer.progress_event(ProgressAction::Installing(&version)),
+            printer.progress_event(ProgressAction::Checking(&version)),
+            printer.complete_step_event(&version, true),
+        ];
 
-        if let Some(version) = version {
-            object.insert("msrv", version.to_string().into());
+        for mode in [
+            ModeIntent::Find,
+            ModeIntent::Verify,
+            ModeIntent::List,
+            ModeIntent::Set,
+            ModeIntent::Show,
+            ModeIntent::Bump,
+        ] {
+            events.push(printer.finish_event(mode, true, Some(&version)));
+            events.push(printer.finish_event(mode, false, None));
         }
 
-        object.insert("toolchain", self.toolchain.into());
+        let events = events
+            .iter()
+            .map(|line| assert_conforms(line))
+            .collect::<Vec<_>>();
+
+        assert_eq!(events[4]["total"], 3);
+        assert_eq!(events[5]["msrv"], "1.56.0");
+        assert!(!events[6].has_key("msrv"));
+    }
+
+    #[test]
+    fn printer_events_without_check_command() {
+        let printer = JsonPrinter::new(3, "x86_64-unknown-linux-gnu", None);
+
+        let event = assert_conforms(&printer.mode_event(ModeIntent::Find));
+        assert!(!event.has_key("check_cmd"));
+    }
 
-        if let Some(cmd) = self.cmd {
-            object.insert("check_cmd", cmd.into());
+    fn failure(diagnostics: Vec<CompilerDiagnostic>) -> FailureOutcome {
+        let toolchain =
+            OwnedToolchainSpec::new(&Version::new(1, 56, 0), "x86_64-unknown-linux-gnu");
+
+        match Outcome::new_failure_with_diagnostics(
+            toolchain,
+            "error[E0658]: use of unstable library feature 'let_else'\n".to_string(),
+            diagnostics,
+        ) {
+            Outcome::Failure(failure) => failure,
+            Outcome::Success(_) => unreachable!(),
         }
+    }
+
+    #[test]
+    fn check_events_conform_to_schema() {
+        let toolchain =
+            OwnedToolchainSpec::new(&Version::new(1, 56, 0), "x86_64-unknown-linux-gnu");
+        let success = match Outcome::new_success(toolchain) {
+            Outcome::Success(success) => success,
+            Outcome::Failure(_) => unreachable!(),
+        };
+
+        let diagnostic = CompilerDiagnostic {
+            level: "error".to_string(),
+            code: Some("E0658".to_string()),
+            message: "use of unstable library feature 'let_else'".to_string(),
+            rendered: None,
+            spans: vec![DiagnosticSpan {
+                file_name: "src/lib.rs".to_string(),
+                line_start: 3,
+                line_end: 3,
+                column_start: 5,
+                column_end: 9,
+                is_primary: true,
+                label: None,
+            }],
+            children: vec!["see issue #87335".to_string()],
+        };
+
+        assert_conforms(&FormatUserOutput::<Json>::format_line(&success));
+        assert_conforms(&FormatUserOutput::<Json>::format_line(&failure(Vec::new())));
 
-        println!("{}", JsonValue::Object(object));
+        let event = assert_conforms(&FormatUserOutput::<Json>::format_line(&failure(vec![
+            diagnostic,
+        ])));
+        assert_eq!(event["diagnostics"][0]["spans"][0]["line_start"], 3);
     }
 
-    fn finish_failure(&self, mode: ModeIntent, _: Option<&str>) {
-        let reason = Self::reason(mode);
+    #[test]
+    fn diagnosis_conforms_to_schema() {
+        let diagnosis = Diagnosis::new(Version::new(1, 57, 0), &failure(Vec::new()));
 
-        let mut object = Object::new();
-        object.insert("reason", reason.into());
-        object.insert("success", false.into());
-        object.insert("toolchain", self.toolchain.into());
+        let event = assert_conforms(&FormatUserOutput::<Json>::format_line(&diagnosis));
+        assert_eq!(event["causes"][0]["code"], "E0658");
+        assert!(event["causes"][0]["file"].is_null());
+    }
 
-        if let Some(cmd) = self.cmd {
-            object.insert("check_cmd", cmd.into());
-        }
+    #[test]
+    fn unstable_features_conform_to_schema() {
+        let features = UnstableFeatures::new(Vec::new(), None);
 
-        println!("{}", JsonValue::Object(object));
+        let event = assert_conforms(&FormatUserOutput::<Json>::format_line(&features));
+        assert!(event["nightly"].is_null());
     }
 
-    fn write_line(&self, content: &str) {
-        println!("{}", content);
+    #[test]
+    #[should_panic(expected = "does not conform to the schema")]
+    fn nested_fields_are_validated() {
+        assert_conforms(&format!(
+            "{{\"reason\":\"bump-plan\",\"schema_version\":{},\"current_msrv\":\"1.56\",\"msrv\":null,\"requirements\":[{{\"requirement\":\"syn\"}}]}}",
+            SCHEMA_VERSION
+        ));
     }
 }
@@ -0,0 +1,264 @@
+use std::cell::{Cell, RefCell};
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use rust_releases::semver;
+
+use crate::config::ModeIntent;
+use crate::reporter::ProgressAction;
+use crate::toolchain::toolchain_name;
+
+/// Reports the checked toolchains as a JUnit XML document, with a test case for each checked
+/// toolchain, and a test suite for each mode in which cargo-msrv operated.
+///
+/// Since a test report should be a single document, nothing is printed while cargo-msrv runs; the
+/// report should be obtained with [`JunitPrinter::report`] once it has finished.
+#[derive(Debug)]
+pub struct JunitPrinter<'a> {
+    toolchain: &'a str,
+    cmd: Option<&'a str>,
+    suites: RefCell<Vec<TestSuite>>,
+    started: Cell<Option<Instant>>,
+}
+
+impl<'a> JunitPrinter<'a> {
+    pub fn new(toolchain: &'a str, cmd: Option<&'a str>) -> Self {
+        Self {
+            toolchain,
+            cmd,
+            suites: RefCell::new(Vec::new()),
+            started: Cell::new(None),
+        }
+    }
+
+    /// The JUnit XML document of the toolchains checked so far.
+    pub fn report(&self) -> String {
+        let suites = self.suites.borrow();
+        let tests = suites.iter().map(|suite| suite.cases.len()).sum::<usize>();
+        let failures = suites.iter().map(TestSuite::failures).sum::<usize>();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"cargo-msrv\" tests=\"{}\" failures=\"{}\">",
+            tests, failures
+        );
+
+        for suite in suites.iter() {
+            self.write_suite(&mut xml, suite);
+        }
+
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+
+    fn write_suite(&self, xml: &mut String, suite: &TestSuite) {
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">",
+            suite.name,
+            suite.cases.len(),
+            suite.failures()
+        );
+
+        xml.push_str("    <properties>\n");
+        let _ = writeln!(
+            xml,
+            "      <property name=\"target\" value=\"{}\"/>",
+            escape(self.toolchain)
+        );
+        if let Some(cmd) = self.cmd {
+            let _ = writeln!(
+                xml,
+                "      <property name=\"check_cmd\" value=\"{}\"/>",
+                escape(cmd)
+            );
+        }
+        xml.push_str("    </properties>\n");
+
+        for case in &suite.cases {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"cargo-msrv.{}\" time=\"{:.3}\"",
+                escape(&toolchain_name(&case.version)),
+                suite.name,
+                case.time.as_secs_f64()
+            );
+
+            if case.success {
+                xml.push_str("/>\n");
+                continue;
+            }
+
+            let _ = writeln!(
+                xml,
+                ">\n      <failure message=\"Check for toolchain '{}-{}' failed\">{}</failure>\n    </testcase>",
+                escape(&toolchain_name(&case.version)),
+                escape(self.toolchain),
+                escape(case.failure.as_deref().unwrap_or_default().trim())
+            );
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+}
+
+impl<'a> crate::Output for JunitPrinter<'a> {
+    fn mode(&self, mode: ModeIntent) {
+        self.suites.borrow_mut().push(TestSuite::new(mode.into()));
+    }
+
+    fn set_steps(&self, _steps: u64) {}
+
+    fn progress(&self, action: ProgressAction) {
+        if let ProgressAction::Checking(_) = action {
+            self.started.set(Some(Instant::now()));
+        }
+    }
+
+    fn complete_step(&self, version: &semver::Version, success: bool) {
+        let time = self
+            .started
+            .take()
+            .map(|started| started.elapsed())
+            .unwrap_or_default();
+
+        let mut suites = self.suites.borrow_mut();
+        if suites.is_empty() {
+            suites.push(TestSuite::new("cargo-msrv"));
+        }
+
+        if let Some(suite) = suites.last_mut() {
+            suite.cases.push(TestCase {
+                version: version.clone(),
+                success,
+                time,
+                failure: None,
+            });
+        }
+    }
+
+    fn finish_success(&self, _mode: ModeIntent, _version: Option<&semver::Version>) {}
+
+    fn finish_failure(&self, _mode: ModeIntent, _cmd: Option<&str>) {}
+
+    fn check_failed(&self, version: &semver::Version, error_message: &str) {
+        let mut suites = self.suites.borrow_mut();
+
+        let case = suites.last_mut().and_then(|suite| {
+            suite
+                .cases
+                .iter_mut()
+                .rev()
+                .find(|case| &case.version == version)
+        });
+
+        if let Some(case) = case {
+            case.failure = Some(error_message.to_string());
+        }
+    }
+
+    fn write_line(&self, _content: &str) {}
+}
+
+#[derive(Debug)]
+struct TestSuite {
+    name: &'static str,
+    cases: Vec<TestCase>,
+}
+
+impl TestSuite {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            cases: Vec::new(),
+        }
+    }
+
+    fn failures(&self) -> usize {
+        self.cases.iter().filter(|case| !case.success).count()
+    }
+}
+
+#[derive(Debug)]
+struct TestCase {
+    version: semver::Version,
+    success: bool,
+    time: Duration,
+    failure: Option<String>,
+}
+
+/// Escapes the characters which have a special meaning in XML, and drops the control characters
+/// which may not occur in an XML document, like the escape codes of colored output.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, JunitPrinter};
+    use crate::config::ModeIntent;
+    use crate::reporter::{Output, ProgressAction};
+    use crate::semver::Version;
+
+    #[test]
+    fn report_with_failure() {
+        let printer = JunitPrinter::new("x86_64-unknown-linux-gnu", Some("cargo check"));
+        let (failed, succeeded) = (Version::new(1, 55, 0), Version::new(1, 56, 0));
+
+        printer.mode(ModeIntent::Find);
+        printer.progress(ProgressAction::Checking(&failed));
+        printer.complete_step(&failed, false);
+        printer.check_failed(&failed, "error[E0658]: `let...else` is <unstable>\n");
+        printer.progress(ProgressAction::Checking(&succeeded));
+        printer.complete_step(&succeeded, true);
+
+        let report = printer.report();
+        let lines = report
+            .lines()
+            .map(|line| line.split(" time=").next().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            vec![
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<testsuites name="cargo-msrv" tests="2" failures="1">"#,
+                r#"  <testsuite name="determine-msrv" tests="2" failures="1">"#,
+                r#"    <properties>"#,
+                r#"      <property name="target" value="x86_64-unknown-linux-gnu"/>"#,
+                r#"      <property name="check_cmd" value="cargo check"/>"#,
+                r#"    </properties>"#,
+                r#"    <testcase name="1.55.0" classname="cargo-msrv.determine-msrv""#,
+                r#"      <failure message="Check for toolchain '1.55.0-x86_64-unknown-linux-gnu' failed">error[E0658]: `let...else` is &lt;unstable&gt;</failure>"#,
+                r#"    </testcase>"#,
+                r#"    <testcase name="1.56.0" classname="cargo-msrv.determine-msrv""#,
+                r#"  </testsuite>"#,
+                r#"</testsuites>"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn escape_control_characters() {
+        assert_eq!(
+            escape("\u{1b}[31merror\u{1b}[0m: \"a\" & 'b'\n"),
+            "[31merror[0m: &quot;a&quot; &amp; &apos;b&apos;\n"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 
 use crate::Config;
 use rust_releases::semver;
@@ -8,7 +8,9 @@ use crate::formatter::{FormatUserOutput, Human, Json};
 use crate::outcome::{FailureOutcome, SuccessOutcome};
 
 pub mod json;
+pub mod junit;
 pub mod no_output;
+pub mod sarif;
 pub mod ui;
 
 #[derive(Debug, Clone, Copy)]
@@ -18,7 +20,11 @@ pub enum ProgressAction<'a> {
     FetchingIndex,
 }
 
-pub trait Output: Debug {
+/// Reports the progress and results of cargo-msrv.
+///
+/// Outputs are `Send`, so cargo-msrv can be run on another thread than the one which created the
+/// output, for example when it's used as a library.
+pub trait Output: Debug + Send {
     // Shows the mode in which cargo-msrv will operate
     fn mode(&self, mode: ModeIntent);
 
@@ -31,12 +37,14 @@ pub trait Output: Debug {
     fn finish_success(&self, mode: ModeIntent, version: Option<&semver::Version>);
     fn finish_failure(&self, mode: ModeIntent, cmd: Option<&str>);
 
+    // Reports the error output of a failed check, after its step was completed
+    fn check_failed(&self, _version: &semver::Version, _error_message: &str) {}
+
     fn write_line(&self, content: &str);
 }
 
 pub mod __private {
-    use std::cell::RefCell;
-    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
 
     use rust_releases::semver;
 
@@ -46,7 +54,7 @@ pub mod __private {
     /// This is meant to be used for testing
     #[derive(Debug)]
     pub struct SuccessOutput {
-        successes: Rc<RefCell<Vec<(bool, semver::Version)>>>,
+        successes: Arc<Mutex<Vec<(bool, semver::Version)>>>,
     }
 
     impl SuccessOutput {
@@ -60,7 +68,7 @@ pub mod __private {
         fn set_steps(&self, _steps: u64) {}
         fn progress(&self, _action: ProgressAction) {}
         fn complete_step(&self, version: &semver::Version, success: bool) {
-            let mut successes = self.successes.borrow_mut();
+            let mut successes = self.successes.lock().unwrap();
             successes.push((success, version.clone()));
         }
         fn finish_success(&self, _mode: ModeIntent, _version: Option<&semver::Version>) {}
@@ -71,13 +79,13 @@ pub mod __private {
     impl Default for SuccessOutput {
         fn default() -> Self {
             Self {
-                successes: Rc::new(RefCell::new(Vec::new())),
+                successes: Arc::new(Mutex::new(Vec::new())),
             }
         }
     }
     impl SuccessOutput {
         pub fn successes(&self) -> Vec<(bool, semver::Version)> {
-            self.successes.clone().borrow().to_owned()
+            self.successes.lock().unwrap().to_owned()
         }
     }
 }
@@ -91,15 +99,7 @@ pub fn write_succeeded_check(
         return;
     }
 
-    match config.output_format() {
-        OutputFormat::Human => {
-            output.write_line(&FormatUserOutput::<Human>::format_line(success_outcome))
-        }
-        OutputFormat::Json => {
-            output.write_line(&FormatUserOutput::<Json>::format_line(success_outcome))
-        }
-        _ => {}
-    };
+    write_report(success_outcome, config, output);
 }
 
 pub fn write_failed_check(failure_outcome: &FailureOutcome, config: &Config, output: &impl Output) {
@@ -107,13 +107,38 @@ pub fn write_failed_check(failure_outcome: &FailureOutcome, config: &Config, out
         return;
     }
 
+    write_report(failure_outcome, config, output);
+}
+
+/// Write a report, like the MSRV of each target or a bump plan, in the output format of the config.
+pub fn write_report<T>(report: &T, config: &Config, output: &impl Output)
+where
+    T: FormatUserOutput<Human> + FormatUserOutput<Json>,
+{
+    write_formatted(
+        report,
+        config,
+        output,
+        <T as FormatUserOutput<Human>>::format_line,
+        <T as FormatUserOutput<Json>>::format_line,
+    );
+}
+
+/// Write the value with the formatter of the output format of the config. Nothing is written for
+/// output formats which are not meant to be read line by line, like JUnit.
+pub(crate) fn write_formatted<T, H: Display, J: Display>(
+    value: T,
+    config: &Config,
+    output: &impl Output,
+    human: impl FnOnce(T) -> H,
+    json: impl FnOnce(T) -> J,
+) {
     match config.output_format() {
-        OutputFormat::Human => {
-            output.write_line(&FormatUserOutput::<Human>::format_line(failure_outcome))
-        }
-        OutputFormat::Json => {
-            output.write_line(&FormatUserOutput::<Json>::format_line(failure_outcome))
-        }
-        _ => {}
+        OutputFormat::Human => output.write_line(&human(value).to_string()),
+        OutputFormat::Json => output.write_line(&json(value).to_string()),
+        OutputFormat::None
+        | OutputFormat::TestSuccesses
+        | OutputFormat::Junit
+        | OutputFormat::Sarif => {}
     };
 }
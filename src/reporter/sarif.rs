@@ -0,0 +1,225 @@
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+use json::JsonValue;
+use rust_releases::semver;
+
+use crate::config::ModeIntent;
+use crate::reporter::ProgressAction;
+
+const RULE_ID: &str = "msrv-not-satisfied";
+
+/// Reports the failed verifications of the MSRV as a SARIF log, so they can be shown as code
+/// scanning alerts. Each result points at the line of the Cargo manifest which specifies the MSRV.
+///
+/// Since a SARIF log should be a single document, nothing is printed while cargo-msrv runs; the
+/// log should be obtained with [`SarifPrinter::report`] once it has finished. When the MSRV is
+/// satisfied, or cargo-msrv ran in another mode, the log contains no results.
+#[derive(Debug)]
+pub struct SarifPrinter<'a> {
+    manifest: PathBuf,
+    cmd: &'a str,
+    last_check: RefCell<Option<(semver::Version, Option<String>)>>,
+    results: RefCell<Vec<JsonValue>>,
+}
+
+impl<'a> SarifPrinter<'a> {
+    /// The manifest is the `Cargo.toml` file of the crate, which is expected to specify the MSRV.
+    pub fn new(manifest: PathBuf, cmd: &'a str) -> Self {
+        Self {
+            manifest,
+            cmd,
+            last_check: RefCell::new(None),
+            results: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// The SARIF log of the failed verifications reported so far.
+    pub fn report(&self) -> String {
+        let mut log = json::object! {
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            version: "2.1.0",
+            runs: [{
+                tool: {
+                    driver: {
+                        name: "cargo-msrv",
+                        version: env!("CARGO_PKG_VERSION"),
+                        informationUri: "https://github.com/foresterre/cargo-msrv",
+                        rules: [{
+                            id: RULE_ID,
+                            shortDescription: {
+                                text: "The crate is not compatible with its MSRV",
+                            },
+                            fullDescription: {
+                                text: "The check command failed for the Rust version given as the MSRV in the Cargo manifest.",
+                            },
+                        }],
+                    },
+                },
+                results: [],
+            }],
+        };
+
+        log["runs"][0]["results"] = JsonValue::Array(self.results.borrow().clone());
+        log.pretty(2)
+    }
+
+    fn verify_failed(&self) {
+        let (version, error_message) = match self.last_check.borrow().clone() {
+            Some(check) => check,
+            None => return,
+        };
+
+        let mut text = format!(
+            "The crate is not compatible with its MSRV {}: the check command `{}` failed",
+            version, self.cmd
+        );
+        if let Some(error_message) = error_message {
+            text.push_str(&format!("\n\n{}", error_message.trim()));
+        }
+
+        let mut physical_location = json::object! {
+            artifactLocation: {
+                uri: artifact_uri(&self.manifest),
+            },
+        };
+
+        if let Some(line) = std::fs::read_to_string(&self.manifest)
+            .ok()
+            .and_then(|contents| msrv_line(&contents))
+        {
+            physical_location["region"] = json::object! { startLine: line };
+        }
+
+        let mut result = json::object! {
+            ruleId: RULE_ID,
+            level: "error",
+            message: {
+                text: text,
+            },
+            locations: [{}],
+        };
+        result["locations"][0]["physicalLocation"] = physical_location;
+
+        self.results.borrow_mut().push(result);
+    }
+}
+
+impl<'a> crate::Output for SarifPrinter<'a> {
+    fn mode(&self, _mode: ModeIntent) {}
+
+    fn set_steps(&self, _steps: u64) {}
+
+    fn progress(&self, _action: ProgressAction) {}
+
+    fn complete_step(&self, version: &semver::Version, _success: bool) {
+        self.last_check.replace(Some((version.clone(), None)));
+    }
+
+    fn finish_success(&self, _mode: ModeIntent, _version: Option<&semver::Version>) {}
+
+    fn finish_failure(&self, mode: ModeIntent, _cmd: Option<&str>) {
+        if let ModeIntent::Verify = mode {
+            self.verify_failed();
+        }
+    }
+
+    fn check_failed(&self, version: &semver::Version, error_message: &str) {
+        self.last_check
+            .replace(Some((version.clone(), Some(error_message.to_string()))));
+    }
+
+    fn write_line(&self, _content: &str) {}
+}
+
+/// The path of the manifest relative to the current folder, which usually is the root of the
+/// repository in CI, and otherwise the absolute path.
+fn artifact_uri(manifest: &Path) -> String {
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|folder| manifest.strip_prefix(folder).ok().map(Path::to_path_buf));
+
+    relative
+        .unwrap_or_else(|| manifest.to_path_buf())
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// The line number of the `rust-version` key in a Cargo manifest, or else of the `msrv` key of
+/// the `package.metadata` table.
+fn msrv_line(manifest: &str) -> Option<usize> {
+    let line_of = |key: &str| {
+        manifest.lines().position(|line| {
+            line.trim_start()
+                .strip_prefix(key)
+                .map_or(false, |rest| rest.trim_start().starts_with('='))
+        })
+    };
+
+    line_of("rust-version")
+        .or_else(|| line_of("msrv"))
+        .map(|index| index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{msrv_line, SarifPrinter};
+    use crate::config::ModeIntent;
+    use crate::reporter::Output;
+    use crate::semver::Version;
+
+    #[yare::parameterized(
+        rust_version = { "[package]\nname = \"a\"\nrust-version = \"1.56\"\n", Some(3) },
+        metadata = { "[package]\nname = \"a\"\n\n[package.metadata]\nmsrv = \"1.56\"\n", Some(5) },
+        other_keys = { "[package]\nrust-version-comment = 1\nmsrv-like = 2\n", None },
+    )]
+    fn line_of_msrv(manifest: &str, expected: Option<usize>) {
+        assert_eq!(msrv_line(manifest), expected);
+    }
+
+    #[test]
+    fn result_for_failed_verification() {
+        let folder = std::env::temp_dir().join("cargo-msrv-test-sarif");
+        std::fs::create_dir_all(&folder).unwrap();
+        let manifest = folder.join("Cargo.toml");
+        std::fs::write(
+            &manifest,
+            "[package]\nname = \"a\"\nrust-version = \"1.56\"\n",
+        )
+        .unwrap();
+
+        let printer = SarifPrinter::new(manifest, "cargo check");
+        let version = Version::new(1, 56, 0);
+
+        printer.mode(ModeIntent::Verify);
+        printer.complete_step(&version, false);
+        printer.check_failed(&version, "error: let-else is unstable\n");
+        printer.finish_failure(ModeIntent::Verify, Some("cargo check"));
+
+        let log = json::parse(&printer.report()).unwrap();
+        let result = &log["runs"][0]["results"][0];
+
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(result["ruleId"], "msrv-not-satisfied");
+        assert_eq!(
+            result["message"]["text"],
+            "The crate is not compatible with its MSRV 1.56.0: the check command `cargo check` failed\n\nerror: let-else is unstable"
+        );
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startLine"],
+            3
+        );
+    }
+
+    #[test]
+    fn no_results_when_satisfied() {
+        let printer = SarifPrinter::new("Cargo.toml".into(), "cargo check");
+
+        printer.mode(ModeIntent::Verify);
+        printer.complete_step(&Version::new(1, 56, 0), true);
+        printer.finish_success(ModeIntent::Verify, Some(&Version::new(1, 56, 0)));
+
+        let log = json::parse(&printer.report()).unwrap();
+        assert!(log["runs"][0]["results"].is_empty());
+    }
+}
@@ -5,6 +5,7 @@ use indicatif::{ProgressBar, ProgressStyle};
 use rust_releases::semver;
 
 use crate::config::ModeIntent;
+use crate::toolchain::toolchain_name;
 
 pub struct HumanPrinter<'a> {
     term: Term,
@@ -43,6 +44,7 @@ impl<'a> HumanPrinter<'a> {
         let verb = match action_intent {
             ModeIntent::Find => "Determining",
             ModeIntent::Verify => "Verifying",
+            ModeIntent::Bump => "Bumping",
             ModeIntent::List | ModeIntent::Set | ModeIntent::Show => "",
         };
 
@@ -71,7 +73,7 @@ impl<'a> HumanPrinter<'a> {
         self.progress.set_message(format!(
             "{} {}",
             style(action).green().bold(),
-            style(version).cyan()
+            style(toolchain_name(version)).cyan()
         ));
     }
 
@@ -90,7 +92,7 @@ impl<'a> HumanPrinter<'a> {
             "{} {} {}",
             style("Finished").green().bold(),
             message,
-            style(version).cyan()
+            style(toolchain_name(version)).cyan()
         ));
     }
 
@@ -157,6 +159,7 @@ impl<'a> crate::Output for HumanPrinter<'a> {
             match mode {
                 ModeIntent::Find => self.finish_with_ok("The MSRV is:", version),
                 ModeIntent::Verify => self.finish_with_ok("Satisfied MSRV check:", version),
+                ModeIntent::Bump => self.finish_with_ok("Bumped MSRV to:", version),
                 ModeIntent::Show => {
                     let _ = self.term.write_line(&format!("{}", version));
                 }
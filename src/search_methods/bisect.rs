@@ -152,12 +152,13 @@ mod tests {
                 Ok(Outcome::Failure(FailureOutcome {
                     toolchain_spec: OwnedToolchainSpec::new(toolchain.version(), config.target()),
                     error_message: "".to_string(),
+                    diagnostics: Vec::new(),
                 }))
             }
         }
     }
 
-    fn fake_config() -> Config<'static> {
+    fn fake_config() -> Config {
         Config::new(ModeIntent::Find, "".to_string())
     }
 
@@ -1,7 +1,11 @@
 use crate::{Config, MinimalCompatibility, Output, TResult};
 use rust_releases::Release;
 
-pub use {bisect::Bisect, linear::Linear};
+pub use {
+    bisect::Bisect,
+    linear::Linear,
+    parallel::{ConcurrentCheck, Parallel},
+};
 
 /// Use a bisection method to find the MSRV. By using a binary search, we halve our search space each
 /// step, making this an efficient search function.
@@ -10,6 +14,9 @@ pub(crate) mod bisect;
 /// not very efficient, but is useful as a baseline, or if you're certain the MSRV is very close to
 /// the head.
 pub(crate) mod linear;
+/// Find the MSRV like the linear search does, but check a batch of toolchains at once. Each check
+/// runs in a separate process, and uses its own target directory.
+pub(crate) mod parallel;
 
 pub trait FindMinimalCapableToolchain {
     /// Method to find the minimum capable toolchain.
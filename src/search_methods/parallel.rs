@@ -0,0 +1,374 @@
+use std::path::PathBuf;
+
+use rust_releases::Release;
+
+use crate::check::{
+    check_command, failure_outcome, install_toolchain, remove_lockfile, uses_json_diagnostics,
+    Check,
+};
+use crate::command::RustupCommand;
+use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
+use crate::lockfile::{LockfileHandler, CARGO_LOCK};
+use crate::outcome::Outcome;
+use crate::paths::crate_root_folder;
+use crate::reporter::{write_failed_check, write_succeeded_check};
+use crate::search_methods::FindMinimalCapableToolchain;
+use crate::toolchain::{OwnedToolchainSpec, ToolchainSpec};
+use crate::{Config, MinimalCompatibility, Output, ProgressAction};
+
+pub struct Parallel<C: Check + Sync> {
+    jobs: usize,
+    runner: C,
+}
+
+impl<C: Check + Sync> Parallel<C> {
+    /// Check up to `jobs` toolchains at once, with the given runner, which is shared by the
+    /// worker threads. The checks are usually run by a [`ConcurrentCheck`].
+    pub fn new(jobs: usize, runner: C) -> Self {
+        Self {
+            jobs: jobs.max(1),
+            runner,
+        }
+    }
+
+    /// Check each release of the batch, and return the outcomes in the order of the batch.
+    ///
+    /// Toolchains are installed one by one on the current thread, since the reporter can not be
+    /// shared with the worker threads. Whether the outcome of a check is cached is only known to
+    /// the runner, so a toolchain is installed even if its check won't run. Only the checks run
+    /// concurrently, and share the lockfile of the crate, see [`SharedLockfile`].
+    fn check_batch(
+        &self,
+        batch: &[Release],
+        config: &Config,
+        output: &impl Output,
+    ) -> TResult<Vec<Outcome>> {
+        if config.install_toolchains() {
+            for release in batch {
+                let toolchain = ToolchainSpec::new(release.version(), config.target());
+                install_toolchain(&toolchain, config, output)?;
+            }
+        }
+
+        if config.ignore_lockfile() {
+            remove_lockfile(config)?;
+        }
+
+        let oldest = batch
+            .iter()
+            .map(Release::version)
+            .min()
+            .map(|version| OwnedToolchainSpec::new(version, config.target()));
+        let lockfile = SharedLockfile::prepare(config, oldest.as_ref())?;
+
+        // Wait for all checks before an error is propagated, so no check is left running. The
+        // checks are joined in the order of the batch, so the outcomes are deterministic.
+        let outcomes = crossbeam_utils::thread::scope(|scope| {
+            let handles = batch
+                .iter()
+                .map(|release| {
+                    scope.spawn(move |_| {
+                        let toolchain = ToolchainSpec::new(release.version(), config.target());
+                        self.runner.check(config, &toolchain)
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .map_err(|_| CargoMSRVError::UnableToRunCheck)
+                        .and_then(|outcome| outcome)
+                })
+                .collect::<Vec<_>>()
+        })
+        .map_err(|_| CargoMSRVError::UnableToRunCheck)?;
+
+        lockfile.restore()?;
+
+        outcomes.into_iter().collect()
+    }
+
+    fn search(
+        &self,
+        search_space: &[Release],
+        config: &Config,
+        output: &impl Output,
+    ) -> TResult<MinimalCompatibility> {
+        let mut last_compatible = None;
+
+        for batch in search_space.chunks(self.jobs) {
+            let outcomes = self.check_batch(batch, config, output)?;
+
+            // Report the outcomes like a linear search would have, i.e. up to and including
+            // the first failure.
+            for outcome in outcomes {
+                output.progress(ProgressAction::Checking(outcome.version()));
+                output.complete_step(outcome.version(), outcome.is_success());
+
+                match outcome {
+                    Outcome::Failure(outcome) => {
+                        output
+                            .check_failed(outcome.toolchain_spec.version(), &outcome.error_message);
+                        write_failed_check(&outcome, config, output);
+                        return Ok(minimum_capable(last_compatible));
+                    }
+                    Outcome::Success(outcome) => {
+                        write_succeeded_check(&outcome, config, output);
+                        last_compatible = Some(outcome.toolchain_spec);
+                    }
+                }
+            }
+        }
+
+        Ok(minimum_capable(last_compatible))
+    }
+}
+
+impl<C: Check + Sync> FindMinimalCapableToolchain for Parallel<C> {
+    fn find_toolchain(
+        &self,
+        search_space: &[Release],
+        config: &Config,
+        output: &impl Output,
+    ) -> TResult<MinimalCompatibility> {
+        info!(
+            jobs = self.jobs,
+            ignore_lockfile_enabled = config.ignore_lockfile()
+        );
+
+        // Temporarily move the lockfile if the user opted to ignore it, and it exists. Unlike the
+        // other search methods, the lockfile is moved once for the whole search, since concurrent
+        // checks share the crate root.
+        let cargo_lock = crate_root_folder(config).map(|p| p.join(CARGO_LOCK))?;
+        let handle_wrap = if config.ignore_lockfile() && cargo_lock.is_file() {
+            Some(LockfileHandler::new(cargo_lock).move_lockfile()?)
+        } else {
+            None
+        };
+
+        let result = self.search(search_space, config, output);
+
+        // move the lockfile back
+        if let Some(handle) = handle_wrap {
+            handle.move_lockfile_back()?;
+        }
+
+        result
+    }
+}
+
+/// A [`Check`] which runs the check command with a target directory of its own, so concurrent
+/// builds do not block on, or clobber, each other's build artifacts.
+///
+/// Unlike [`RunCheck`](crate::check::RunCheck), it does not install the toolchain, nor does it
+/// touch the lockfile, since [`Parallel`] takes care of both for the whole batch. It also does not
+/// report its progress, since the reporter can not be shared with the worker threads.
+#[derive(Debug, Default)]
+pub struct ConcurrentCheck;
+
+impl Check for ConcurrentCheck {
+    fn check(&self, config: &Config, toolchain: &ToolchainSpec) -> TResult<Outcome> {
+        let target_dir = crate_root_folder(config)?
+            .join("target")
+            .join("cargo-msrv")
+            .join(toolchain.spec());
+        let json_diagnostics = uses_json_diagnostics(config);
+
+        let mut cmd = vec![toolchain.spec().to_string()];
+        cmd.extend(check_command(config));
+
+        let mut rustup = RustupCommand::new()
+            .with_args(cmd.iter())
+            .with_optional_dir(config.crate_path())
+            .with_env("CARGO_TARGET_DIR", &target_dir)
+            .with_stderr();
+
+        if json_diagnostics {
+            rustup = rustup.with_stdout();
+        }
+
+        let rustup_output = rustup.run().map_err(|_| CargoMSRVError::UnableToRunCheck)?;
+
+        if rustup_output.exit_status().success() {
+            Ok(Outcome::new_success(toolchain.to_owned()))
+        } else {
+            let stderr = rustup_output.stderr();
+
+            info!(
+                toolchain = toolchain.spec(),
+                stderr,
+                cmd = cmd.join(" ").as_str(),
+                "try_building run failed"
+            );
+
+            Ok(failure_outcome(
+                toolchain.to_owned(),
+                &rustup_output,
+                json_diagnostics,
+            ))
+        }
+    }
+}
+
+/// The lockfile of the crate, which is shared by the concurrent checks of a batch.
+///
+/// A newer Cargo may write a lockfile in a format which an older Cargo can not read. If one of the
+/// checks would write the lockfile while another check reads it, the latter could fail regardless
+/// of whether the crate is compatible with its toolchain. To avoid this, a missing lockfile is
+/// generated by the oldest toolchain of the batch before the checks start, so every check can
+/// read it, and none has to write it. Should a check rewrite the lockfile regardless, it is
+/// restored once all checks of the batch completed, so each batch starts from the same lockfile.
+struct SharedLockfile {
+    path: PathBuf,
+    contents: Option<Vec<u8>>,
+}
+
+impl SharedLockfile {
+    fn prepare(config: &Config, oldest: Option<&OwnedToolchainSpec>) -> TResult<Self> {
+        let path = crate_root_folder(config)?.join(CARGO_LOCK);
+
+        if let Some(toolchain) = oldest.filter(|_| !path.is_file()) {
+            generate_lockfile(config, toolchain);
+        }
+
+        let contents = std::fs::read(&path).ok();
+
+        Ok(Self { path, contents })
+    }
+
+    fn restore(self) -> TResult<()> {
+        let Self { path, contents } = self;
+
+        if std::fs::read(&path).ok() == contents {
+            return Ok(());
+        }
+
+        warn!(
+            lockfile = %path.display(),
+            "lockfile was changed by a concurrent check, restoring it"
+        );
+
+        match contents {
+            Some(contents) => std::fs::write(&path, contents).map_err(|error| CargoMSRVError::Io {
+                error,
+                source: IoErrorSource::WriteFile(path.clone()),
+            }),
+            None => std::fs::remove_file(&path).map_err(|error| CargoMSRVError::Io {
+                error,
+                source: IoErrorSource::RemoveFile(path.clone()),
+            }),
+        }
+    }
+}
+
+/// Generate the lockfile with the given toolchain. When this fails, the checks will report the
+/// failure, so it is only logged.
+fn generate_lockfile(config: &Config, toolchain: &OwnedToolchainSpec) {
+    let generated = RustupCommand::new()
+        .with_args([toolchain.spec(), "cargo", "generate-lockfile"])
+        .with_optional_dir(config.crate_path())
+        .with_stderr()
+        .run();
+
+    match generated {
+        Ok(output) if output.exit_status().success() => {
+            info!(toolchain = toolchain.spec(), "generated lockfile");
+        }
+        Ok(output) => {
+            info!(
+                toolchain = toolchain.spec(),
+                stderr = output.stderr(),
+                "unable to generate lockfile"
+            );
+        }
+        Err(err) => warn!(%err, "unable to generate lockfile"),
+    }
+}
+
+fn minimum_capable(toolchain: Option<OwnedToolchainSpec>) -> MinimalCompatibility {
+    toolchain.map_or(MinimalCompatibility::NoCompatibleToolchains, |toolchain| {
+        MinimalCompatibility::CapableToolchain { toolchain }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Parallel;
+    use crate::check::Check;
+    use crate::config::{ConfigBuilder, ReleaseSource};
+    use crate::lockfile::CARGO_LOCK;
+    use crate::outcome::Outcome;
+    use crate::reporter::no_output::NoOutput;
+    use crate::search_methods::FindMinimalCapableToolchain;
+    use crate::semver::Version;
+    use crate::toolchain::ToolchainSpec;
+    use crate::{Config, MinimalCompatibility, ModeIntent, TResult};
+    use rust_releases::Release;
+    use std::sync::Mutex;
+
+    /// Succeeds for the toolchains from the given version, and records the checked versions.
+    struct FakeRunner {
+        oldest_compatible: Version,
+        checked: Mutex<Vec<Version>>,
+    }
+
+    impl Check for FakeRunner {
+        fn check(&self, _config: &Config, toolchain: &ToolchainSpec) -> TResult<Outcome> {
+            self.checked
+                .lock()
+                .unwrap()
+                .push(toolchain.version().clone());
+
+            if toolchain.version() >= &self.oldest_compatible {
+                Ok(Outcome::new_success(toolchain.to_owned()))
+            } else {
+                Ok(Outcome::new_failure(toolchain.to_owned(), String::new()))
+            }
+        }
+    }
+
+    #[test]
+    fn checks_batches_with_runner() {
+        let crate_root = std::env::temp_dir().join("cargo-msrv-test-parallel-runner");
+        std::fs::create_dir_all(&crate_root).unwrap();
+        std::fs::write(crate_root.join(CARGO_LOCK), "version = 3\n").unwrap();
+
+        let config = ConfigBuilder::new(ModeIntent::Find, "x86_64-unknown-linux-gnu")
+            .crate_path(Some(&crate_root))
+            .release_source(ReleaseSource::Installed)
+            .build();
+
+        let releases = (50..=58)
+            .rev()
+            .map(|minor| Release::new_stable(Version::new(1, minor, 0)))
+            .collect::<Vec<_>>();
+
+        let runner = FakeRunner {
+            oldest_compatible: Version::new(1, 55, 0),
+            checked: Mutex::default(),
+        };
+        let method = Parallel::new(3, runner);
+
+        let compatibility = method
+            .find_toolchain(&releases, &config, &NoOutput)
+            .unwrap();
+
+        match compatibility {
+            MinimalCompatibility::CapableToolchain { toolchain } => {
+                assert_eq!(toolchain.version(), &Version::new(1, 55, 0));
+            }
+            MinimalCompatibility::NoCompatibleToolchains => panic!("expected a capable toolchain"),
+        }
+
+        // The search stops after the batch of the first incompatible toolchain
+        let mut checked = method.runner.checked.into_inner().unwrap();
+        checked.sort();
+        let expected = (53..=58)
+            .map(|minor| Version::new(1, minor, 0))
+            .collect::<Vec<_>>();
+        assert_eq!(checked, expected);
+    }
+}
@@ -0,0 +1,141 @@
+use proc_macro2::{Delimiter, TokenTree};
+
+use crate::static_msrv::syntax::{is_group, is_ident, is_punct};
+use crate::static_msrv::Usage;
+
+/// How the use of a standard library API is recognized in the tokens of a source file.
+#[derive(Clone, Copy, Debug)]
+enum Pattern {
+    /// A method call, like `.is_some_and(...)`.
+    Method(&'static str),
+    /// The last two segments of a path, like `thread::scope`.
+    Path(&'static str, &'static str),
+    /// The name of a type or trait, like `OnceLock`.
+    Type(&'static str),
+    /// A macro invocation, like `matches!(...)`.
+    Macro(&'static str),
+}
+
+use Pattern::{Macro, Method, Path, Type};
+
+/// Standard library APIs, and the Rust release in which they were stabilized.
+///
+/// Like the language features, this is not an exhaustive list. Only APIs with a name which is
+/// distinctive enough to not be confused with the items of other crates are included. Methods
+/// which share their name with an older method of another standard library type, like
+/// `str::strip_prefix` and `Path::strip_prefix`, are left out.
+const STABILIZED_APIS: &[(&str, Pattern, (u64, u64))] = &[
+    ("TryFrom", Type("TryFrom"), (1, 34)),
+    ("TryInto", Type("TryInto"), (1, 34)),
+    ("std::iter::from_fn", Path("iter", "from_fn"), (1, 34)),
+    ("std::iter::successors", Path("iter", "successors"), (1, 34)),
+    ("i32::div_euclid", Method("div_euclid"), (1, 38)),
+    ("todo!", Macro("todo"), (1, 40)),
+    ("std::mem::take", Path("mem", "take"), (1, 40)),
+    ("Option::as_deref", Method("as_deref"), (1, 40)),
+    ("matches!", Macro("matches"), (1, 42)),
+    ("std::ptr::addr_of", Path("ptr", "addr_of"), (1, 51)),
+    ("i32::unsigned_abs", Method("unsigned_abs"), (1, 51)),
+    ("str::split_once", Method("split_once"), (1, 52)),
+    ("str::rsplit_once", Method("rsplit_once"), (1, 52)),
+    ("std::ops::ControlFlow", Type("ControlFlow"), (1, 55)),
+    (
+        "Option::unwrap_unchecked",
+        Method("unwrap_unchecked"),
+        (1, 58),
+    ),
+    ("std::iter::zip", Path("iter", "zip"), (1, 59)),
+    (
+        "std::thread::available_parallelism",
+        Path("thread", "available_parallelism"),
+        (1, 59),
+    ),
+    ("i32::abs_diff", Method("abs_diff"), (1, 60)),
+    ("Rc::new_cyclic", Path("Rc", "new_cyclic"), (1, 60)),
+    ("Arc::new_cyclic", Path("Arc", "new_cyclic"), (1, 60)),
+    ("Vec::retain_mut", Method("retain_mut"), (1, 61)),
+    ("bool::then_some", Method("then_some"), (1, 62)),
+    ("std::thread::scope", Path("thread", "scope"), (1, 63)),
+    ("std::array::from_fn", Path("array", "from_fn"), (1, 63)),
+    (
+        "std::io::read_to_string",
+        Path("io", "read_to_string"),
+        (1, 65),
+    ),
+    ("std::hint::black_box", Path("hint", "black_box"), (1, 66)),
+    ("u32::ilog2", Method("ilog2"), (1, 67)),
+    ("u32::ilog10", Method("ilog10"), (1, 67)),
+    ("std::pin::pin!", Path("pin", "pin"), (1, 68)),
+    ("Option::is_some_and", Method("is_some_and"), (1, 70)),
+    ("Result::is_ok_and", Method("is_ok_and"), (1, 70)),
+    ("Result::is_err_and", Method("is_err_and"), (1, 70)),
+    ("std::sync::OnceLock", Type("OnceLock"), (1, 70)),
+    ("std::cell::OnceCell", Path("cell", "OnceCell"), (1, 70)),
+    ("std::io::IsTerminal", Type("IsTerminal"), (1, 70)),
+    ("u32::div_ceil", Method("div_ceil"), (1, 73)),
+    ("u32::next_multiple_of", Method("next_multiple_of"), (1, 73)),
+    ("std::num::Saturating", Type("Saturating"), (1, 74)),
+    ("std::ptr::from_ref", Path("ptr", "from_ref"), (1, 76)),
+    ("Result::inspect_err", Method("inspect_err"), (1, 76)),
+    ("std::mem::offset_of!", Path("mem", "offset_of"), (1, 77)),
+    ("slice::first_chunk", Method("first_chunk"), (1, 77)),
+    ("std::num::NonZero", Type("NonZero"), (1, 79)),
+    ("std::sync::LazyLock", Type("LazyLock"), (1, 80)),
+    ("std::cell::LazyCell", Type("LazyCell"), (1, 80)),
+    ("Option::take_if", Method("take_if"), (1, 80)),
+    ("slice::trim_ascii", Method("trim_ascii"), (1, 80)),
+    ("Option::is_none_or", Method("is_none_or"), (1, 82)),
+    ("slice::is_sorted", Method("is_sorted"), (1, 82)),
+    ("std::iter::repeat_n", Path("iter", "repeat_n"), (1, 82)),
+    (
+        "Option::get_or_insert_default",
+        Method("get_or_insert_default"),
+        (1, 83),
+    ),
+    ("u32::isqrt", Method("isqrt"), (1, 84)),
+    ("Vec::pop_if", Method("pop_if"), (1, 86)),
+    (
+        "slice::get_disjoint_mut",
+        Method("get_disjoint_mut"),
+        (1, 86),
+    ),
+    ("Vec::extract_if", Method("extract_if"), (1, 87)),
+];
+
+/// Records the standard library APIs used by the token at index `i`.
+pub(super) fn detect(tokens: &[TokenTree], i: usize, usages: &mut Vec<Usage>) {
+    let ident = match &tokens[i] {
+        TokenTree::Ident(ident) => ident,
+        _ => return,
+    };
+
+    let next = |n: usize| tokens.get(i + n);
+    let previous = |n: usize| i.checked_sub(n).and_then(|j| tokens.get(j));
+
+    for &(name, pattern, (major, minor)) in STABILIZED_APIS {
+        let used = match pattern {
+            Method(method) => {
+                ident == method
+                    && is_punct(previous(1), '.')
+                    && (is_group(next(1), Delimiter::Parenthesis) || is_punct(next(1), ':'))
+            }
+            Path(parent, item) => {
+                ident == item
+                    && is_punct(previous(1), ':')
+                    && is_punct(previous(2), ':')
+                    && is_ident(previous(3), parent)
+            }
+            Type(type_name) => ident == type_name && !is_punct(previous(1), '.'),
+            Macro(macro_name) => {
+                ident == macro_name
+                    && is_punct(next(1), '!')
+                    && matches!(next(2), Some(TokenTree::Group(_)))
+            }
+        };
+
+        if used {
+            let ambiguous = matches!(pattern, Method(_));
+            usages.push(Usage::api(name, major, minor, ident.span(), ambiguous));
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use std::path::{Path, PathBuf};
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, ContentArrangement, Table};
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use rust_releases::semver;
+use serde::Serialize;
+
+use crate::errors::TResult;
+use crate::formatter::{FormatUserOutput, Human, Json};
+use crate::language_features::stabilized_in;
+use crate::reporter::json::Event;
+use crate::static_msrv::syntax::{is_group, is_ident, is_punct};
+use crate::static_msrv::{read_tokens, source_files};
+use crate::toolchain::toolchain_name;
+
+/// Find the unstable features which the crate at the given crate root enables with
+/// `#![feature(...)]` attributes, including those within a `#![cfg_attr(..., feature(...))]`.
+///
+/// Each feature is listed once, at its first occurrence.
+pub fn feature_gates(crate_root: &Path) -> TResult<Vec<FeatureGate>> {
+    let mut gates: Vec<FeatureGate> = Vec::new();
+
+    for file in source_files(crate_root)? {
+        let path = file.strip_prefix(crate_root).unwrap_or(&file).to_path_buf();
+
+        for (name, line) in enabled_features(read_tokens(&file)?) {
+            if gates.iter().all(|gate| gate.name != name) {
+                gates.push(FeatureGate {
+                    stabilized_in: stabilized_in(&name),
+                    name,
+                    path: path.clone(),
+                    line,
+                });
+            }
+        }
+    }
+
+    Ok(gates)
+}
+
+/// The features enabled by the inner attributes of a source file, and the line at which each
+/// is enabled.
+fn enabled_features(stream: TokenStream) -> Vec<(String, usize)> {
+    let tokens = stream.into_iter().collect::<Vec<_>>();
+    let mut features = Vec::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        let previous = |n: usize| i.checked_sub(n).and_then(|j| tokens.get(j));
+
+        match token {
+            TokenTree::Group(group)
+                if group.delimiter() == Delimiter::Bracket
+                    && is_punct(previous(2), '#')
+                    && is_punct(previous(1), '!') =>
+            {
+                let inner = group.stream().into_iter().collect::<Vec<_>>();
+                attribute_features(&inner, &mut features);
+            }
+            _ => {}
+        }
+    }
+
+    features
+}
+
+/// Collects the features of a `feature(...)` attribute, which may be nested in a `cfg_attr`.
+fn attribute_features(tokens: &[TokenTree], features: &mut Vec<(String, usize)>) {
+    for (i, token) in tokens.iter().enumerate() {
+        if let TokenTree::Group(group) = token {
+            let inner = group.stream().into_iter().collect::<Vec<_>>();
+
+            if is_ident(i.checked_sub(1).and_then(|j| tokens.get(j)), "feature")
+                && is_group(Some(token), Delimiter::Parenthesis)
+            {
+                features.extend(inner.iter().filter_map(|token| match token {
+                    TokenTree::Ident(ident) => Some((ident.to_string(), ident.span().start().line)),
+                    _ => None,
+                }));
+            } else {
+                attribute_features(&inner, features);
+            }
+        }
+    }
+}
+
+/// An unstable feature enabled by a `#![feature(...)]` attribute.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeatureGate {
+    name: String,
+    stabilized_in: Option<semver::Version>,
+    path: PathBuf,
+    line: usize,
+}
+
+impl FeatureGate {
+    /// The name of the feature gate, like `let_chains`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The Rust release in which the feature was stabilized, or `None` if the feature is not
+    /// known to be stabilized.
+    pub fn stabilized_in(&self) -> Option<&semver::Version> {
+        self.stabilized_in.as_ref()
+    }
+
+    /// The source file in which the feature is enabled, relative to the crate root.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+/// The unstable features enabled by a crate, together with the oldest nightly toolchain on which
+/// the crate compiles.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnstableFeatures {
+    features: Vec<FeatureGate>,
+    nightly: Option<semver::Version>,
+}
+
+impl UnstableFeatures {
+    pub fn new(features: Vec<FeatureGate>, nightly: Option<semver::Version>) -> Self {
+        Self { features, nightly }
+    }
+
+    pub fn features(&self) -> &[FeatureGate] {
+        &self.features
+    }
+
+    /// The version of the oldest nightly toolchain on which the crate compiles, or `None` if
+    /// none of the searched nightly toolchains is compatible.
+    pub fn nightly(&self) -> Option<&semver::Version> {
+        self.nightly.as_ref()
+    }
+
+    /// The Rust release since which all enabled features are stable, and the crate can thus move
+    /// to the stable channel once the feature attributes are removed. Returns `None` if any of the
+    /// features is not known to be stabilized, or when the crate enables no features.
+    pub fn stable_since(&self) -> Option<&semver::Version> {
+        self.features
+            .iter()
+            .map(FeatureGate::stabilized_in)
+            .collect::<Option<Vec<_>>>()
+            .and_then(|versions| versions.into_iter().max())
+    }
+
+    fn unstable(&self) -> Vec<&str> {
+        self.features
+            .iter()
+            .filter(|feature| feature.stabilized_in.is_none())
+            .map(FeatureGate::name)
+            .collect()
+    }
+}
+
+impl FormatUserOutput<Human> for UnstableFeatures {
+    fn format_line(&self) -> String {
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec!["Feature", "Stabilized in", "Enabled at"]);
+
+        for feature in &self.features {
+            table.add_row(vec![
+                Cell::new(feature.name.as_str()),
+                Cell::new(
+                    feature
+                        .stabilized_in
+                        .as_ref()
+                        .map_or_else(|| "unknown".to_string(), ToString::to_string),
+                ),
+                Cell::new(format!("{}:{}", feature.path.display(), feature.line)),
+            ]);
+        }
+
+        let nightly = match &self.nightly {
+            Some(version) => format!("The crate compiles since {}", toolchain_name(version)),
+            None => "None of the searched nightly toolchains compiles the crate".to_string(),
+        };
+
+        let stable = match (self.features.is_empty(), self.stable_since()) {
+            (true, _) => "No feature attributes were found".to_string(),
+            (false, Some(version)) => format!(
+                "All features are stable since Rust {}, so the crate can move to the stable channel",
+                version
+            ),
+            (false, None) => format!(
+                "Not known to be stabilized: {}",
+                self.unstable().join(", ")
+            ),
+        };
+
+        format!("{}\n{}\n{}", table, nightly, stable)
+    }
+}
+
+#[derive(Serialize)]
+struct UnstableFeaturesEvent<'a> {
+    nightly: Option<String>,
+    stable_since: Option<String>,
+    features: Vec<FeatureGateFields<'a>>,
+}
+
+#[derive(Serialize)]
+struct FeatureGateFields<'a> {
+    feature: &'a str,
+    stabilized_in: Option<String>,
+    path: String,
+    line: usize,
+}
+
+impl FormatUserOutput<Json> for UnstableFeatures {
+    fn format_line(&self) -> String {
+        let features = self
+            .features
+            .iter()
+            .map(|feature| FeatureGateFields {
+                feature: &feature.name,
+                stabilized_in: feature.stabilized_in.as_ref().map(ToString::to_string),
+                path: format!("{}", feature.path.display()),
+                line: feature.line,
+            })
+            .collect();
+
+        let event = UnstableFeaturesEvent {
+            nightly: self.nightly.as_ref().map(toolchain_name),
+            stable_since: self.stable_since().map(ToString::to_string),
+            features,
+        };
+
+        Event::new("unstable-features", event).to_line()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{enabled_features, feature_gates, UnstableFeatures};
+    use proc_macro2::TokenStream;
+    use rust_releases::semver::Version;
+    use std::str::FromStr;
+
+    fn features(source: &str) -> Vec<String> {
+        enabled_features(TokenStream::from_str(source).unwrap())
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    #[yare::parameterized(
+        single = { "#![feature(let_chains)]", &["let_chains"] },
+        several = { "#![feature(let_chains, never_type)]", &["let_chains", "never_type"] },
+        cfg_attr = { "#![cfg_attr(nightly, feature(never_type))]", &["never_type"] },
+        outer_attribute = { "#[feature(let_chains)] fn f() {}", &[] },
+        cfg_feature = { "#![cfg(feature = \"std\")]", &[] },
+        other_attribute = { "#![allow(dead_code)]", &[] },
+    )]
+    fn detects(source: &str, expected: &[&str]) {
+        assert_eq!(features(source), expected);
+    }
+
+    #[test]
+    fn feature_gates_of_crate_sources() {
+        let root = std::env::temp_dir().join("cargo-msrv-test-feature-gates");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src")).unwrap();
+
+        std::fs::write(
+            root.join("src").join("lib.rs"),
+            "#![feature(let_chains)]\n#![feature(never_type, let_chains)]\n\nmod nested;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src").join("nested.rs"),
+            "#![cfg_attr(test, feature(let_else))]\n",
+        )
+        .unwrap();
+
+        let gates = feature_gates(&root).unwrap();
+
+        let names = gates.iter().map(|gate| gate.name()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["let_chains", "never_type", "let_else"]);
+        assert_eq!(gates[1].line(), 2);
+        assert_eq!(gates[1].stabilized_in(), None);
+
+        let report = UnstableFeatures::new(gates.clone(), None);
+        assert_eq!(report.stable_since(), None);
+
+        let stable = gates
+            .into_iter()
+            .filter(|gate| gate.name() != "never_type")
+            .collect();
+        let report = UnstableFeatures::new(stable, None);
+        assert_eq!(report.stable_since(), Some(&Version::new(1, 88, 0)));
+    }
+}
@@ -0,0 +1,432 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, ContentArrangement, Table};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use rust_releases::semver;
+use serde::Serialize;
+
+use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
+use crate::formatter::{FormatUserOutput, Human, Json};
+use crate::language_features::stabilized_in;
+use crate::reporter::json::Event;
+use crate::static_msrv::syntax::Context;
+
+pub use feature_gates::{feature_gates, FeatureGate, UnstableFeatures};
+
+mod apis;
+mod feature_gates;
+mod syntax;
+
+/// Determine a lower bound on the MSRV of the crate at the given crate root, without running
+/// any toolchain.
+///
+/// The Rust source files of the `src` folder, and the build script, are tokenized, and the syntax
+/// and standard library APIs they use are looked up in a table of the Rust release in which each
+/// was stabilized. Since the tables are not exhaustive, and items of other crates may share the
+/// names of standard library APIs, the result is an estimate, which the true MSRV may exceed.
+///
+/// Source files which can not be tokenized are skipped.
+pub fn static_msrv(crate_root: &Path) -> TResult<StaticMsrv> {
+    let files = source_files(crate_root)?;
+
+    // the first usage of each feature, and the number of times it was used
+    let mut features: BTreeMap<&'static str, FeatureUsage> = BTreeMap::new();
+
+    for file in &files {
+        let path = file.strip_prefix(crate_root).unwrap_or(file).to_path_buf();
+
+        for usage in usages(read_tokens(file)?) {
+            features
+                .entry(usage.name)
+                .and_modify(|feature| feature.uses += 1)
+                .or_insert_with(|| FeatureUsage {
+                    name: usage.name.to_string(),
+                    rust_version: usage.rust_version,
+                    path: path.clone(),
+                    line: usage.line,
+                    uses: 1,
+                    ambiguous: usage.ambiguous,
+                });
+        }
+    }
+
+    let mut features = features
+        .into_iter()
+        .map(|(_, feature)| feature)
+        .collect::<Vec<_>>();
+    features.sort_by(|lhs, rhs| rhs.rust_version.cmp(&lhs.rust_version));
+
+    Ok(StaticMsrv {
+        files: files.len(),
+        features,
+    })
+}
+
+/// The Rust source files of the `src` folder, and the build script, of the crate at the given
+/// crate root.
+fn source_files(crate_root: &Path) -> TResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    let build_script = crate_root.join("build.rs");
+    if build_script.is_file() {
+        files.push(build_script);
+    }
+
+    let src = crate_root.join("src");
+    if src.is_dir() {
+        collect_source_files(&src, &mut files)?;
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+fn collect_source_files(folder: &Path, files: &mut Vec<PathBuf>) -> TResult<()> {
+    let entries = std::fs::read_dir(folder).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFolder(folder.to_path_buf()),
+    })?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            collect_source_files(&path, files)?;
+        } else if path
+            .extension()
+            .map_or(false, |extension| extension == "rs")
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// The tokens of a source file, or an empty token stream if the file can not be tokenized.
+fn read_tokens(file: &Path) -> TResult<TokenStream> {
+    let contents = std::fs::read_to_string(file).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFile(file.to_path_buf()),
+    })?;
+
+    match TokenStream::from_str(&contents) {
+        Ok(stream) => Ok(stream),
+        Err(err) => {
+            warn!(file = %file.display(), ?err, "unable to tokenize source file, skipping it");
+            Ok(TokenStream::new())
+        }
+    }
+}
+
+/// The language features and standard library APIs used in the given tokens.
+fn usages(stream: TokenStream) -> Vec<Usage> {
+    let mut usages = Vec::new();
+    visit(
+        &stream.into_iter().collect::<Vec<_>>(),
+        Context::Other,
+        &mut usages,
+    );
+    usages
+}
+
+fn visit(tokens: &[TokenTree], context: Context, usages: &mut Vec<Usage>) {
+    for (i, token) in tokens.iter().enumerate() {
+        syntax::detect(tokens, i, context, usages);
+        apis::detect(tokens, i, usages);
+
+        if let TokenTree::Group(group) = token {
+            let inner = group.stream().into_iter().collect::<Vec<_>>();
+            visit(&inner, syntax::group_context(tokens, i), usages);
+        }
+    }
+}
+
+/// The use of a language feature, or of a standard library API, in a source file.
+#[derive(Clone, Debug)]
+struct Usage {
+    name: &'static str,
+    rust_version: semver::Version,
+    line: usize,
+    ambiguous: bool,
+}
+
+impl Usage {
+    /// The use of the language feature with the given feature gate name, which must be listed in
+    /// the table of stabilized language features.
+    fn language_feature(feature: &'static str, span: Span) -> Self {
+        Self {
+            name: feature,
+            rust_version: stabilized_in(feature).expect("stabilized language feature"),
+            line: span.start().line,
+            ambiguous: false,
+        }
+    }
+
+    /// The use of a standard library API. It's ambiguous when the API was only recognized by the
+    /// name of a method, which methods of other types may share.
+    fn api(name: &'static str, major: u64, minor: u64, span: Span, ambiguous: bool) -> Self {
+        Self {
+            name,
+            rust_version: semver::Version::new(major, minor, 0),
+            line: span.start().line,
+            ambiguous,
+        }
+    }
+}
+
+/// The language features and standard library APIs used by a crate, and the lower bound on the
+/// MSRV which follows from them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StaticMsrv {
+    files: usize,
+    features: Vec<FeatureUsage>,
+}
+
+impl StaticMsrv {
+    /// The greatest Rust version required by the used features, or `None` if none of the used
+    /// features are known.
+    pub fn lower_bound(&self) -> Option<&semver::Version> {
+        self.features.first().map(|feature| &feature.rust_version)
+    }
+
+    /// Like [`StaticMsrv::lower_bound`], but ignoring the APIs which were only recognized by the
+    /// name of a method. This lower bound is used to raise the minimum version of a search.
+    pub fn unambiguous_lower_bound(&self) -> Option<&semver::Version> {
+        self.features
+            .iter()
+            .find(|feature| !feature.ambiguous)
+            .map(|feature| &feature.rust_version)
+    }
+
+    /// The used features, from the most to the least recently stabilized.
+    pub fn features(&self) -> &[FeatureUsage] {
+        &self.features
+    }
+}
+
+/// A language feature or standard library API, where it was first used, and how often.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeatureUsage {
+    name: String,
+    rust_version: semver::Version,
+    path: PathBuf,
+    line: usize,
+    uses: usize,
+    ambiguous: bool,
+}
+
+impl FeatureUsage {
+    /// The feature gate name of a language feature, like `let_else`, or the path of a standard
+    /// library API, like `Option::is_some_and`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The Rust release in which the feature was stabilized.
+    pub fn rust_version(&self) -> &semver::Version {
+        &self.rust_version
+    }
+
+    /// The source file in which the feature was first used, relative to the crate root.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn uses(&self) -> usize {
+        self.uses
+    }
+}
+
+impl FormatUserOutput<Human> for StaticMsrv {
+    fn format_line(&self) -> String {
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec!["Feature", "Stabilized in", "First used at", "Uses"]);
+
+        for feature in &self.features {
+            table.add_row(vec![
+                Cell::new(feature.name.as_str()),
+                Cell::new(feature.rust_version.to_string()),
+                Cell::new(format!("{}:{}", feature.path.display(), feature.line)),
+                Cell::new(feature.uses),
+            ]);
+        }
+
+        let summary = match self.lower_bound() {
+            Some(version) => format!(
+                "The MSRV is at least {}, based on {} source files",
+                version, self.files
+            ),
+            None => format!(
+                "No features with a known Rust version were found in {} source files",
+                self.files
+            ),
+        };
+
+        format!("{}\n{}", table, summary)
+    }
+}
+
+#[derive(Serialize)]
+struct StaticMsrvEvent<'a> {
+    lower_bound: Option<String>,
+    files: usize,
+    features: Vec<StaticFeatureFields<'a>>,
+}
+
+#[derive(Serialize)]
+struct StaticFeatureFields<'a> {
+    feature: &'a str,
+    rust_version: String,
+    path: String,
+    line: usize,
+    uses: usize,
+}
+
+impl FormatUserOutput<Json> for StaticMsrv {
+    fn format_line(&self) -> String {
+        let features = self
+            .features
+            .iter()
+            .map(|feature| StaticFeatureFields {
+                feature: &feature.name,
+                rust_version: feature.rust_version.to_string(),
+                path: format!("{}", feature.path.display()),
+                line: feature.line,
+                uses: feature.uses,
+            })
+            .collect();
+
+        let event = StaticMsrvEvent {
+            lower_bound: self.lower_bound().map(ToString::to_string),
+            files: self.files,
+            features,
+        };
+
+        Event::new("static-msrv", event).to_line()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{static_msrv, usages};
+    use crate::formatter::{FormatUserOutput, Json};
+    use crate::reporter::json::schema::assert_conforms;
+    use proc_macro2::TokenStream;
+    use rust_releases::semver::Version;
+    use std::str::FromStr;
+
+    fn features(source: &str) -> Vec<&'static str> {
+        usages(TokenStream::from_str(source).unwrap())
+            .into_iter()
+            .map(|usage| usage.name)
+            .collect()
+    }
+
+    #[yare::parameterized(
+        let_else = { "fn f() { let Some(x) = y else { return; }; }", "let_else" },
+        let_chains = { "fn f() { if let Some(x) = y && x > 1 {} }", "let_chains" },
+        generic_associated_types = { "trait T { type Item<'a> where Self: 'a; }", "generic_associated_types" },
+        impl_trait_argument = { "fn f(x: impl Display) {}", "universal_impl_trait" },
+        impl_trait_return = { "fn f<T: Fn() -> u8>(t: T) -> impl Display { 1 }", "conservative_impl_trait" },
+        impl_trait_in_trait = { "trait T { fn f(&self) -> impl Display; }", "return_position_impl_trait_in_trait" },
+        async_fn_in_trait = { "trait T { async fn f(&self); }", "async_fn_in_trait" },
+        await_ = { "async fn f() { g().await; }", "async_await" },
+        const_generics = { "struct A<T, const N: usize>([T; N]);", "min_const_generics" },
+        const_generics_defaults = { "struct A<const N: usize = 1>;", "const_generics_defaults" },
+        inline_const = { "fn f() { let x = const { 1 + 1 }; }", "inline_const" },
+        label_break_value = { "fn f() { 'done: { break 'done; } }", "label_break_value" },
+        format_args_capture = { "fn f() { println!(\"{name:>8}\"); }", "format_args_capture" },
+        format_args_capture_write = { "fn f() { write!(f, \"{name}\"); }", "format_args_capture" },
+        format_args_capture_assert_eq = { "fn f() { assert_eq!(a, b, \"{name}\"); }", "format_args_capture" },
+        lint_reasons = { "#[expect(dead_code)] fn f() {}", "lint_reasons" },
+        is_some_and = { "fn f() { x.is_some_and(|v| v > 1); }", "Option::is_some_and" },
+        thread_scope = { "fn f() { std::thread::scope(|s| {}); }", "std::thread::scope" },
+        once_lock = { "static A: OnceLock<u8> = OnceLock::new();", "std::sync::OnceLock" },
+    )]
+    fn detects(source: &str, expected: &str) {
+        assert!(features(source).contains(&expected));
+    }
+
+    #[yare::parameterized(
+        if_else = { "fn f() { let x = if y { 1 } else { 2 }; }", "let_else" },
+        if_let = { "fn f() { if let Some(x) = y { } else { } }", "let_else" },
+        inherent_type_alias = { "type Alias<T> = Vec<T>;", "generic_associated_types" },
+        impl_trait_in_inherent_impl = { "impl A { fn f(&self) -> impl Display { 1 } }", "return_position_impl_trait_in_trait" },
+        positional_arguments = { "fn f() { println!(\"{} {0} {{escaped}}\", 1); }", "format_args_capture" },
+        named_arguments = { "fn f() { println!(\"{name}\", name = 1); }", "format_args_capture" },
+        string_argument = { "fn f() { println!(\"{}\", \"{name}\"); }", "format_args_capture" },
+        assert_eq_operand = { "fn f() { assert_eq!(x, \"{name}\"); }", "format_args_capture" },
+        assert_condition = { "fn f() { assert!(x == \"{name}\"); }", "format_args_capture" },
+        write_argument = { "fn f() { write!(f, \"{}\", \"{name}\"); }", "format_args_capture" },
+        thread_local_const = { "thread_local! { static A: u8 = const { 1 }; }", "inline_const" },
+        unsized_bound = { "fn f<T: ?Sized>() {}", "question_mark" },
+        comment = { "// x.is_some_and(f)\nfn f() {}", "Option::is_some_and" },
+        user_method_path = { "fn f() { Foo::is_some_and(x); }", "Option::is_some_and" },
+        path_strip_prefix = { "fn f(p: &Path) { p.strip_prefix(q); }", "str::strip_prefix" },
+    )]
+    fn does_not_detect(source: &str, unexpected: &str) {
+        assert!(!features(source).contains(&unexpected));
+    }
+
+    #[test]
+    fn lower_bound_of_crate_sources() {
+        let root = std::env::temp_dir().join("cargo-msrv-test-static-msrv");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src").join("nested")).unwrap();
+        std::fs::create_dir_all(root.join("tests")).unwrap();
+
+        std::fs::write(
+            root.join("src").join("lib.rs"),
+            "mod nested;\n\npub fn f(x: Option<u8>) -> bool {\n    x.is_some_and(|x| x > 1)\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src").join("nested").join("mod.rs"),
+            "pub fn g(x: Option<u8>) -> u8 {\n    let Some(x) = x else { return 0 };\n    x\n}\n",
+        )
+        .unwrap();
+        // only the sources of the library and binaries are considered
+        std::fs::write(
+            root.join("tests").join("t.rs"),
+            "fn t() { let _ = std::sync::LazyLock::new(|| 1); }",
+        )
+        .unwrap();
+
+        let msrv = static_msrv(&root).unwrap();
+
+        assert_eq!(msrv.lower_bound(), Some(&Version::new(1, 70, 0)));
+        // `is_some_and` is only recognized by the name of the method
+        assert_eq!(
+            msrv.unambiguous_lower_bound(),
+            Some(&Version::new(1, 65, 0))
+        );
+
+        let names = msrv
+            .features()
+            .iter()
+            .map(|feature| feature.name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Option::is_some_and", "let_else"]);
+
+        let first = &msrv.features()[0];
+        assert_eq!(first.path(), std::path::Path::new("src").join("lib.rs"));
+        assert_eq!(first.line(), 4);
+        assert_eq!(first.uses(), 1);
+
+        let event = assert_conforms(&FormatUserOutput::<Json>::format_line(&msrv));
+        assert_eq!(event["lower_bound"], "1.70.0");
+        assert_eq!(event["features"][0]["line"], 4);
+    }
+}
@@ -0,0 +1,429 @@
+use proc_macro2::{Delimiter, Group, Spacing, Span, TokenTree};
+
+use crate::static_msrv::Usage;
+
+/// The kind of item whose body holds the tokens, which changes the meaning of some syntax. For
+/// example, `async fn` may only be used in the body of a trait since Rust 1.75.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum Context {
+    Trait,
+    TraitImpl,
+    InherentImpl,
+    ThreadLocal,
+    Other,
+}
+
+/// The macros which accept a format string, and may capture variables by their name.
+const FORMAT_MACROS: &[&str] = &[
+    "format",
+    "format_args",
+    "print",
+    "println",
+    "eprint",
+    "eprintln",
+    "write",
+    "writeln",
+    "panic",
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "debug_assert",
+    "debug_assert_eq",
+    "debug_assert_ne",
+    "unreachable",
+    "todo",
+    "unimplemented",
+];
+
+/// Records the language features used by the token at index `i`, by the name of their feature
+/// gate.
+pub(super) fn detect(tokens: &[TokenTree], i: usize, context: Context, usages: &mut Vec<Usage>) {
+    let next = |n: usize| tokens.get(i + n);
+    let previous = |n: usize| i.checked_sub(n).and_then(|j| tokens.get(j));
+
+    match &tokens[i] {
+        TokenTree::Ident(ident) => match ident.to_string().as_str() {
+            "let" => {
+                if let Some(span) = let_else(&tokens[i + 1..]) {
+                    usages.push(Usage::language_feature("let_else", span));
+                }
+            }
+            "if" | "while" if let_chain(&tokens[i + 1..]) => {
+                usages.push(Usage::language_feature("let_chains", ident.span()));
+            }
+            "type"
+                if matches!(context, Context::Trait | Context::TraitImpl)
+                    && matches!(next(1), Some(TokenTree::Ident(_)))
+                    && is_punct(next(2), '<') =>
+            {
+                usages.push(Usage::language_feature(
+                    "generic_associated_types",
+                    ident.span(),
+                ));
+            }
+            "fn" => function(tokens, i, context, usages),
+            "async" if is_ident(next(1), "fn") => {
+                if matches!(context, Context::Trait | Context::TraitImpl) {
+                    usages.push(Usage::language_feature("async_fn_in_trait", ident.span()));
+                } else {
+                    usages.push(Usage::language_feature("async_await", ident.span()));
+                }
+            }
+            "async"
+                if is_punct(next(1), '|')
+                    || (is_ident(next(1), "move") && is_punct(next(2), '|')) =>
+            {
+                usages.push(Usage::language_feature("async_closure", ident.span()));
+            }
+            "async" if is_ident(next(1), "move") || is_group(next(1), Delimiter::Brace) => {
+                usages.push(Usage::language_feature("async_await", ident.span()));
+            }
+            "const" if is_group(next(1), Delimiter::Brace) && context != Context::ThreadLocal => {
+                usages.push(Usage::language_feature("inline_const", ident.span()));
+            }
+            "const"
+                if (is_punct(previous(1), '<') || is_punct(previous(1), ','))
+                    && matches!(next(1), Some(TokenTree::Ident(_)))
+                    && is_punct(next(2), ':') =>
+            {
+                // a default value follows the type of the const parameter, e.g. `const N: usize = 1`
+                let feature = if is_punct(next(4), '=') {
+                    "const_generics_defaults"
+                } else {
+                    "min_const_generics"
+                };
+
+                usages.push(Usage::language_feature(feature, ident.span()));
+            }
+            "dyn" if matches!(next(1), Some(TokenTree::Ident(_))) => {
+                usages.push(Usage::language_feature("dyn_trait", ident.span()));
+            }
+            "use" if is_punct(next(1), '<') => {
+                usages.push(Usage::language_feature("precise_capturing", ident.span()));
+            }
+            "raw"
+                if is_punct(previous(1), '&')
+                    && (is_ident(next(1), "const") || is_ident(next(1), "mut")) =>
+            {
+                usages.push(Usage::language_feature("raw_ref_op", ident.span()));
+            }
+            name if FORMAT_MACROS.contains(&name) && is_punct(next(1), '!') => {
+                if let Some(TokenTree::Group(arguments)) = next(2) {
+                    if let Some(span) = format_args_capture(arguments, format_string_position(name))
+                    {
+                        usages.push(Usage::language_feature("format_args_capture", span));
+                    }
+                }
+            }
+            _ => {}
+        },
+        TokenTree::Punct(punct) => match punct.as_char() {
+            // `?Sized` is a bound, and `$(...)?` a repetition of `macro_rules!`
+            '?' if !matches!(next(1), Some(TokenTree::Ident(_))) && !is_repetition(tokens, i) => {
+                usages.push(Usage::language_feature("question_mark", punct.span()));
+            }
+            '.' if is_ident(next(1), "await") => {
+                usages.push(Usage::language_feature("async_await", punct.span()));
+            }
+            // a label of a block, e.g. `'done: { ... }`
+            '\'' if punct.spacing() == Spacing::Joint
+                && is_punct(next(2), ':')
+                && is_group(next(3), Delimiter::Brace) =>
+            {
+                usages.push(Usage::language_feature("label_break_value", punct.span()));
+            }
+            '#' => {
+                let attribute = if is_punct(next(1), '!') {
+                    next(2)
+                } else {
+                    next(1)
+                };
+
+                if let Some(TokenTree::Group(group)) = attribute {
+                    if let Some(feature) = attribute_feature(group) {
+                        usages.push(Usage::language_feature(feature, punct.span()));
+                    }
+                }
+            }
+            _ => {}
+        },
+        TokenTree::Group(_) | TokenTree::Literal(_) => {}
+    }
+}
+
+/// The kind of item whose body is the group at index `i`.
+pub(super) fn group_context(tokens: &[TokenTree], i: usize) -> Context {
+    if i >= 2 && is_punct(tokens.get(i - 1), '!') && is_ident(tokens.get(i - 2), "thread_local") {
+        return Context::ThreadLocal;
+    }
+
+    if !is_group(tokens.get(i), Delimiter::Brace) {
+        return Context::Other;
+    }
+
+    // the header of the item runs back to the end of the previous item or statement
+    let start = tokens[..i]
+        .iter()
+        .rposition(|token| is_punct(Some(token), ';') || is_group(Some(token), Delimiter::Brace))
+        .map_or(0, |position| position + 1);
+    let header = &tokens[start..i];
+
+    let has_keyword = |keyword: &str| header.iter().any(|token| is_ident(Some(token), keyword));
+
+    if has_keyword("fn") {
+        Context::Other
+    } else if has_keyword("trait") {
+        Context::Trait
+    } else if has_keyword("impl") {
+        // `for` may also start higher-ranked trait bounds, like `for<'a> Fn(&'a str)`
+        let implements_trait = header
+            .iter()
+            .enumerate()
+            .any(|(j, token)| is_ident(Some(token), "for") && !is_punct(header.get(j + 1), '<'));
+
+        if implements_trait {
+            Context::TraitImpl
+        } else {
+            Context::InherentImpl
+        }
+    } else {
+        Context::Other
+    }
+}
+
+/// The `else` of a `let` statement, if it has one. Unlike an `if` expression, the expression of a
+/// `let` statement with an `else` block may not end with a block.
+fn let_else(tokens: &[TokenTree]) -> Option<Span> {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) if ident == "else" => return Some(ident.span()),
+            TokenTree::Punct(punct) if punct.as_char() == ';' => return None,
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => return None,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Whether the condition of an `if` or `while` expression chains a `let` with `&&`.
+fn let_chain(tokens: &[TokenTree]) -> bool {
+    let condition = tokens
+        .iter()
+        .position(|token| is_group(Some(token), Delimiter::Brace) || is_punct(Some(token), ';'))
+        .map_or(tokens, |end| &tokens[..end]);
+
+    let has_let = condition.iter().any(|token| is_ident(Some(token), "let"));
+    let has_and = condition.windows(2).any(|pair| match &pair[0] {
+        TokenTree::Punct(punct) => {
+            punct.as_char() == '&'
+                && punct.spacing() == Spacing::Joint
+                && is_punct(Some(&pair[1]), '&')
+        }
+        _ => false,
+    });
+
+    has_let && has_and
+}
+
+/// Records the features used by the signature of the function which is declared by the `fn`
+/// keyword at index `i`: `impl Trait` in argument position, and in return position.
+fn function(tokens: &[TokenTree], i: usize, context: Context, usages: &mut Vec<Usage>) {
+    // function pointer types, like `fn(u8) -> u8`, have no name
+    if !matches!(tokens.get(i + 1), Some(TokenTree::Ident(_))) {
+        return;
+    }
+
+    let mut j = i + 2;
+    if is_punct(tokens.get(j), '<') {
+        j = skip_generics(tokens, j);
+    }
+
+    let parameters = match tokens.get(j) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => group,
+        _ => return,
+    };
+
+    let impl_argument = parameters
+        .stream()
+        .into_iter()
+        .find_map(|token| match token {
+            TokenTree::Ident(ident) if ident == "impl" => Some(ident.span()),
+            _ => None,
+        });
+
+    if let Some(span) = impl_argument {
+        usages.push(Usage::language_feature("universal_impl_trait", span));
+    }
+
+    if is_punct(tokens.get(j + 1), '-') && is_punct(tokens.get(j + 2), '>') {
+        if let Some(TokenTree::Ident(ident)) =
+            tokens.get(j + 3).filter(|t| is_ident(Some(t), "impl"))
+        {
+            let feature = match context {
+                Context::Trait | Context::TraitImpl => "return_position_impl_trait_in_trait",
+                _ => "conservative_impl_trait",
+            };
+
+            usages.push(Usage::language_feature(feature, ident.span()));
+        }
+    }
+}
+
+/// The index of the token which follows the generic parameters that start at index `start`.
+fn skip_generics(tokens: &[TokenTree], start: usize) -> usize {
+    let mut depth = 0usize;
+
+    for (j, token) in tokens.iter().enumerate().skip(start) {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '<' => depth += 1,
+            // the `>` of `->` does not close the generic parameters
+            TokenTree::Punct(punct) if punct.as_char() == '>' && !is_arrow(tokens, j) => {
+                depth = depth.saturating_sub(1);
+
+                if depth == 0 {
+                    return j + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    tokens.len()
+}
+
+/// Whether the `?` at index `i` is the repetition operator of a `macro_rules!` matcher.
+fn is_repetition(tokens: &[TokenTree], i: usize) -> bool {
+    i >= 2 && matches!(tokens[i - 1], TokenTree::Group(_)) && is_punct(tokens.get(i - 2), '$')
+}
+
+fn is_arrow(tokens: &[TokenTree], j: usize) -> bool {
+    match j.checked_sub(1).and_then(|i| tokens.get(i)) {
+        Some(TokenTree::Punct(punct)) => {
+            punct.as_char() == '-' && punct.spacing() == Spacing::Joint
+        }
+        _ => false,
+    }
+}
+
+/// The language feature required by an attribute, like `#[non_exhaustive]`.
+fn attribute_feature(attribute: &Group) -> Option<&'static str> {
+    if attribute.delimiter() != Delimiter::Bracket {
+        return None;
+    }
+
+    let tokens = attribute.stream().into_iter().collect::<Vec<_>>();
+
+    match tokens.first() {
+        Some(TokenTree::Ident(ident)) if ident == "non_exhaustive" => Some("non_exhaustive"),
+        Some(TokenTree::Ident(ident)) if ident == "expect" => Some("lint_reasons"),
+        Some(TokenTree::Ident(ident)) if ident == "diagnostic" && is_punct(tokens.get(1), ':') => {
+            Some("diagnostic_namespace")
+        }
+        _ => None,
+    }
+}
+
+/// The number of arguments which precede the format string of a format macro, like the
+/// destination of `write!`, or the operands of `assert_eq!`.
+fn format_string_position(name: &str) -> usize {
+    match name {
+        "write" | "writeln" | "assert" | "debug_assert" => 1,
+        "assert_eq" | "assert_ne" | "debug_assert_eq" | "debug_assert_ne" => 2,
+        _ => 0,
+    }
+}
+
+/// The format string of a format macro, if it captures a variable which is not passed to the
+/// macro as a named argument, like `println!("{name}")`. Only the string literal at the position
+/// of the format string is considered, since other arguments, like `println!("{}", "{name}")`,
+/// are not format strings.
+fn format_args_capture(arguments: &Group, position: usize) -> Option<Span> {
+    let tokens = arguments.stream().into_iter().collect::<Vec<_>>();
+
+    let literal = match tokens
+        .split(|token| is_punct(Some(token), ','))
+        .nth(position)
+    {
+        Some([TokenTree::Literal(literal)]) => literal,
+        _ => return None,
+    };
+
+    let named_arguments = tokens
+        .windows(2)
+        .filter_map(|pair| match (&pair[0], &pair[1]) {
+            (TokenTree::Ident(name), TokenTree::Punct(punct))
+                if punct.as_char() == '=' && punct.spacing() == Spacing::Alone =>
+            {
+                Some(name.to_string())
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let literal_text = literal.to_string();
+    let format = format_string(&literal_text)?;
+
+    captured_names(format)
+        .iter()
+        .any(|name| !named_arguments.iter().any(|named| named == name))
+        .then(|| literal.span())
+}
+
+/// The contents of a (raw) string literal, or `None` for other literals.
+fn format_string(literal: &str) -> Option<&str> {
+    if let Some(contents) = literal.strip_prefix('"') {
+        return contents.strip_suffix('"');
+    }
+
+    let raw = literal.strip_prefix('r')?;
+    let hashes = raw.len() - raw.trim_start_matches('#').len();
+
+    raw[hashes..]
+        .strip_prefix('"')?
+        .get(..raw.len() - 2 * hashes - 2)
+}
+
+/// The names of the variables in the placeholders of a format string, e.g. `name` in
+/// `"{name:>8}"`. Positional placeholders, like `{0}` and `{}`, are skipped.
+fn captured_names(format: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = format;
+
+    while let Some(start) = rest.find('{') {
+        let placeholder = &rest[start + 1..];
+
+        // an escaped brace, or the code point of a unicode escape, like `\u{1F980}`
+        if placeholder.starts_with('{') || rest[..start].ends_with("\\u") {
+            rest = placeholder.get(1..).unwrap_or_default();
+            continue;
+        }
+
+        let end = placeholder
+            .find(|c| c == '}' || c == ':')
+            .unwrap_or(placeholder.len());
+        let name = placeholder[..end].trim();
+
+        if name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        {
+            names.push(name);
+        }
+
+        rest = &placeholder[end..];
+    }
+
+    names
+}
+
+pub(super) fn is_ident(token: Option<&TokenTree>, name: &str) -> bool {
+    matches!(token, Some(TokenTree::Ident(ident)) if ident == name)
+}
+
+pub(super) fn is_punct(token: Option<&TokenTree>, c: char) -> bool {
+    matches!(token, Some(TokenTree::Punct(punct)) if punct.as_char() == c)
+}
+
+pub(super) fn is_group(token: Option<&TokenTree>, delimiter: Delimiter) -> bool {
+    matches!(token, Some(TokenTree::Group(group)) if group.delimiter() == delimiter)
+}
@@ -0,0 +1,488 @@
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, ContentArrangement, Table};
+use rust_releases::{semver, Release, ReleaseIndex};
+use serde::Serialize;
+use toml_edit::Document;
+
+use crate::cache::CheckCache;
+use crate::check::{CachedCheck, Check, RunCheck};
+use crate::config::bump::{BumpTarget, DependencyVersion};
+use crate::config::{Config, ModeIntent};
+use crate::dependencies::registry_index::CargoIndexCache;
+use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
+use crate::formatter::{FormatUserOutput, Human, Json};
+use crate::language_features::stabilized_in;
+use crate::manifest::bare_version::BareVersion;
+use crate::manifest::{CargoManifest, CargoManifestParser, TomlParser};
+use crate::paths::crate_root_folder;
+use crate::releases::filter_releases;
+use crate::reporter::json::Event;
+use crate::reporter::{write_report, Output};
+use crate::subcommands::set::write_msrv;
+use crate::toolchain::ToolchainSpec;
+use crate::toolchain_file::raise_toolchain_file_version;
+
+pub fn run_bump_msrv<R: Output>(
+    config: &Config,
+    output: &R,
+    release_index: &ReleaseIndex,
+) -> TResult<()> {
+    let crate_folder = crate_root_folder(config)?;
+    let cargo_toml = crate_folder.join("Cargo.toml");
+
+    let contents = std::fs::read_to_string(&cargo_toml).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFile(cargo_toml.clone()),
+    })?;
+
+    let manifest = CargoManifestParser.parse::<Document>(&contents)?;
+    let manifest = CargoManifest::try_from(manifest)?;
+
+    let current = manifest
+        .minimum_rust_version()
+        .ok_or_else(|| CargoMSRVError::NoMSRVKeyInCargoToml(cargo_toml.to_owned()))?;
+
+    output.mode(ModeIntent::Bump);
+
+    let requirements = config
+        .sub_command_config()
+        .bump()
+        .targets
+        .iter()
+        .map(|target| {
+            required_rust_version(target).map(|rust_version| Requirement {
+                target: target.clone(),
+                rust_version,
+            })
+        })
+        .collect::<TResult<Vec<_>>>()?;
+
+    let releases = filter_releases(config, release_index.releases());
+    let plan = BumpPlan::new(current.clone(), requirements, &releases)?;
+    write_report(&plan, config, output);
+
+    let msrv = match plan.msrv() {
+        Some(msrv) => msrv,
+        // The current MSRV already satisfies every requirement
+        None => {
+            output.finish_success(ModeIntent::Bump, Some(&current.to_semver_version()));
+            return Ok(());
+        }
+    };
+
+    let runner = RunCheck::new(output);
+    let toolchain = ToolchainSpec::new(msrv, config.target());
+    let status = if config.no_cache() {
+        runner.check(config, &toolchain)?
+    } else {
+        let cache = CheckCache::from_config(config)?;
+        CachedCheck::new(runner, cache, output).check(config, &toolchain)?
+    };
+
+    if !status.is_success() {
+        output.finish_failure(ModeIntent::Bump, Some(&config.check_command_string()));
+
+        return Err(CargoMSRVError::SubCommandBump(Error::CheckFailed {
+            msrv: msrv.to_owned(),
+        }));
+    }
+
+    let bare_msrv = plan.bare_msrv(msrv);
+    write_msrv(config, &bare_msrv)?;
+
+    if let Some(path) = raise_toolchain_file_version(config, &bare_msrv)? {
+        info!(path = %path.display(), "raised Rust version of toolchain file");
+    }
+
+    output.finish_success(ModeIntent::Bump, Some(msrv));
+
+    Ok(())
+}
+
+/// The Rust version required to use the bump target.
+fn required_rust_version(target: &BumpTarget) -> TResult<semver::Version> {
+    match target {
+        BumpTarget::Version(version) => Ok(version.to_semver_version()),
+        BumpTarget::LanguageFeature(feature) => stabilized_in(feature).ok_or_else(|| {
+            CargoMSRVError::SubCommandBump(Error::UnknownLanguageFeature(feature.clone()))
+        }),
+        BumpTarget::Dependency(dependency) => dependency_rust_version(dependency),
+    }
+}
+
+/// The MSRV of a dependency, as published in the index of the registry.
+///
+/// The index is read from the index cache of the local Cargo registries, which holds every
+/// published version of the crates Cargo looked up, so the version does not have to be locked,
+/// or even downloaded. Versions published before the index held the `rust-version` fall back to
+/// the Cargo manifest in the downloaded sources of the crate, if available.
+fn dependency_rust_version(dependency: &DependencyVersion) -> TResult<semver::Version> {
+    let cargo_home =
+        cargo_home().ok_or_else(|| Error::DependencyNotFound(dependency.to_string()))?;
+
+    find_dependency_rust_version(&cargo_home, dependency)
+}
+
+fn find_dependency_rust_version(
+    cargo_home: &Path,
+    dependency: &DependencyVersion,
+) -> TResult<semver::Version> {
+    let mut published = false;
+
+    for cache in CargoIndexCache::in_cargo_home(cargo_home) {
+        let entry = cache
+            .versions(&dependency.name)?
+            .into_iter()
+            .find(|entry| entry.vers == dependency.version);
+
+        if let Some(entry) = entry {
+            published = true;
+
+            if let Some(rust_version) = entry.rust_version() {
+                return Ok(rust_version);
+            }
+        }
+    }
+
+    if let Some(manifest_path) = find_dependency_manifest(cargo_home, dependency) {
+        return manifest_rust_version(&manifest_path, dependency);
+    }
+
+    if published {
+        Err(Error::DependencyWithoutMsrv(dependency.to_string()).into())
+    } else {
+        Err(Error::DependencyNotFound(dependency.to_string()).into())
+    }
+}
+
+fn manifest_rust_version(
+    manifest_path: &Path,
+    dependency: &DependencyVersion,
+) -> TResult<semver::Version> {
+    let contents = std::fs::read_to_string(manifest_path).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFile(manifest_path.to_path_buf()),
+    })?;
+
+    let manifest = CargoManifestParser.parse::<Document>(&contents)?;
+    let manifest = CargoManifest::try_from(manifest)?;
+
+    manifest
+        .minimum_rust_version()
+        .map(BareVersion::to_semver_version)
+        .ok_or_else(|| Error::DependencyWithoutMsrv(dependency.to_string()).into())
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))
+}
+
+/// Find the Cargo manifest of the dependency in the sources of any of the registries Cargo has
+/// downloaded crates from.
+fn find_dependency_manifest(cargo_home: &Path, dependency: &DependencyVersion) -> Option<PathBuf> {
+    let package = format!("{}-{}", dependency.name, dependency.version);
+
+    std::fs::read_dir(cargo_home.join("registry").join("src"))
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|registry| registry.path().join(&package).join("Cargo.toml"))
+        .find(|manifest| manifest.is_file())
+}
+
+/// A bump target, and the Rust version it requires.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Requirement {
+    target: BumpTarget,
+    rust_version: semver::Version,
+}
+
+/// The plan to raise the MSRV from the current MSRV, to the first Rust release which satisfies
+/// all requirements.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BumpPlan {
+    current: BareVersion,
+    requirements: Vec<Requirement>,
+    msrv: Option<semver::Version>,
+}
+
+impl BumpPlan {
+    /// Select the first release which satisfies all requirements from the given releases.
+    ///
+    /// Returns an error if a newer MSRV is required, but no release satisfies the requirements.
+    pub fn new(
+        current: BareVersion,
+        requirements: Vec<Requirement>,
+        releases: &[Release],
+    ) -> TResult<Self> {
+        let current_version = current.to_semver_version();
+
+        let required = requirements
+            .iter()
+            .map(|requirement| &requirement.rust_version)
+            .max()
+            .filter(|&required| required > &current_version);
+
+        let msrv = match required {
+            Some(required) => Some(
+                releases
+                    .iter()
+                    .map(Release::version)
+                    .filter(|&version| version >= required)
+                    .min()
+                    .cloned()
+                    .ok_or_else(|| Error::NoReleaseFound {
+                        required: required.clone(),
+                    })?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            current,
+            requirements,
+            msrv,
+        })
+    }
+
+    /// The new MSRV, or `None` if the current MSRV already satisfies all requirements.
+    pub fn msrv(&self) -> Option<&semver::Version> {
+        self.msrv.as_ref()
+    }
+
+    /// Write the new MSRV with two components if the current MSRV is written with two components,
+    /// and the patch version can be left out.
+    fn bare_msrv(&self, msrv: &semver::Version) -> BareVersion {
+        match self.current {
+            BareVersion::TwoComponents(..) if msrv.patch == 0 => {
+                BareVersion::TwoComponents(msrv.major, msrv.minor)
+            }
+            _ => BareVersion::ThreeComponents(msrv.major, msrv.minor, msrv.patch),
+        }
+    }
+}
+
+impl FormatUserOutput<Human> for BumpPlan {
+    fn format_line(&self) -> String {
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec!["Requirement", "Rust version"]);
+
+        for requirement in &self.requirements {
+            table.add_row(vec![
+                Cell::new(&requirement.target),
+                Cell::new(&requirement.rust_version),
+            ]);
+        }
+
+        let summary = match self.msrv() {
+            Some(msrv) => format!("Raising the MSRV from {} to {}", self.current, msrv),
+            None => format!(
+                "The current MSRV {} already satisfies all requirements",
+                self.current
+            ),
+        };
+
+        format!("{}\n{}", table, summary)
+    }
+}
+
+#[derive(Serialize)]
+struct BumpPlanEvent {
+    current_msrv: String,
+    msrv: Option<String>,
+    requirements: Vec<RequirementFields>,
+}
+
+#[derive(Serialize)]
+struct RequirementFields {
+    requirement: String,
+    rust_version: String,
+}
+
+impl FormatUserOutput<Json> for BumpPlan {
+    fn format_line(&self) -> String {
+        let requirements = self
+            .requirements
+            .iter()
+            .map(|requirement| RequirementFields {
+                requirement: requirement.target.to_string(),
+                rust_version: requirement.rust_version.to_string(),
+            })
+            .collect();
+
+        let event = BumpPlanEvent {
+            current_msrv: self.current.to_string(),
+            msrv: self.msrv().map(ToString::to_string),
+            requirements,
+        };
+
+        Event::new("bump-plan", event).to_line()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Crate source was found to be incompatible with the new MSRV '{msrv}'")]
+    CheckFailed { msrv: semver::Version },
+
+    #[error(
+        "Unable to find '{0}' in the dependencies of the crate, add it to the Cargo manifest first"
+    )]
+    DependencyNotFound(String),
+
+    #[error("Dependency '{0}' does not specify an MSRV in its Cargo manifest")]
+    DependencyWithoutMsrv(String),
+
+    #[error("No Rust release found which is at least '{required}'")]
+    NoReleaseFound { required: semver::Version },
+
+    #[error("Unknown language feature '{0}'")]
+    UnknownLanguageFeature(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_dependency_rust_version, BumpPlan, Error, Requirement};
+    use crate::config::bump::{BumpTarget, DependencyVersion};
+    use crate::dependencies::registry_index::cache_file;
+    use crate::errors::CargoMSRVError;
+    use crate::formatter::{FormatUserOutput, Json};
+    use crate::manifest::bare_version::BareVersion;
+    use crate::reporter::json::schema::assert_conforms;
+    use crate::semver::Version;
+    use rust_releases::Release;
+
+    fn releases(minors: &[u64]) -> Vec<Release> {
+        minors
+            .iter()
+            .map(|minor| Release::new_stable(Version::new(1, *minor, 0)))
+            .collect()
+    }
+
+    fn feature(name: &str, minor: u64) -> Requirement {
+        Requirement {
+            target: BumpTarget::LanguageFeature(name.to_string()),
+            rust_version: Version::new(1, minor, 0),
+        }
+    }
+
+    #[test]
+    fn bump_to_first_release_satisfying_all_requirements() {
+        let plan = BumpPlan::new(
+            BareVersion::TwoComponents(1, 56),
+            vec![feature("let_else", 65), feature("format_args_capture", 58)],
+            &releases(&[70, 66, 65, 64, 56]),
+        )
+        .unwrap();
+
+        assert_eq!(plan.msrv(), Some(&Version::new(1, 65, 0)));
+        assert_eq!(
+            plan.bare_msrv(plan.msrv().unwrap()),
+            BareVersion::TwoComponents(1, 65)
+        );
+    }
+
+    #[test]
+    fn no_bump_if_current_msrv_satisfies_requirements() {
+        let plan = BumpPlan::new(
+            BareVersion::ThreeComponents(1, 65, 0),
+            vec![feature("format_args_capture", 58)],
+            &releases(&[70, 65, 58]),
+        )
+        .unwrap();
+
+        assert!(plan.msrv().is_none());
+    }
+
+    #[test]
+    fn no_release_satisfies_requirements() {
+        let plan = BumpPlan::new(
+            BareVersion::TwoComponents(1, 56),
+            vec![feature("let_else", 65)],
+            &releases(&[60, 56]),
+        );
+
+        assert!(plan.is_err());
+    }
+
+    #[test]
+    fn json_output() {
+        let plan = BumpPlan::new(
+            BareVersion::TwoComponents(1, 56),
+            vec![feature("let_else", 65)],
+            &releases(&[65, 56]),
+        )
+        .unwrap();
+
+        let output = assert_conforms(&FormatUserOutput::<Json>::format_line(&plan));
+
+        assert_eq!(output["reason"], "bump-plan");
+        assert_eq!(output["current_msrv"], "1.56");
+        assert_eq!(output["msrv"], "1.65.0");
+        assert_eq!(output["requirements"][0]["requirement"], "feature let_else");
+        assert_eq!(output["requirements"][0]["rust_version"], "1.65.0");
+    }
+
+    #[test]
+    fn find_rust_version_of_dependency() {
+        let cargo_home = std::env::temp_dir().join("cargo-msrv-test-bump-registry");
+        let _ = std::fs::remove_dir_all(&cargo_home);
+
+        let cache = cargo_home
+            .join("registry")
+            .join("index")
+            .join("index.crates.io-6f17d22bba15001f")
+            .join(".cache");
+        std::fs::create_dir_all(cache.join("se").join("rd")).unwrap();
+        std::fs::write(
+            cache.join("se").join("rd").join("serde"),
+            cache_file(&[
+                r#"{"name":"serde","vers":"1.0.100","deps":[],"cksum":"00","features":{}}"#,
+                r#"{"name":"serde","vers":"1.0.150","deps":[],"cksum":"01","features":{}}"#,
+                r#"{"name":"serde","vers":"1.0.200","deps":[],"cksum":"02","features":{},"rust_version":"1.56"}"#,
+            ]),
+        )
+        .unwrap();
+
+        // only 1.0.150 was downloaded
+        let package = cargo_home
+            .join("registry")
+            .join("src")
+            .join("index.crates.io-6f17d22bba15001f")
+            .join("serde-1.0.150");
+        std::fs::create_dir_all(&package).unwrap();
+        std::fs::write(
+            package.join("Cargo.toml"),
+            "[package]\nname = \"serde\"\nrust-version = \"1.31\"\n",
+        )
+        .unwrap();
+
+        let find = |spec: &str| {
+            find_dependency_rust_version(&cargo_home, &spec.parse::<DependencyVersion>().unwrap())
+        };
+
+        // a version which was neither locked, nor downloaded
+        assert_eq!(find("serde@1.0.200").unwrap(), Version::new(1, 56, 0));
+        // the index has no rust-version, so it is read from the downloaded manifest
+        assert_eq!(find("serde@1.0.150").unwrap(), Version::new(1, 31, 0));
+        assert!(matches!(
+            find("serde@1.0.100"),
+            Err(CargoMSRVError::SubCommandBump(
+                Error::DependencyWithoutMsrv(_)
+            ))
+        ));
+        assert!(matches!(
+            find("serde@2.0.0"),
+            Err(CargoMSRVError::SubCommandBump(Error::DependencyNotFound(_)))
+        ));
+
+        std::fs::remove_dir_all(&cargo_home).unwrap();
+    }
+}
@@ -1,20 +1,90 @@
 use rust_releases::{Release, ReleaseIndex};
 
-use crate::check::RunCheck;
-use crate::config::{Config, ModeIntent, SearchMethod};
+use crate::cache::CheckCache;
+use crate::check::{CachedCheck, Check, RecordChecks, RecordFailures, RunCheck};
+use crate::compatibility_report::{write_compatibility_report, CheckLog};
+use crate::config::{Config, ConfigBuilder, ModeIntent, SearchMethod};
+use crate::diagnose::{Diagnosis, FailureLog};
 use crate::errors::{CargoMSRVError, TResult};
+use crate::feature_matrix::FeatureMatrixCompatibility;
+use crate::msrv_per_key::{MsrvKey, MsrvPerKey};
+use crate::multi_target::TargetCompatibility;
+use crate::paths::crate_root_folder;
 use crate::releases::filter_releases;
-use crate::reporter::Output;
+use crate::reporter::no_output::NoOutput;
+use crate::reporter::{write_report, Output};
 use crate::result::MinimalCompatibility;
-use crate::search_methods::{Bisect, FindMinimalCapableToolchain, Linear};
+use crate::search_methods::{
+    Bisect, ConcurrentCheck, FindMinimalCapableToolchain, Linear, Parallel,
+};
+use crate::static_msrv::{feature_gates, static_msrv, UnstableFeatures};
 use crate::toolchain_file::write_toolchain_file;
+use crate::workspace::WorkspaceCompatibility;
 
 pub fn run_find_msrv_action<R: Output>(
     config: &Config,
     reporter: &R,
     release_index: &ReleaseIndex,
 ) -> TResult<()> {
-    match find_msrv(config, reporter, release_index)? {
+    match find(config, reporter, release_index)? {
+        FindReport::Msrv(compatibility) => report_msrv(config, compatibility),
+        FindReport::UnstableFeatures(report) => report_unstable_features(config, reporter, report),
+        FindReport::Workspace(compatibility) => {
+            report_msrv_per_key(config, reporter, compatibility)
+        }
+        FindReport::FeatureMatrix(compatibility) => {
+            report_msrv_per_key(config, reporter, compatibility)
+        }
+        FindReport::Targets(compatibility) => report_msrv_per_key(config, reporter, compatibility),
+    }
+}
+
+/// The outcome of a search for the MSRV, which depends on what the config asks for.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FindReport {
+    /// The MSRV of the crate
+    Msrv(MinimalCompatibility),
+    /// The unstable features which the crate enables, and the oldest nightly toolchain on which
+    /// it compiles
+    UnstableFeatures(UnstableFeatures),
+    /// The MSRV of each member of the workspace
+    Workspace(WorkspaceCompatibility),
+    /// The MSRV of each feature combination of the feature matrix
+    FeatureMatrix(FeatureMatrixCompatibility),
+    /// The MSRV for each of the compile targets
+    Targets(TargetCompatibility),
+}
+
+/// Find the MSRV, or the MSRV per workspace member, feature combination or target, or the unstable
+/// features, like `cargo msrv` would for the given config, without reporting the outcome.
+pub fn find<R: Output>(
+    config: &Config,
+    reporter: &R,
+    release_index: &ReleaseIndex,
+) -> TResult<FindReport> {
+    if config.unstable_features() {
+        return find_unstable_features(config, reporter, release_index)
+            .map(FindReport::UnstableFeatures);
+    }
+
+    if config.workspace() {
+        return find_workspace_msrv(config, reporter, release_index).map(FindReport::Workspace);
+    }
+
+    if !config.feature_matrix().is_empty() {
+        return find_feature_matrix_msrv(config, reporter, release_index)
+            .map(FindReport::FeatureMatrix);
+    }
+
+    if !config.compile_targets().is_empty() {
+        return find_targets_msrv(config, reporter, release_index).map(FindReport::Targets);
+    }
+
+    find_msrv(config, reporter, release_index).map(FindReport::Msrv)
+}
+
+fn report_msrv(config: &Config, compatibility: MinimalCompatibility) -> TResult<()> {
+    match compatibility {
         MinimalCompatibility::NoCompatibleToolchains => {
             info!("no minimal-compatible toolchain found");
 
@@ -37,39 +107,295 @@ pub fn run_find_msrv_action<R: Output>(
     }
 }
 
+/// Report a lower bound on the MSRV, determined from the crate sources, without running any
+/// toolchain.
+pub fn run_static_msrv_action<R: Output>(config: &Config, reporter: &R) -> TResult<()> {
+    let msrv = static_msrv(&crate_root_folder(config)?)?;
+
+    if let Some(version) = msrv.lower_bound() {
+        info!(%version, "found lower bound on msrv from crate sources");
+    }
+
+    write_report(&msrv, config, reporter);
+
+    Ok(())
+}
+
+/// Find the oldest nightly toolchain which compiles the crate, and the unstable features which
+/// the crate enables, with the Rust release in which each was stabilized.
+fn find_unstable_features<R: Output>(
+    config: &Config,
+    reporter: &R,
+    release_index: &ReleaseIndex,
+) -> TResult<UnstableFeatures> {
+    let gates = feature_gates(&crate_root_folder(config)?)?;
+
+    let nightly = match find_msrv(config, reporter, release_index)? {
+        MinimalCompatibility::CapableToolchain { toolchain } => Some(toolchain.version().clone()),
+        MinimalCompatibility::NoCompatibleToolchains => None,
+    };
+
+    Ok(UnstableFeatures::new(gates, nightly))
+}
+
+/// Report the unstable features which the crate enables, with the oldest nightly toolchain on
+/// which the crate compiles.
+fn report_unstable_features<R: Output>(
+    config: &Config,
+    reporter: &R,
+    report: UnstableFeatures,
+) -> TResult<()> {
+    if let Some(version) = report.nightly() {
+        info!(%version, "found oldest compatible nightly toolchain");
+    }
+
+    write_report(&report, config, reporter);
+
+    match report.nightly() {
+        Some(_) => Ok(()),
+        None => Err(CargoMSRVError::UnableToFindAnyGoodVersion {
+            command: config.check_command().join(" "),
+        }),
+    }
+}
+
+/// Report the MSRV of each key, like each target, and the MSRV of all keys combined.
+fn report_msrv_per_key<K: MsrvKey, R: Output>(
+    config: &Config,
+    reporter: &R,
+    compatibility: MsrvPerKey<K>,
+) -> TResult<()> {
+    write_report(&compatibility, config, reporter);
+
+    if let Some(key) = compatibility.incompatible().next() {
+        info!(
+            key = key.name().as_str(),
+            "no minimal-compatible toolchain found"
+        );
+
+        return Err(CargoMSRVError::UnableToFindAnyGoodVersion {
+            command: config.check_command().join(" "),
+        });
+    }
+
+    if let Some(version) = compatibility.msrv() {
+        info!(%version, "found minimal-compatible toolchain for {}", K::ALL);
+
+        if config.output_toolchain_file() {
+            write_toolchain_file(config, version)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the MSRV of each key, by running [`find_msrv`] with the configuration of each key.
+///
+/// The checks of all keys are written to a single compatibility report, if one was requested.
+pub fn find_msrv_per_key<K: MsrvKey, R: Output>(
+    config: &Config,
+    reporter: &R,
+    index: &rust_releases::ReleaseIndex,
+) -> TResult<MsrvPerKey<K>> {
+    let keys = K::keys(config)?;
+    let mut compatibilities = Vec::with_capacity(keys.len());
+    let checks = CheckLog::default();
+
+    for key in keys {
+        info!(key = key.name().as_str(), "finding msrv of key");
+
+        checks.set_scope(Some(key.name()));
+        let compatibility = search_msrv(&key.config(config), reporter, index, &checks)?;
+        compatibilities.push((key, compatibility));
+    }
+
+    let compatibility = MsrvPerKey::new(compatibilities);
+    write_compatibility_report(config, &checks, compatibility.msrv().cloned())?;
+
+    Ok(compatibility)
+}
+
+/// Find the MSRV of each member of the workspace, by running [`find_msrv`] from the folder
+/// of each member.
+pub fn find_workspace_msrv<R: Output>(
+    config: &Config,
+    reporter: &R,
+    index: &rust_releases::ReleaseIndex,
+) -> TResult<WorkspaceCompatibility> {
+    find_msrv_per_key(config, reporter, index)
+}
+
+/// Find the MSRV of each feature combination of the feature matrix, by running [`find_msrv`]
+/// with the feature flags of each combination added to the check command.
+pub fn find_feature_matrix_msrv<R: Output>(
+    config: &Config,
+    reporter: &R,
+    index: &rust_releases::ReleaseIndex,
+) -> TResult<FeatureMatrixCompatibility> {
+    find_msrv_per_key(config, reporter, index)
+}
+
+/// Find the MSRV for each of the compile targets, by running [`find_msrv`] with
+/// `--target <target>` added to the check command.
+pub fn find_targets_msrv<R: Output>(
+    config: &Config,
+    reporter: &R,
+    index: &rust_releases::ReleaseIndex,
+) -> TResult<TargetCompatibility> {
+    find_msrv_per_key(config, reporter, index)
+}
+
 pub fn find_msrv<R: Output>(
     config: &Config,
     reporter: &R,
     index: &rust_releases::ReleaseIndex,
 ) -> TResult<MinimalCompatibility> {
+    let checks = CheckLog::default();
+    let minimum_capable = search_msrv(config, reporter, index, &checks)?;
+
+    let msrv = match &minimum_capable {
+        MinimalCompatibility::CapableToolchain { toolchain } => Some(toolchain.version().clone()),
+        MinimalCompatibility::NoCompatibleToolchains => None,
+    };
+    write_compatibility_report(config, &checks, msrv)?;
+
+    Ok(minimum_capable)
+}
+
+/// Find the MSRV, and record the checks which were run in the given log, so an action which
+/// searches several times can write the checks of all searches to a single report.
+pub(crate) fn search_msrv<R: Output>(
+    config: &Config,
+    reporter: &R,
+    index: &rust_releases::ReleaseIndex,
+    checks: &CheckLog,
+) -> TResult<MinimalCompatibility> {
+    let seeded_config;
+    let config = if config.min_from_source() {
+        seeded_config = with_minimum_from_source(config)?;
+        &seeded_config
+    } else {
+        config
+    };
+
     let releases = index.releases();
     let included_releases = filter_releases(config, releases);
 
-    reporter.mode(ModeIntent::Find);
-    reporter.set_steps(included_releases.len() as u64);
-    run_with_search_method(config, &included_releases, reporter)
+    search_available_releases(included_releases, |included_releases| {
+        reporter.mode(ModeIntent::Find);
+        reporter.set_steps(included_releases.len() as u64);
+        run_with_search_method(config, included_releases, checks, reporter)
+    })
+}
+
+/// Run the search, and run it again without a dated toolchain which turned out to be unavailable,
+/// until the search completes. The outcomes of the toolchains which were checked already are
+/// cached, unless caching is disabled.
+fn search_available_releases(
+    mut releases: Vec<Release>,
+    mut search: impl FnMut(&[Release]) -> TResult<MinimalCompatibility>,
+) -> TResult<MinimalCompatibility> {
+    loop {
+        match search(&releases) {
+            Err(CargoMSRVError::UnavailableDatedToolchain(version))
+                if releases.iter().any(|release| release.version() == &version) =>
+            {
+                info!(%version, "skipping unavailable dated toolchain");
+                releases.retain(|release| release.version() != &version);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Raise the minimum version of the search to the lower bound on the MSRV which follows from the
+/// crate sources, if it's greater. APIs which were only recognized by the name of a method are
+/// ignored, since they may be methods of other types, which would skip compatible toolchains.
+fn with_minimum_from_source(config: &Config) -> TResult<Config> {
+    let msrv = static_msrv(&crate_root_folder(config)?)?;
+
+    match msrv.unambiguous_lower_bound() {
+        Some(lower_bound) if Some(lower_bound) > config.minimum_version() => {
+            info!(%lower_bound, "raising minimum version to lower bound from crate sources");
+
+            Ok(ConfigBuilder::from_config(config)
+                .minimum_version(lower_bound.clone())
+                .build())
+        }
+        _ => Ok(config.clone()),
+    }
 }
 
 fn run_with_search_method(
     config: &Config,
     included_releases: &[Release],
+    checks: &CheckLog,
     output: &impl Output,
 ) -> TResult<MinimalCompatibility> {
     output.set_steps(included_releases.len() as u64);
 
-    let search_method = config.search_method();
-    info!(?search_method);
+    let failures = FailureLog::default();
+
+    // The parallel search shares its runner with worker threads, so cached outcomes are reported
+    // by the search itself, rather than by the runner
+    if let SearchMethod::Parallel = config.search_method() {
+        info!(search_method = ?SearchMethod::Parallel);
+
+        let runner = ConcurrentCheck;
+
+        return if config.no_cache() {
+            let runner = RecordChecks::new(RecordFailures::new(runner, &failures), checks);
+            let method = Parallel::new(config.jobs(), runner);
+            run_searcher(method, included_releases, config, &failures, output)
+        } else {
+            let cache = CheckCache::from_config(config)?;
+            let runner = CachedCheck::new(runner, cache, &NoOutput);
+            let runner = RecordChecks::new(RecordFailures::new(runner, &failures), checks);
+            let method = Parallel::new(config.jobs(), runner);
+            run_searcher(method, included_releases, config, &failures, output)
+        };
+    }
 
     let runner = RunCheck::new(output);
 
+    if config.no_cache() {
+        let runner = RecordChecks::new(RecordFailures::new(runner, &failures), checks);
+        run_with_runner(runner, config, included_releases, &failures, output)
+    } else {
+        let cache = CheckCache::from_config(config)?;
+        let runner = RecordFailures::new(CachedCheck::new(runner, cache, output), &failures);
+        let runner = RecordChecks::new(runner, checks);
+        run_with_runner(runner, config, included_releases, &failures, output)
+    }
+}
+
+fn run_with_runner(
+    runner: impl Check,
+    config: &Config,
+    included_releases: &[Release],
+    failures: &FailureLog,
+    output: &impl Output,
+) -> TResult<MinimalCompatibility> {
+    let search_method = config.search_method();
+    info!(?search_method);
+
     // Run a linear or binary search depending on the configuration
     match search_method {
-        SearchMethod::Linear => {
-            run_searcher(Linear::new(runner), included_releases, config, output)
-        }
-        SearchMethod::Bisect => {
-            run_searcher(Bisect::new(runner), included_releases, config, output)
-        }
+        SearchMethod::Linear => run_searcher(
+            Linear::new(runner),
+            included_releases,
+            config,
+            failures,
+            output,
+        ),
+        SearchMethod::Bisect => run_searcher(
+            Bisect::new(runner),
+            included_releases,
+            config,
+            failures,
+            output,
+        ),
+        SearchMethod::Parallel => unreachable!("the parallel search does not use a runner"),
     }
 }
 
@@ -77,15 +403,37 @@ fn run_searcher(
     method: impl FindMinimalCapableToolchain,
     releases: &[Release],
     config: &Config,
+    failures: &FailureLog,
     output: &impl Output,
 ) -> TResult<MinimalCompatibility> {
     let minimum_capable = method.find_toolchain(releases, config, output)?;
 
+    report_diagnosis(&minimum_capable, failures, config, output);
     report_outcome(&minimum_capable, config, output);
 
     Ok(minimum_capable)
 }
 
+/// Explain why the toolchain just below the MSRV is not compatible, if it was checked and its
+/// errors could be parsed.
+fn report_diagnosis(
+    minimum_capable: &MinimalCompatibility,
+    failures: &FailureLog,
+    config: &Config,
+    output: &impl Output,
+) {
+    if let MinimalCompatibility::CapableToolchain { toolchain } = minimum_capable {
+        if let Some(failure) = failures.failure_below(toolchain.version()) {
+            let diagnosis = Diagnosis::new(toolchain.version().clone(), &failure);
+            info!(?diagnosis);
+
+            if !diagnosis.causes().is_empty() {
+                write_report(&diagnosis, config, output);
+            }
+        }
+    }
+}
+
 fn report_outcome(minimum_capable: &MinimalCompatibility, config: &Config, output: &impl Output) {
     match minimum_capable {
         MinimalCompatibility::CapableToolchain { toolchain } => {
@@ -96,3 +444,74 @@ fn report_outcome(minimum_capable: &MinimalCompatibility, config: &Config, outpu
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::search_available_releases;
+    use crate::errors::CargoMSRVError;
+    use crate::result::MinimalCompatibility;
+    use crate::semver::Version;
+    use crate::toolchain::OwnedToolchainSpec;
+    use rust_releases::Release;
+    use std::str::FromStr;
+
+    fn dated(version: &str) -> Release {
+        Release::new_stable(Version::from_str(version).unwrap())
+    }
+
+    #[test]
+    fn unavailable_dated_toolchains_are_skipped() {
+        let releases = vec![
+            dated("1.70.0-nightly.2023-04-13"),
+            dated("1.70.0-nightly.2023-04-12"),
+            dated("1.70.0-nightly.2023-04-11"),
+        ];
+        let unavailable = Version::from_str("1.70.0-nightly.2023-04-12").unwrap();
+        let mut searches = Vec::new();
+
+        let compatibility = search_available_releases(releases, |releases| {
+            searches.push(releases.len());
+
+            if releases
+                .iter()
+                .any(|release| release.version() == &unavailable)
+            {
+                return Err(CargoMSRVError::UnavailableDatedToolchain(
+                    unavailable.clone(),
+                ));
+            }
+
+            let toolchain =
+                OwnedToolchainSpec::new(releases[1].version(), "x86_64-unknown-linux-gnu");
+            Ok(MinimalCompatibility::CapableToolchain { toolchain })
+        })
+        .unwrap();
+
+        assert_eq!(searches, vec![3, 2]);
+        assert_eq!(
+            compatibility,
+            MinimalCompatibility::CapableToolchain {
+                toolchain: OwnedToolchainSpec::new(
+                    &Version::from_str("1.70.0-nightly.2023-04-11").unwrap(),
+                    "x86_64-unknown-linux-gnu"
+                )
+            }
+        );
+    }
+
+    #[test]
+    fn unavailable_toolchain_outside_search_space_is_an_error() {
+        let releases = vec![dated("1.70.0-nightly.2023-04-13")];
+
+        let result = search_available_releases(releases, |_| {
+            Err(CargoMSRVError::UnavailableDatedToolchain(
+                Version::from_str("1.70.0-nightly.2023-04-12").unwrap(),
+            ))
+        });
+
+        assert!(matches!(
+            result,
+            Err(CargoMSRVError::UnavailableDatedToolchain(_))
+        ));
+    }
+}
@@ -1,47 +1,185 @@
 use crate::config::list::ListVariant;
-use crate::config::{Config, ModeIntent, OutputFormat};
+use crate::config::{Config, ModeIntent, SubCommandConfig};
+use crate::dependencies::formatter::package_msrv;
 use crate::dependencies::resolver::{CargoMetadataResolver, DependencyResolver};
+use crate::dependencies::verify::verify_dependencies;
+use crate::dependencies::DEFAULT_DEPENDENCY_KINDS;
 use crate::errors::TResult;
-use crate::reporter::Output;
+use crate::manifest::bare_version::BareVersion;
+use crate::reporter::json::JsonPrinter;
+use crate::reporter::ui::HumanPrinter;
+use crate::reporter::{write_formatted, write_report, Output};
+use crate::semver;
+use crate::subcommands::show::show_msrv;
+use cargo_metadata::DependencyKind;
+use rust_releases::ReleaseIndex;
 
-pub fn run_list_msrv<R: Output>(config: &Config, output: &R) -> TResult<()> {
+pub fn run_list_msrv<R: Output>(config: &Config, output: &R, index: &ReleaseIndex) -> TResult<()> {
     use crate::dependencies::formatter;
 
     output.mode(ModeIntent::List);
 
-    let resolver = CargoMetadataResolver::try_from_config(config)?;
-    let graph = resolver.resolve()?;
+    let graph = resolver(config)?.resolve()?;
+
+    if config.sub_command_config().list().verify {
+        let crate_msrv = show_msrv(config)?
+            .msrv()
+            .map(BareVersion::to_semver_version);
+        let compatibility = verify_dependencies(config, output, index, &graph, crate_msrv)?;
+
+        write_report(&compatibility, config, output);
+        output.finish_success(ModeIntent::List, None);
+
+        return Ok(());
+    }
+
+    if let Some(format) = config.sub_command_config().list().format {
+        write_formatted(
+            graph,
+            config,
+            output,
+            |graph| formatter::GraphFormatter::<HumanPrinter>::new(graph, format),
+            |graph| formatter::GraphFormatter::<JsonPrinter>::new(graph, format),
+        );
+
+        output.finish_success(ModeIntent::List, None);
+
+        return Ok(());
+    }
 
     match config.sub_command_config().list().variant {
-        ListVariant::DirectDeps => match config.output_format() {
-            OutputFormat::Human => {
-                use crate::reporter::ui::HumanPrinter;
-                let formatter = formatter::DirectDependenciesFormatter::<HumanPrinter>::new(graph);
-                output.write_line(&format!("{}", formatter));
-            }
-            OutputFormat::Json => {
-                use crate::reporter::json::JsonPrinter;
-                let formatter = formatter::DirectDependenciesFormatter::<JsonPrinter>::new(graph);
-                output.write_line(&format!("{}", formatter));
-            }
-            OutputFormat::None | OutputFormat::TestSuccesses => {}
-        },
-        ListVariant::OrderedByMSRV => match config.output_format() {
-            OutputFormat::Human => {
-                use crate::reporter::ui::HumanPrinter;
-                let formatter = formatter::ByMSRVFormatter::<HumanPrinter>::new(graph);
-                output.write_line(&format!("{}", formatter));
-            }
-            OutputFormat::Json => {
-                use crate::reporter::json::JsonPrinter;
-                let formatter = formatter::ByMSRVFormatter::<JsonPrinter>::new(graph);
-                output.write_line(&format!("{}", formatter));
-            }
-            OutputFormat::None | OutputFormat::TestSuccesses => {}
-        },
+        ListVariant::DirectDeps => write_formatted(
+            graph,
+            config,
+            output,
+            formatter::DirectDependenciesFormatter::<HumanPrinter>::new,
+            formatter::DirectDependenciesFormatter::<JsonPrinter>::new,
+        ),
+        ListVariant::OrderedByMSRV => write_formatted(
+            graph,
+            config,
+            output,
+            formatter::ByMSRVFormatter::<HumanPrinter>::new,
+            formatter::ByMSRVFormatter::<JsonPrinter>::new,
+        ),
+        ListVariant::Tree => write_formatted(
+            graph,
+            config,
+            output,
+            formatter::TreeFormatter::<HumanPrinter>::new,
+            formatter::TreeFormatter::<JsonPrinter>::new,
+        ),
     }
 
     output.finish_success(ModeIntent::List, None);
 
     Ok(())
 }
+
+/// Resolves the dependencies which are active for the targets and the feature set of the config,
+/// and which are of the kinds given to the list subcommand.
+fn resolver(config: &Config) -> TResult<CargoMetadataResolver> {
+    Ok(CargoMetadataResolver::try_from_config(config)?
+        .with_platforms(&config.platforms())
+        .with_features(config.feature_matrix())
+        .with_dependency_kinds(dependency_kinds(config)))
+}
+
+/// The kinds of dependency given to the list subcommand, or else the default kinds, when the
+/// config was not built for the list subcommand.
+fn dependency_kinds(config: &Config) -> &[DependencyKind] {
+    match config.sub_command_config() {
+        SubCommandConfig::ListConfig(list) => &list.dep_kinds,
+        _ => DEFAULT_DEPENDENCY_KINDS,
+    }
+}
+
+/// List the dependencies of the crate, with the MSRV specified by their authors.
+///
+/// Like `cargo msrv list`, only the dependencies which are active for the targets, features and
+/// dependency kinds of the config are listed.
+pub fn list_dependencies(config: &Config) -> TResult<ListReport> {
+    let graph = resolver(config)?.resolve()?;
+
+    let dependencies = graph
+        .dependencies()
+        .map(|(package, direct)| DependencyMsrv {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            msrv: package_msrv(package),
+            direct,
+        })
+        .collect();
+
+    Ok(ListReport { dependencies })
+}
+
+/// The dependencies of a crate, and their MSRV's.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListReport {
+    dependencies: Vec<DependencyMsrv>,
+}
+
+impl ListReport {
+    /// The direct and transitive dependencies, ordered from the nearest to the most distant
+    /// dependency.
+    pub fn dependencies(&self) -> &[DependencyMsrv] {
+        &self.dependencies
+    }
+}
+
+/// A dependency, and the MSRV specified by its authors.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DependencyMsrv {
+    name: String,
+    version: semver::Version,
+    msrv: Option<semver::Version>,
+    direct: bool,
+}
+
+impl DependencyMsrv {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> &semver::Version {
+        &self.version
+    }
+
+    /// The MSRV of the dependency, or `None` if its authors did not specify one.
+    pub fn msrv(&self) -> Option<&semver::Version> {
+        self.msrv.as_ref()
+    }
+
+    /// Whether the crate depends on this dependency directly.
+    pub fn is_direct(&self) -> bool {
+        self.direct
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dependency_kinds;
+    use crate::config::list::{ListCmdConfig, ListVariant};
+    use crate::config::{ConfigBuilder, ModeIntent, SubCommandConfig};
+    use cargo_metadata::DependencyKind;
+
+    #[test]
+    fn dependency_kinds_of_list_config() {
+        let config = ConfigBuilder::new(ModeIntent::List, "x86_64-unknown-linux-gnu").build();
+        assert_eq!(
+            dependency_kinds(&config),
+            &[DependencyKind::Normal, DependencyKind::Build]
+        );
+
+        let config = ConfigBuilder::new(ModeIntent::List, "x86_64-unknown-linux-gnu")
+            .sub_command_config(SubCommandConfig::ListConfig(ListCmdConfig {
+                variant: ListVariant::Tree,
+                verify: false,
+                format: None,
+                dep_kinds: vec![DependencyKind::Development],
+            }))
+            .build();
+        assert_eq!(dependency_kinds(&config), &[DependencyKind::Development]);
+    }
+}
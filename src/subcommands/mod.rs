@@ -1,3 +1,4 @@
+pub mod bump;
 pub mod find;
 pub mod list;
 pub mod set;
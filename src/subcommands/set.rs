@@ -1,9 +1,10 @@
 use crate::errors::IoErrorSource;
 use crate::manifest::bare_version::BareVersion;
-use crate::manifest::{CargoManifestParser, TomlParser};
+use crate::manifest::{CargoManifest, CargoManifestParser, TomlParser};
 use crate::paths::crate_root_folder;
 use crate::{CargoMSRVError, Config, ModeIntent, Output, TResult};
 use rust_releases::semver;
+use std::convert::TryFrom;
 use std::io::Write;
 use toml_edit::{value, Document, Item};
 
@@ -12,6 +13,16 @@ const RUST_VERSION_SUPPORTED_SINCE: semver::Version = semver::Version::new(1, 56
 pub fn run_set_msrv<R: Output>(config: &Config, output: &R) -> TResult<()> {
     output.mode(ModeIntent::Show);
 
+    let msrv = &config.sub_command_config().set().msrv;
+    write_msrv(config, msrv)?;
+
+    output.finish_success(ModeIntent::Set, None);
+
+    Ok(())
+}
+
+/// Write the given MSRV to the Cargo manifest of the crate, replacing the current MSRV, if any.
+pub fn write_msrv(config: &Config, msrv: &BareVersion) -> TResult<SetReport> {
     let crate_folder = crate_root_folder(config)?;
     let cargo_toml = crate_folder.join("Cargo.toml");
 
@@ -21,7 +32,9 @@ pub fn run_set_msrv<R: Output>(config: &Config, output: &R) -> TResult<()> {
     })?;
 
     let mut manifest = CargoManifestParser::default().parse::<Document>(&contents)?;
-    let msrv = &config.sub_command_config().set().msrv;
+    let previous = CargoManifest::try_from(manifest.clone())?
+        .minimum_rust_version()
+        .cloned();
 
     set_msrv(&mut manifest, msrv);
 
@@ -39,9 +52,29 @@ pub fn run_set_msrv<R: Output>(config: &Config, output: &R) -> TResult<()> {
         source: IoErrorSource::WriteFile(cargo_toml.clone()),
     })?;
 
-    output.finish_success(ModeIntent::Set, None);
+    Ok(SetReport {
+        previous,
+        msrv: msrv.clone(),
+    })
+}
 
-    Ok(())
+/// The MSRV written to a Cargo manifest, and the MSRV it replaced.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SetReport {
+    previous: Option<BareVersion>,
+    msrv: BareVersion,
+}
+
+impl SetReport {
+    /// The MSRV specified in the Cargo manifest before it was replaced, if any.
+    pub fn previous(&self) -> Option<&BareVersion> {
+        self.previous.as_ref()
+    }
+
+    /// The MSRV which was written to the Cargo manifest.
+    pub fn msrv(&self) -> &BareVersion {
+        &self.msrv
+    }
 }
 
 fn set_msrv(manifest: &mut Document, msrv: &BareVersion) {
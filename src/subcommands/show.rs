@@ -10,6 +10,18 @@ use toml_edit::Document;
 pub fn run_show_msrv<R: Output>(config: &Config, output: &R) -> TResult<()> {
     output.mode(ModeIntent::Show);
 
+    let report = show_msrv(config)?;
+
+    match report.msrv() {
+        Some(msrv) => output.finish_success(ModeIntent::Show, Some(&msrv.to_semver_version())),
+        None => output.finish_failure(ModeIntent::Show, None),
+    }
+
+    Ok(())
+}
+
+/// Read the MSRV of the crate from its Cargo manifest.
+pub fn show_msrv(config: &Config) -> TResult<ShowReport> {
     let crate_folder = crate_root_folder(config)?;
     let cargo_toml = crate_folder.join("Cargo.toml");
 
@@ -21,15 +33,20 @@ pub fn run_show_msrv<R: Output>(config: &Config, output: &R) -> TResult<()> {
     let manifest = CargoManifestParser::default().parse::<Document>(&contents)?;
     let manifest = CargoManifest::try_from(manifest)?;
 
-    let msrv = manifest.minimum_rust_version();
-    if msrv.is_some() {
-        output.finish_success(
-            ModeIntent::Show,
-            msrv.map(BareVersion::to_semver_version).as_ref(),
-        );
-    } else {
-        output.finish_failure(ModeIntent::Show, None);
-    }
+    Ok(ShowReport {
+        msrv: manifest.minimum_rust_version().cloned(),
+    })
+}
 
-    Ok(())
+/// The MSRV of a crate, as specified in its Cargo manifest.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShowReport {
+    msrv: Option<BareVersion>,
+}
+
+impl ShowReport {
+    /// The MSRV, or `None` if the Cargo manifest does not specify one.
+    pub fn msrv(&self) -> Option<&BareVersion> {
+        self.msrv.as_ref()
+    }
 }
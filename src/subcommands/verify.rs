@@ -1,16 +1,20 @@
 use std::convert::TryFrom;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use rust_releases::{semver, Release, ReleaseIndex};
 use toml_edit::Document;
 
-use crate::check::{Check, RunCheck};
+use crate::cache::CheckCache;
+use crate::check::{CachedCheck, Check, RunCheck};
 use crate::config::{Config, ModeIntent};
+use crate::dependencies::msrv_lockfile::resolve_msrv_lockfile;
+use crate::dependencies::registry_index::RegistryIndex;
+use crate::dependencies::resolver::{CargoMetadataResolver, DependencyResolver};
 use crate::errors::{CargoMSRVError, IoErrorSource, TResult};
 use crate::manifest::{CargoManifest, CargoManifestParser, TomlParser};
 use crate::outcome::Outcome;
 use crate::paths::crate_root_folder;
-use crate::reporter::Output;
+use crate::reporter::{write_report, Output};
 use crate::toolchain::ToolchainSpec;
 
 // NB: only public for integration testing
@@ -19,6 +23,118 @@ pub fn run_verify_msrv_action<R: Output>(
     reporter: &R,
     release_index: &ReleaseIndex,
 ) -> TResult<()> {
+    let resolved_lockfile = match &config.sub_command_config().verify().resolve_lockfile {
+        Some(registry_index) => Some(resolve_lockfile(
+            config,
+            reporter,
+            release_index,
+            registry_index,
+        )?),
+        None => None,
+    };
+
+    let report = verify_msrv(config, reporter, release_index)?;
+
+    // keep the resolved lockfile only when the crate can be built with it, otherwise the original
+    // lockfile is restored when the guard is dropped
+    if let Some(guard) = resolved_lockfile {
+        if report.is_satisfied() {
+            guard.keep();
+        }
+    }
+
+    let cmd = config.check_command_string();
+    report_verify_completion(reporter, &report, &cmd);
+
+    if report.is_satisfied() {
+        Ok(())
+    } else {
+        Err(CargoMSRVError::SubCommandVerify(Error::VerifyFailed {
+            expected_msrv: report.msrv,
+            manifest: report.manifest,
+        }))
+    }
+}
+
+/// Downgrade the locked dependencies which require a newer Rust version than the MSRV. The
+/// original lockfile is restored when the returned guard is dropped, unless it's kept.
+fn resolve_lockfile<R: Output>(
+    config: &Config,
+    reporter: &R,
+    release_index: &ReleaseIndex,
+    registry_index: &Path,
+) -> TResult<RestoreLockfile> {
+    let (msrv, _) = manifest_msrv(config, release_index)?;
+
+    let crate_folder = crate_root_folder(config)?;
+    let lockfile = crate_folder
+        .ancestors()
+        .map(|folder| folder.join("Cargo.lock"))
+        .find(|lockfile| lockfile.is_file())
+        .ok_or_else(|| {
+            CargoMSRVError::GenericMessage(format!(
+                "Unable to find a Cargo.lock lockfile for '{}', run `cargo generate-lockfile` to create one",
+                crate_folder.display()
+            ))
+        })?;
+
+    let contents = std::fs::read_to_string(&lockfile).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFile(lockfile.clone()),
+    })?;
+    let guard = RestoreLockfile {
+        lockfile,
+        contents,
+        keep: false,
+    };
+
+    let graph = CargoMetadataResolver::try_from_config(config)?.resolve()?;
+    let resolution = resolve_msrv_lockfile(
+        &graph,
+        &guard.lockfile,
+        &msrv,
+        &RegistryIndex::new(registry_index),
+    )?;
+
+    write_report(&resolution, config, reporter);
+
+    Ok(guard)
+}
+
+/// Writes the original contents of a lockfile back when dropped, so the lockfile is also restored
+/// when verifying fails with an error.
+struct RestoreLockfile {
+    lockfile: PathBuf,
+    contents: String,
+    keep: bool,
+}
+
+impl RestoreLockfile {
+    /// Keep the resolved lockfile, instead of restoring the original.
+    fn keep(mut self) {
+        self.keep = true;
+    }
+}
+
+impl Drop for RestoreLockfile {
+    fn drop(&mut self) {
+        if !self.keep {
+            if let Err(error) = std::fs::write(&self.lockfile, &self.contents) {
+                eprintln!(
+                    "Unable to restore the lockfile '{}': {}",
+                    self.lockfile.display(),
+                    error
+                );
+            }
+        }
+    }
+}
+
+/// The MSRV specified in the Cargo manifest of the crate, and the path of this manifest.
+fn manifest_msrv(
+    config: &Config,
+    release_index: &ReleaseIndex,
+) -> TResult<(semver::Version, PathBuf)> {
     let crate_folder = crate_root_folder(config)?;
     let cargo_toml = crate_folder.join("Cargo.toml");
 
@@ -35,32 +151,76 @@ pub fn run_verify_msrv_action<R: Output>(
         .ok_or_else(|| CargoMSRVError::NoMSRVKeyInCargoToml(cargo_toml.to_owned()))?;
     let version = version.try_to_semver(release_index.releases().iter().map(Release::version))?;
 
-    let cmd = config.check_command_string();
+    Ok((version.to_owned(), cargo_toml))
+}
+
+/// Check whether the crate is compatible with the MSRV specified in its Cargo manifest.
+pub fn verify_msrv<R: Output>(
+    config: &Config,
+    reporter: &R,
+    release_index: &ReleaseIndex,
+) -> TResult<VerifyReport> {
+    let (version, cargo_toml) = manifest_msrv(config, release_index)?;
+
     reporter.mode(ModeIntent::Verify);
 
     let runner = RunCheck::new(reporter);
-    let toolchain = ToolchainSpec::new(version, config.target());
-    let status = runner.check(config, &toolchain)?;
-    report_verify_completion(reporter, &status, &cmd);
-
-    if status.is_success() {
-        Ok(())
+    let toolchain = ToolchainSpec::new(&version, config.target());
+    let status = if config.no_cache() {
+        runner.check(config, &toolchain)?
     } else {
-        Err(CargoMSRVError::SubCommandVerify(Error::VerifyFailed {
-            expected_msrv: version.to_owned(),
-            manifest: cargo_toml,
-        }))
-    }
+        let cache = CheckCache::from_config(config)?;
+        CachedCheck::new(runner, cache, reporter).check(config, &toolchain)?
+    };
+
+    Ok(VerifyReport {
+        msrv: version,
+        manifest: cargo_toml,
+        outcome: status,
+    })
 }
 
-fn report_verify_completion(output: &impl Output, outcome: &Outcome, cmd: &str) {
-    if outcome.is_success() {
-        output.finish_success(ModeIntent::Verify, Some(outcome.version()));
+fn report_verify_completion(output: &impl Output, report: &VerifyReport, cmd: &str) {
+    if report.is_satisfied() {
+        output.finish_success(ModeIntent::Verify, Some(report.msrv()));
     } else {
         output.finish_failure(ModeIntent::Verify, Some(cmd));
     }
 }
 
+/// Whether the crate is compatible with the MSRV specified in its Cargo manifest.
+#[derive(Clone, Debug)]
+pub struct VerifyReport {
+    msrv: semver::Version,
+    manifest: PathBuf,
+    outcome: Outcome,
+}
+
+impl VerifyReport {
+    /// The MSRV which was checked.
+    pub fn msrv(&self) -> &semver::Version {
+        &self.msrv
+    }
+
+    /// The Cargo manifest in which the MSRV is specified.
+    pub fn manifest(&self) -> &Path {
+        &self.manifest
+    }
+
+    /// Whether the check command succeeded for the MSRV.
+    pub fn is_satisfied(&self) -> bool {
+        self.outcome.is_success()
+    }
+
+    /// The error output of the check command, if it failed.
+    pub fn error_message(&self) -> Option<&str> {
+        match &self.outcome {
+            Outcome::Success(_) => None,
+            Outcome::Failure(failure) => Some(&failure.error_message),
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(
@@ -1,5 +1,18 @@
 use once_cell::unsync::OnceCell;
 use rust_releases::semver;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use crate::config::ReleaseChannel;
+use crate::release_index::{date_from_days, days_from_date, Days};
+
+/// The release date of Rust 1.0, in days since the Unix epoch.
+pub(crate) const RUST_1_0: Days = 16_570;
+
+/// Stable releases are published every six weeks, on a Thursday. The release schedule is counted
+/// from the Thursday before the release of Rust 1.0, which was released on a Friday.
+const RELEASE_SCHEDULE_START: Days = RUST_1_0 - 1;
+const RELEASE_CYCLE: Days = 42;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ToolchainSpec<'spec> {
@@ -68,5 +81,166 @@ impl std::fmt::Display for OwnedToolchainSpec {
 }
 
 fn make_toolchain_spec(version: &semver::Version, target: &str) -> String {
-    format!("{}-{}", version, target)
+    format!("{}-{}", toolchain_name(version), target)
+}
+
+/// The name by which rustup refers to the toolchain of the given version, without a target.
+///
+/// This is the version itself for stable releases, e.g. `1.56.1`, and the channel and release
+/// date for dated toolchains, e.g. `nightly-2023-04-12`.
+pub fn toolchain_name(version: &semver::Version) -> String {
+    match DatedToolchain::from_version(version) {
+        Some(toolchain) => toolchain.to_string(),
+        None => version.to_string(),
+    }
+}
+
+/// A toolchain of the beta or nightly channel, identified by its release date, like
+/// `nightly-2023-04-12`.
+///
+/// Dated toolchains are represented by a pre-release version, so they can be searched like stable
+/// releases. The version consists of the Rust version under development on the channel at the
+/// release date, followed by the channel and the date, e.g. `1.70.0-nightly.2023-04-12`. Since
+/// the dates sort lexicographically, the toolchains of a channel are ordered by release date.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DatedToolchain {
+    channel: ReleaseChannel,
+    days: Days,
+}
+
+impl DatedToolchain {
+    /// The toolchain released on the given day, or `None` for the stable channel, which is
+    /// identified by versions instead.
+    pub fn new(channel: ReleaseChannel, days: Days) -> Option<Self> {
+        match channel {
+            ReleaseChannel::Stable => None,
+            ReleaseChannel::Beta | ReleaseChannel::Nightly => Some(Self { channel, days }),
+        }
+    }
+
+    /// The dated toolchain represented by the given version, if it is one.
+    pub fn from_version(version: &semver::Version) -> Option<Self> {
+        let (channel, date) = version.pre.as_str().split_once('.')?;
+
+        Self::new(
+            ReleaseChannel::try_from(channel).ok()?,
+            days_from_date(date)?,
+        )
+    }
+
+    pub fn to_version(self) -> semver::Version {
+        let mut version = semver::Version::new(1, self.minor_version(), 0);
+        version.pre = semver::Prerelease::new(&format!(
+            "{}.{}",
+            Into::<&'static str>::into(self.channel),
+            date_from_days(self.days)
+        ))
+        .expect("channel and date are valid pre-release identifiers");
+
+        version
+    }
+
+    /// The minor version of the Rust release under development on the channel at the release
+    /// date. Since a new version is branched off at each stable release, the beta channel is one,
+    /// and the nightly channel two minor versions ahead of the stable channel.
+    fn minor_version(self) -> u64 {
+        let stable = (self.days - RELEASE_SCHEDULE_START).max(0) / RELEASE_CYCLE;
+        let ahead = match self.channel {
+            ReleaseChannel::Stable => 0,
+            ReleaseChannel::Beta => 1,
+            ReleaseChannel::Nightly => 2,
+        };
+
+        (stable + ahead) as u64
+    }
+}
+
+impl std::fmt::Display for DatedToolchain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            Into::<&'static str>::into(self.channel),
+            date_from_days(self.days)
+        )
+    }
+}
+
+impl FromStr for DatedToolchain {
+    type Err = String;
+
+    /// Parses toolchains like `nightly-2023-04-12`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Expected a dated toolchain like 'nightly-2023-04-12', got '{}'",
+                s
+            )
+        };
+        let (channel, date) = s.split_once('-').ok_or_else(invalid)?;
+        let channel = ReleaseChannel::try_from(channel).map_err(|_| invalid())?;
+        let days = days_from_date(date).ok_or_else(invalid)?;
+
+        Self::new(channel, days).ok_or_else(invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{toolchain_name, DatedToolchain, ToolchainSpec};
+    use crate::config::ReleaseChannel;
+    use crate::semver::Version;
+    use std::str::FromStr;
+
+    #[yare::parameterized(
+        first_nightly = { "nightly-2015-05-15", "1.2.0-nightly.2015-05-15" },
+        nightly = { "nightly-2023-04-12", "1.70.0-nightly.2023-04-12" },
+        beta = { "beta-2023-04-12", "1.69.0-beta.2023-04-12" },
+        nightly_on_release_day = { "nightly-2021-10-21", "1.58.0-nightly.2021-10-21" },
+        nightly_before_release_day = { "nightly-2021-10-20", "1.57.0-nightly.2021-10-20" },
+    )]
+    fn dated_toolchain_version(toolchain: &str, version: &str) {
+        let dated = DatedToolchain::from_str(toolchain).unwrap();
+        let version = Version::parse(version).unwrap();
+
+        assert_eq!(dated.to_version(), version);
+        assert_eq!(DatedToolchain::from_version(&version), Some(dated));
+        assert_eq!(toolchain_name(&version), toolchain);
+    }
+
+    #[yare::parameterized(
+        stable = { "stable-2023-04-12" },
+        no_date = { "nightly" },
+        invalid_date = { "nightly-2023-04" },
+        unknown_channel = { "dev-2023-04-12" },
+    )]
+    fn invalid_dated_toolchain(toolchain: &str) {
+        assert!(DatedToolchain::from_str(toolchain).is_err());
+    }
+
+    #[test]
+    fn dated_toolchains_are_ordered_by_date() {
+        let toolchain = |s| DatedToolchain::from_str(s).unwrap().to_version();
+
+        assert!(toolchain("nightly-2023-04-12") < toolchain("nightly-2023-04-13"));
+        assert!(toolchain("nightly-2023-02-28") < toolchain("nightly-2023-03-01"));
+        assert!(toolchain("nightly-2023-04-12") < Version::new(1, 70, 0));
+    }
+
+    #[yare::parameterized(
+        stable = { "1.56.1", "1.56.1-x86_64-unknown-linux-gnu" },
+        nightly = { "1.70.0-nightly.2023-04-12", "nightly-2023-04-12-x86_64-unknown-linux-gnu" },
+    )]
+    fn toolchain_spec(version: &str, expected: &str) {
+        let version = Version::parse(version).unwrap();
+        let spec = ToolchainSpec::new(&version, "x86_64-unknown-linux-gnu");
+
+        assert_eq!(spec.spec(), expected);
+    }
+
+    #[test]
+    fn stable_versions_are_not_dated() {
+        assert!(DatedToolchain::from_version(&Version::new(1, 70, 0)).is_none());
+        assert!(DatedToolchain::new(ReleaseChannel::Stable, 0).is_none());
+    }
 }
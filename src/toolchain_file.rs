@@ -1,5 +1,11 @@
+use std::path::PathBuf;
+
+use toml_edit::{value, Document};
+
 use crate::errors::IoErrorSource;
+use crate::manifest::bare_version::BareVersion;
 use crate::paths::crate_root_folder;
+use crate::toolchain::toolchain_name;
 use crate::{semver, CargoMSRVError, Config, TResult};
 
 const TOOLCHAIN_FILE: &str = "rust-toolchain";
@@ -28,7 +34,7 @@ pub fn write_toolchain_file(config: &Config, stable_version: &semver::Version) -
         r#"[toolchain]
 channel = "{}"
 "#,
-        stable_version
+        toolchain_name(stable_version)
     );
 
     std::fs::write(&path, content).map_err(|error| CargoMSRVError::Io {
@@ -39,3 +45,99 @@ channel = "{}"
 
     Ok(())
 }
+
+/// Raise the Rust version pinned by an existing rust-toolchain(.toml) file to the given MSRV.
+///
+/// Only toolchain files which pin a Rust version older than the MSRV are updated, since the pinned
+/// toolchain would no longer be able to build the crate. Toolchain files which pin a channel,
+/// like `stable`, are left untouched.
+///
+/// Returns the path of the toolchain file if it was updated.
+pub fn raise_toolchain_file_version(
+    config: &Config,
+    msrv: &BareVersion,
+) -> TResult<Option<PathBuf>> {
+    let path_prefix = crate_root_folder(config)?;
+
+    let path = [TOOLCHAIN_FILE_TOML, TOOLCHAIN_FILE]
+        .iter()
+        .map(|file| path_prefix.join(file))
+        .find(|path| path.is_file());
+
+    let path = match path {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+
+    let contents = std::fs::read_to_string(&path).map_err(|error| CargoMSRVError::Io {
+        error,
+        source: IoErrorSource::ReadFile(path.clone()),
+    })?;
+
+    match raise_channel(&contents, msrv) {
+        Some(contents) => {
+            std::fs::write(&path, contents).map_err(|error| CargoMSRVError::Io {
+                error,
+                source: IoErrorSource::WriteFile(path.clone()),
+            })?;
+
+            Ok(Some(path))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Returns the updated contents of a toolchain file, if it pins a Rust version older than the MSRV.
+///
+/// Toolchain files are either TOML files with a `toolchain.channel` key, or, for the legacy
+/// `rust-toolchain` file, consist of just the channel.
+fn raise_channel(contents: &str, msrv: &BareVersion) -> Option<String> {
+    let is_older = |channel: &str| {
+        channel
+            .parse::<BareVersion>()
+            .map(|version| version.to_semver_version() < msrv.to_semver_version())
+            .unwrap_or(false)
+    };
+
+    match contents.parse::<Document>() {
+        Ok(mut document) => {
+            let channel = document
+                .get("toolchain")
+                .and_then(|toolchain| toolchain.get("channel"))
+                .and_then(|channel| channel.as_str())?;
+
+            if is_older(channel) {
+                document["toolchain"]["channel"] = value(msrv.to_string());
+                Some(document.to_string())
+            } else {
+                None
+            }
+        }
+        Err(_) if is_older(contents.trim()) => Some(format!("{}\n", msrv)),
+        Err(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::raise_channel;
+    use crate::manifest::bare_version::BareVersion;
+
+    #[yare::parameterized(
+        toml = { "[toolchain]\nchannel = \"1.56.0\"\n", Some("[toolchain]\nchannel = \"1.65\"\n") },
+        toml_with_components = {
+            "[toolchain]\nchannel = \"1.56\"\ncomponents = [\"clippy\"]\n",
+            Some("[toolchain]\nchannel = \"1.65\"\ncomponents = [\"clippy\"]\n")
+        },
+        legacy = { "1.56.1\n", Some("1.65\n") },
+        toml_newer = { "[toolchain]\nchannel = \"1.70.0\"\n", None },
+        legacy_newer = { "1.70", None },
+        toml_stable = { "[toolchain]\nchannel = \"stable\"\n", None },
+        legacy_nightly = { "nightly\n", None },
+    )]
+    fn raise(contents: &str, expected: Option<&str>) {
+        let msrv = BareVersion::TwoComponents(1, 65);
+
+        assert_eq!(raise_channel(contents, &msrv).as_deref(), expected);
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::{Path, PathBuf};
+
+use cargo_metadata::MetadataCommand;
+use serde::Serialize;
+
+use crate::config::{Config, ConfigBuilder};
+use crate::errors::TResult;
+use crate::msrv_per_key::{MsrvKey, MsrvPerKey};
+use crate::paths::crate_root_folder;
+
+/// A package which is a member of a Cargo workspace.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct WorkspaceMember {
+    name: String,
+    path: PathBuf,
+}
+
+impl WorkspaceMember {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The folder which contains the Cargo manifest of this member.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Find the members of the workspace located at the crate root, ordered by name.
+pub(crate) fn workspace_members(config: &Config) -> TResult<Vec<WorkspaceMember>> {
+    let crate_root = crate_root_folder(config)?;
+
+    let metadata = MetadataCommand::new()
+        .manifest_path(crate_root.join("Cargo.toml"))
+        .no_deps()
+        .exec()?;
+
+    let workspace_members = metadata.workspace_members;
+
+    let mut members = metadata
+        .packages
+        .into_iter()
+        .filter(|package| workspace_members.contains(&package.id))
+        .map(|package| {
+            let manifest_path = PathBuf::from(package.manifest_path);
+            let path = manifest_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or(manifest_path);
+
+            WorkspaceMember {
+                name: package.name,
+                path,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    members.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+
+    Ok(members)
+}
+
+impl MsrvKey for WorkspaceMember {
+    const HEADER: &'static str = "Member";
+    const ALL: &'static str = "the workspace";
+    const REASON: &'static str = "workspace-msrv-complete";
+    const FIELD: &'static str = "members";
+
+    fn keys(config: &Config) -> TResult<Vec<Self>> {
+        workspace_members(config)
+    }
+
+    /// Runs the check command from the folder of the member.
+    fn config(&self, config: &Config) -> Config {
+        ConfigBuilder::from_config(config)
+            .crate_path(Some(self.path()))
+            .build()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// The minimal compatibility of each member of a workspace.
+pub type WorkspaceCompatibility = MsrvPerKey<WorkspaceMember>;
+
+#[cfg(test)]
+mod tests {
+    use super::{workspace_members, WorkspaceCompatibility, WorkspaceMember};
+    use crate::config::{ConfigBuilder, ModeIntent};
+    use crate::formatter::{FormatUserOutput, Json};
+    use crate::reporter::json::schema::assert_conforms;
+    use crate::result::MinimalCompatibility;
+    use std::path::PathBuf;
+
+    #[test]
+    fn members_of_virtual_workspace() {
+        let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("virtual-workspace");
+
+        let config = ConfigBuilder::new(ModeIntent::Find, "")
+            .crate_path(Some(&fixture))
+            .build();
+
+        let members = workspace_members(&config).unwrap();
+        let names = members.iter().map(|m| m.name()).collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["a", "b"]);
+        assert!(members[0].path().ends_with("a"));
+        assert!(members[1].path().ends_with("b"));
+    }
+
+    #[test]
+    fn json_output() {
+        let compatibility = WorkspaceCompatibility::new(vec![(
+            WorkspaceMember {
+                name: "a".to_string(),
+                path: PathBuf::from("a"),
+            },
+            MinimalCompatibility::NoCompatibleToolchains,
+        )]);
+
+        let output = assert_conforms(&FormatUserOutput::<Json>::format_line(&compatibility));
+
+        assert_eq!(output["reason"], "workspace-msrv-complete");
+        assert_eq!(output["success"], false);
+        assert_eq!(output["members"][0]["name"], "a");
+        assert_eq!(output["members"][0]["path"], "a");
+        assert!(output["members"][0]["msrv"].is_null());
+    }
+}
@@ -61,6 +61,38 @@ fn msrv_using_bisect_method(folder: &str, expected_version: semver::Version) {
     assert_eq!(actual_version, expected_version);
 }
 
+#[parameterized(
+    folder = {
+        "1.35.0",
+        "1.36.0",
+        "1.37.0",
+        "1.38.0",
+    },
+    expected_version = {
+        semver::Version::new(1,35,0),
+        semver::Version::new(1,36,0),
+        semver::Version::new(1,37,0),
+        semver::Version::new(1,38,0),
+    }
+)]
+fn msrv_using_parallel_method(folder: &str, expected_version: semver::Version) {
+    let folder = fixtures_path().join(folder);
+
+    let with_args = vec![
+        "cargo-msrv",
+        "--parallel",
+        "--jobs",
+        "3",
+        "--path",
+        folder.to_str().unwrap(),
+    ];
+
+    let result = run_msrv(with_args);
+    let actual_version = result.to_version();
+
+    assert_eq!(actual_version, expected_version);
+}
+
 #[test]
 fn msrv_unsupported() {
     let folder = fixtures_path().join("unbuildable");
@@ -0,0 +1,4 @@
+#[cfg(feature = "std")]
+pub fn hello() -> String {
+    String::from("hello")
+}